@@ -28,6 +28,7 @@ use vector::{
 mod batch;
 mod buffering;
 mod event;
+mod event_batches;
 mod files;
 mod http;
 mod lua;
@@ -53,6 +54,7 @@ criterion_main!(
     files::files,
     lua::lua,
     event::event,
+    event_batches::event_batches,
 );
 
 fn benchmark_simple_pipe(c: &mut Criterion) {
@@ -710,6 +712,7 @@ fn benchmark_remap(c: &mut Criterion) {
             .copy = .copy_from"#
                 .to_string(),
             drop_on_err: true,
+            reroute_errors: false,
         });
 
         b.iter(add_fields_runner(Box::new(tform.unwrap())))
@@ -752,6 +755,7 @@ fn benchmark_remap(c: &mut Criterion) {
         let tform = Remap::new(RemapConfig {
             mapping: ".bar = parse_json(.foo)".to_owned(),
             drop_on_err: false,
+            reroute_errors: false,
         });
 
         b.iter(json_parser_runner(Box::new(tform.unwrap())))
@@ -806,6 +810,7 @@ fn benchmark_remap(c: &mut Criterion) {
                 "#
             .to_owned(),
             drop_on_err: true,
+            reroute_errors: false,
         })
         .unwrap();
 
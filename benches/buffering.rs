@@ -39,6 +39,8 @@ fn benchmark_buffers(c: &mut Criterion) {
                     config.sinks["out"].buffer = BufferConfig::Memory {
                         max_events: 100,
                         when_full: Default::default(),
+                        max_size: None,
+                        max_age: None,
                     };
 
                     let mut rt = runtime();
@@ -80,6 +82,7 @@ fn benchmark_buffers(c: &mut Criterion) {
                     config.sinks["out"].buffer = BufferConfig::Disk {
                         max_size: 1_000_000,
                         when_full: Default::default(),
+                        max_age: None,
                     };
                     config.global.data_dir = Some(data_dir.clone());
                     let mut rt = runtime();
@@ -123,6 +126,7 @@ fn benchmark_buffers(c: &mut Criterion) {
                     config.sinks["out"].buffer = BufferConfig::Disk {
                         max_size: 10_000,
                         when_full: Default::default(),
+                        max_age: None,
                     };
                     config.global.data_dir = Some(data_dir2.clone());
                     let mut rt = runtime();
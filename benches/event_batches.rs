@@ -0,0 +1,88 @@
+use criterion::{criterion_group, Benchmark, Criterion, Throughput};
+use futures::compat::Future01CompatExt;
+use futures01::{stream, sync::mpsc, Future, Sink, Stream};
+use vector::{
+    event::{batch::ready_batches, Event},
+    test_util::{random_lines, runtime},
+    transforms::Transform,
+};
+
+/// A transform cheap enough for the cost of moving events around to show.
+struct Mark;
+
+impl Transform for Mark {
+    fn transform(&mut self, mut event: Event) -> Option<Event> {
+        event.as_mut_log().insert("marked", true);
+        Some(event)
+    }
+}
+
+fn events(num_events: usize) -> Vec<Event> {
+    random_lines(100)
+        .take(num_events)
+        .map(Event::from)
+        .collect()
+}
+
+/// Passes `events` one at a time through `hops` transforms, each running in
+/// its own task behind a channel, and counts what comes out.
+fn per_event(events: Vec<Event>, hops: usize) -> impl Future<Item = usize, Error = ()> {
+    let mut rx: Box<dyn Stream<Item = Event, Error = ()> + Send> =
+        Box::new(stream::iter_ok(events));
+    for _ in 0..hops {
+        let (tx, next) = mpsc::channel(100);
+        let output = Box::new(Mark).transform_stream(rx);
+        tokio::spawn(output.forward(tx.sink_map_err(drop)).compat());
+        rx = Box::new(next);
+    }
+    rx.fold(0, |count, _| Ok(count + 1))
+}
+
+/// Same as `per_event`, with the events passed in batches.
+fn batched(events: Vec<Event>, hops: usize) -> impl Future<Item = usize, Error = ()> {
+    let mut rx: Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send> =
+        Box::new(ready_batches(stream::iter_ok(events)));
+    for _ in 0..hops {
+        let (tx, next) = mpsc::channel(100);
+        let output = Box::new(Mark).transform_batches(rx);
+        tokio::spawn(output.forward(tx.sink_map_err(drop)).compat());
+        rx = Box::new(next);
+    }
+    rx.fold(0, |count, batch| Ok(count + batch.len()))
+}
+
+fn benchmark_event_batches(c: &mut Criterion) {
+    let num_events: usize = 100_000;
+    let hops: usize = 3;
+
+    c.bench(
+        "event_batches",
+        Benchmark::new("per_event", move |b| {
+            b.iter_with_setup(
+                || (runtime(), events(num_events)),
+                |(mut rt, events)| {
+                    let count = rt
+                        .block_on(async move { per_event(events, hops).compat().await })
+                        .unwrap();
+                    assert_eq!(count, num_events);
+                },
+            );
+        })
+        .with_function("batched", move |b| {
+            b.iter_with_setup(
+                || (runtime(), events(num_events)),
+                |(mut rt, events)| {
+                    let count = rt
+                        .block_on(async move { batched(events, hops).compat().await })
+                        .unwrap();
+                    assert_eq!(count, num_events);
+                },
+            );
+        })
+        .sample_size(10)
+        .noise_threshold(0.05)
+        .throughput(Throughput::Elements(num_events as u64)),
+    );
+}
+
+criterion_group!(event_batches, benchmark_event_batches);
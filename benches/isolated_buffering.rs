@@ -6,7 +6,7 @@ use futures::{
 use futures01::{stream, AsyncSink, Poll, Sink, StartSend, Stream};
 use tempfile::tempdir;
 use vector::{
-    buffers::disk::{leveldb_buffer, DiskBuffer},
+    buffers::disk::{segment_buffer, DiskBuffer},
     sinks::util::StreamSinkOld,
     test_util::runtime,
     Event,
@@ -88,7 +88,7 @@ fn benchmark_buffers(c: &mut Criterion) {
                 },
             );
         })
-        .with_function("leveldb/writing", move |b| {
+        .with_function("disk/writing", move |b| {
             b.iter_with_setup(
                 || {
                     let rt = runtime();
@@ -102,7 +102,7 @@ fn benchmark_buffers(c: &mut Criterion) {
 
                     let plenty_of_room = num_lines * line_size * 2;
                     let (writer, _reader, _acker) =
-                        leveldb_buffer::Buffer::build(path, plenty_of_room).unwrap();
+                        segment_buffer::Buffer::build(path, plenty_of_room).unwrap();
 
                     (rt, writer)
                 },
@@ -113,7 +113,7 @@ fn benchmark_buffers(c: &mut Criterion) {
                 },
             );
         })
-        .with_function("leveldb/reading", move |b| {
+        .with_function("disk/reading", move |b| {
             b.iter_with_setup(
                 || {
                     let mut rt = runtime();
@@ -127,7 +127,7 @@ fn benchmark_buffers(c: &mut Criterion) {
 
                     let plenty_of_room = num_lines * line_size * 2;
                     let (writer, reader, acker) =
-                        leveldb_buffer::Buffer::build(path, plenty_of_room).unwrap();
+                        segment_buffer::Buffer::build(path, plenty_of_room).unwrap();
 
                     let send = writer.send_all(random_events(line_size).take(num_lines as u64));
                     let write_handle = rt.spawn(send.compat());
@@ -144,7 +144,7 @@ fn benchmark_buffers(c: &mut Criterion) {
                 },
             );
         })
-        .with_function("leveldb/both", move |b| {
+        .with_function("disk/both", move |b| {
             b.iter_with_setup(
                 || {
                     let rt = runtime();
@@ -158,7 +158,7 @@ fn benchmark_buffers(c: &mut Criterion) {
 
                     let plenty_of_room = num_lines * line_size * 2;
                     let (writer, reader, acker) =
-                        leveldb_buffer::Buffer::build(path, plenty_of_room).unwrap();
+                        segment_buffer::Buffer::build(path, plenty_of_room).unwrap();
 
                     let read_loop = StreamSinkOld::new(NullSink, acker).send_all(reader);
 
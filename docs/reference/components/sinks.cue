@@ -62,6 +62,16 @@ components: sinks: [Name=string]: {
 				type: object: {
 					examples: []
 					options: {
+						max_age: {
+							common:      false
+							description: "The maximum age of the events in the buffer, based on their timestamp. Older events are dropped instead of being sent. Events without a timestamp never expire."
+							required:    false
+							type: uint: {
+								default: null
+								examples: [86400]
+								unit: "seconds"
+							}
+						}
 						max_events: {
 							common:        true
 							description:   "The maximum number of [events][docs.data-model] allowed in the buffer."
@@ -73,9 +83,9 @@ components: sinks: [Name=string]: {
 							}
 						}
 						max_size: {
-							description:   "The maximum size of the buffer on the disk."
-							required:      true
-							relevant_when: "type = \"disk\""
+							description:   "The maximum size of the buffer on the disk. Required for disk buffers and for memory buffers that overflow to disk."
+							required:      false
+							relevant_when: "type = \"disk\" or when_full = \"overflow\""
 							type: uint: {
 								examples: [104900000]
 								unit: "bytes"
@@ -102,6 +112,7 @@ components: sinks: [Name=string]: {
 								enum: {
 									block:       "Applies back pressure when the buffer is full. This prevents data loss, but will cause data to pile up on the edge."
 									drop_newest: "Drops new data as it's received. This data is lost. This should be used when performance is the highest priority."
+									overflow:    "Writes new data to a disk buffer in the `data_dir` while the memory buffer is full, and reads it back in order once the sink catches up. Only applies to memory buffers."
 								}
 							}
 						}
@@ -183,6 +194,27 @@ components: sinks: [Name=string]: {
 			}
 		}
 
+		dead_letter: {
+			common:      false
+			description: "Exposes the events this sink permanently fails to deliver as an output named `<sink>.dead_letter`, which other components can use as an input, including the events it can't encode. The events get the fields `dead_letter.reason`, `dead_letter.sink` and `dead_letter.attempts` added."
+			required:    false
+			type: bool: default: false
+		}
+
+		isolation: {
+			common:      false
+			description: "What the components feeding this sink do once its buffer has no room for more events. Isolating the sink keeps a stalled sink from holding up the other sinks fed by the same components."
+			required:    false
+			type: string: {
+				default: "none"
+				enum: {
+					none:   "Wait for the sink, holding up the other outputs of the components feeding it."
+					skip:   "Drop the events the sink has no room for, for this sink only."
+					detach: "Detach the sink, reporting it, and drop the events it misses until it catches up."
+				}
+			}
+		}
+
 		if sinks[Name].features.send != _|_ {
 			if sinks[Name].features.healthcheck.enabled {
 				healthcheck: {
@@ -65,6 +65,12 @@ components: sources: file: {
 	}
 
 	configuration: {
+		acknowledgements: {
+			common:      false
+			description: "If `true`, a file is only checkpointed past a line once its event has been delivered by every sink it reaches. Once an event fails to be delivered, the file is no longer checkpointed, so that the line is read again when Vector restarts."
+			required:    false
+			type: bool: default: false
+		}
 		exclude: {
 			common:      false
 			description: "Array of file patterns to exclude. [Globbing](#globbing) is supported.*Takes precedence over the [`include` option](#include).*"
@@ -57,6 +57,12 @@ components: sources: http: {
 	}
 
 	configuration: {
+		acknowledgements: {
+			common:      false
+			description: "If `true`, the source only responds to a request once all of its events have been delivered by every sink they reach, and responds with a `503` if any of them failed."
+			required:    false
+			type: bool: default: false
+		}
 		address: {
 			description: "The address to accept connections on. The address _must_ include a port."
 			required:    true
@@ -32,6 +32,12 @@ components: sources: kafka: {
 	support: components._kafka.support
 
 	configuration: {
+		acknowledgements: {
+			common:      false
+			description: "If `true`, the offset of a message is only stored once its event has been delivered by every sink it reaches. Once an event fails to be delivered, no further offsets of its partition are stored, so that it is read again when Vector restarts."
+			required:    false
+			type: bool: default: false
+		}
 		auto_offset_reset: {
 			common:      false
 			description: "If offsets for consumer group do not exist, set them using this strategy. [librdkafka documentation][urls.librdkafka_config] for `auto.offset.reset` option for explanation."
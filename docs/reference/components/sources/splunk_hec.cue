@@ -59,6 +59,12 @@ components: sources: splunk_hec: {
 	}
 
 	configuration: {
+		acknowledgements: {
+			common:      false
+			description: "If `true`, the source only responds to a request once all of its events have been delivered by every sink they reach, and responds with a `503` if any of them failed."
+			required:    false
+			type: bool: default: false
+		}
 		address: {
 			common:      true
 			description: "The address to accept connections on."
@@ -2,4 +2,23 @@ package metadata
 
 components: transforms: [Name=string]: {
 	kind: "transform"
+
+	configuration: {
+		concurrency: {
+			common:      false
+			description: "The number of instances of this transform processing events in parallel. Only transforms that keep no state between events, such as parsers, support a value above `1`."
+			required:    false
+			type: uint: {
+				default: 1
+				unit:    null
+			}
+		}
+
+		preserve_order: {
+			common:      false
+			description: "When `concurrency` is above `1`, whether events leave the transform in the order they came in. Disabling this can improve throughput when some events take longer to process than others."
+			required:    false
+			type: bool: default: true
+		}
+	}
 }
@@ -0,0 +1,112 @@
+package metadata
+
+components: transforms: enrich: {
+	title: "Enrich"
+
+	classes: {
+		commonly_used: false
+		development:   "beta"
+		egress_method: "stream"
+	}
+
+	features: {
+		shape: {}
+	}
+
+	support: {
+		platforms: {
+			"aarch64-unknown-linux-gnu":  true
+			"aarch64-unknown-linux-musl": true
+			"x86_64-apple-darwin":        true
+			"x86_64-pc-windows-msv":      true
+			"x86_64-unknown-linux-gnu":   true
+			"x86_64-unknown-linux-musl":  true
+		}
+
+		requirements: []
+		warnings: []
+		notices: []
+	}
+
+	configuration: {
+		table: {
+			description: "The name of the enrichment table, from the `enrichment_tables` section of the configuration, to look events up in."
+			required:    true
+			warnings: []
+			type: string: {
+				examples: ["owners"]
+			}
+		}
+		keys: {
+			description: "The columns a record of the table must match, each with the field of the event holding the value to match. Values are compared as strings."
+			required:    true
+			warnings: []
+			type: object: {
+				examples: [
+					{
+						hostname: "host"
+						service:  "labels.service"
+					},
+				]
+				options: {
+					"*": {
+						description: "The field holding the value the column must match."
+						required:    true
+						warnings: []
+						type: string: {}
+					}
+				}
+			}
+		}
+		target: {
+			common:      true
+			description: "The field to insert the matching record into, with a sub-field for each column of the table. Defaults to the name of the table."
+			required:    false
+			warnings: []
+			type: string: {
+				default: null
+				examples: ["owner", "parent.child"]
+			}
+		}
+	}
+
+	input: {
+		logs:    true
+		metrics: null
+	}
+
+	how_it_works: {
+		enrichment_tables: {
+			title: "Enrichment Tables"
+			body: """
+				Enrichment tables are CSV files declared in the `enrichment_tables`
+				section of the configuration, with the names of their columns in
+				their first row:
+
+				```toml
+				[enrichment_tables.owners]
+				  path = "/etc/vector/owners.csv"
+				  delimiter = ","            # optional, default
+				  reload_interval_secs = 10  # optional, default
+				```
+
+				Every column of a table is indexed in memory. Vector checks the file
+				for changes every `reload_interval_secs` seconds, which must be at
+				least 1, and reads it again when it changes, keeping the previous
+				content if the new one can't be read. When the configuration is
+				reloaded, its tables only replace the ones in use once it's running.
+
+				The same tables can be looked up from `remap` mappings with the
+				`get_enrichment_record(table, column, value)` function.
+				"""
+		}
+		unmatched_events: {
+			title: "Unmatched Events"
+			body: """
+				Events without one of the key fields, or matching no record of the
+				table, pass through unchanged. The first matching record is used
+				when several match.
+				"""
+		}
+	}
+}
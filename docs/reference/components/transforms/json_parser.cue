@@ -65,6 +65,13 @@ components: transforms: json_parser: {
 			warnings: []
 			type: bool: default: false
 		}
+		reroute_errors: {
+			common:      false
+			description: "If `true` events with invalid JSON are sent to the `errors` output, which other components can use as the `<transform name>.errors` input. This takes precedence over `drop_invalid`."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
 		target_field: {
 			common:      false
 			description: "If this setting is present, the parsed JSON will be inserted into the log as a sub-object with this name. If a field with the same name already exists, the parser will fail and produce an error."
@@ -91,6 +98,10 @@ components: transforms: json_parser: {
 				discard the event and drop it entirely. Setting it to `false` will keep the
 				event and pass it through. Note that passing through the event could cause
 				problems and violate assumptions about the structure of your event.
+
+				Alternatively, setting `reroute_errors` to `true` sends these events to
+				the `errors` output of the transform, so that they can be handled
+				separately by using `<transform name>.errors` as an input.
 				"""
 		}
 
@@ -48,6 +48,13 @@ components: transforms: remap: {
 				]
 			}
 		}
+		reroute_errors: {
+			common:      false
+			description: "If `true` events that fail to be mapped are sent unchanged to the `errors` output, which other components can use as the `<transform name>.errors` input."
+			required:    false
+			warnings: []
+			type: bool: default: false
+		}
 	}
 
 	input: {
@@ -11,6 +11,7 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, remove_file, File};
 use std::io::{self, Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{self, Duration};
 use tokio::time::delay_for;
 
@@ -40,9 +41,23 @@ where
     pub fingerprinter: Fingerprinter,
     pub oldest_first: bool,
     pub remove_after: Option<Duration>,
+    /// When set, the checkpoints of files don't move as their lines are read,
+    /// but only as the consumer of the lines updates them in this view, e.g.
+    /// once the lines have been acknowledged.
+    pub acknowledged_checkpoints: Option<Arc<CheckpointsView>>,
     pub emitter: E,
 }
 
+/// A line read from a file, along with the position the line ends at in that
+/// file.
+#[derive(Debug)]
+pub struct Line {
+    pub text: Bytes,
+    pub filename: String,
+    pub file_id: FileFingerprint,
+    pub offset: FilePosition,
+}
+
 /// `FileServer` as Source
 ///
 /// The 'run' of `FileServer` performs the cooperative scheduling of reads over
@@ -65,10 +80,10 @@ where
         self,
         mut chans: C,
         mut shutdown: impl Future + Unpin,
-    ) -> Result<Shutdown, <C as Sink<(Bytes, String)>>::Error>
+    ) -> Result<Shutdown, <C as Sink<Line>>::Error>
     where
-        C: Sink<(Bytes, String)> + Unpin,
-        <C as Sink<(Bytes, String)>>::Error: std::error::Error,
+        C: Sink<Line> + Unpin,
+        <C as Sink<Line>>::Error: std::error::Error,
     {
         let mut fingerprint_buffer = Vec::new();
 
@@ -77,7 +92,8 @@ where
         let mut backoff_cap: usize = 1;
         let mut lines = Vec::new();
 
-        let mut checkpointer = Checkpointer::new(&self.data_dir);
+        let checkpoints = self.acknowledged_checkpoints.clone().unwrap_or_default();
+        let mut checkpointer = Checkpointer::new(&self.data_dir, checkpoints);
         checkpointer.read_checkpoints(self.ignore_before);
 
         let mut known_small_files = HashSet::new();
@@ -232,10 +248,12 @@ where
 
                     bytes_read += sz;
 
-                    lines.push((
-                        line,
-                        watcher.path.to_str().expect("not a valid path").to_owned(),
-                    ));
+                    lines.push(Line {
+                        text: line,
+                        filename: watcher.path.to_str().expect("not a valid path").to_owned(),
+                        file_id,
+                        offset: watcher.get_file_position(),
+                    });
 
                     if bytes_read > self.max_read_bytes {
                         maxed_out_reading_single_file = true;
@@ -246,7 +264,9 @@ where
 
                 if bytes_read > 0 {
                     global_bytes_read = global_bytes_read.saturating_add(bytes_read);
-                    checkpointer.set_checkpoint(file_id, watcher.get_file_position());
+                    if self.acknowledged_checkpoints.is_none() {
+                        checkpointer.set_checkpoint(file_id, watcher.get_file_position());
+                    }
                 } else {
                     // Should the file be removed
                     if let Some(grace_period) = self.remove_after {
@@ -324,7 +344,16 @@ where
             };
             futures::pin_mut!(sleep);
             match block_on(select(shutdown, sleep)) {
-                Either::Left((_, _)) => return Ok(Shutdown),
+                Either::Left((_, _)) => {
+                    // Keep the checkpoints that moved since the last pass, e.g.
+                    // as lines were acknowledged.
+                    checkpointer
+                        .write_checkpoints()
+                        .map_err(|error| self.emitter.emit_file_checkpoint_write_failed(error))
+                        .map(|count| self.emitter.emit_file_checkpointed(count))
+                        .ok();
+                    return Ok(Shutdown);
+                }
                 Either::Right((_, future)) => shutdown = future,
             }
             stats.record("sleeping", start.elapsed());
@@ -372,20 +401,37 @@ where
 #[derive(Debug)]
 pub struct Shutdown;
 
+/// The checkpoints of a file server, shared with the consumer of its lines
+/// when checkpoints wait for the lines to be acknowledged.
+#[derive(Debug, Default)]
+pub struct CheckpointsView {
+    checkpoints: Mutex<HashMap<FileFingerprint, FilePosition>>,
+}
+
+impl CheckpointsView {
+    pub fn update(&self, fng: FileFingerprint, pos: FilePosition) {
+        self.checkpoints.lock().unwrap().insert(fng, pos);
+    }
+
+    pub fn get(&self, fng: FileFingerprint) -> Option<FilePosition> {
+        self.checkpoints.lock().unwrap().get(&fng).cloned()
+    }
+}
+
 pub struct Checkpointer {
     directory: PathBuf,
     glob_string: String,
-    checkpoints: HashMap<FileFingerprint, FilePosition>,
+    checkpoints: Arc<CheckpointsView>,
 }
 
 impl Checkpointer {
-    pub fn new(data_dir: &Path) -> Checkpointer {
+    pub fn new(data_dir: &Path, checkpoints: Arc<CheckpointsView>) -> Checkpointer {
         let directory = data_dir.join("checkpoints");
         let glob_string = directory.join("*").to_string_lossy().into_owned();
         Checkpointer {
             directory,
             glob_string,
-            checkpoints: HashMap::new(),
+            checkpoints,
         }
     }
 
@@ -398,20 +444,21 @@ impl Checkpointer {
     }
 
     pub fn set_checkpoint(&mut self, fng: FileFingerprint, pos: FilePosition) {
-        self.checkpoints.insert(fng, pos);
+        self.checkpoints.update(fng, pos);
     }
 
     pub fn get_checkpoint(&self, fng: FileFingerprint) -> Option<FilePosition> {
-        self.checkpoints.get(&fng).cloned()
+        self.checkpoints.get(fng)
     }
 
     pub fn write_checkpoints(&mut self) -> Result<usize, io::Error> {
         fs::remove_dir_all(&self.directory).ok();
         fs::create_dir_all(&self.directory)?;
-        for (&fng, &pos) in self.checkpoints.iter() {
+        let checkpoints = self.checkpoints.checkpoints.lock().unwrap();
+        for (&fng, &pos) in checkpoints.iter() {
             fs::File::create(self.encode(fng, pos))?;
         }
-        Ok(self.checkpoints.len())
+        Ok(checkpoints.len())
     }
 
     pub fn read_checkpoints(&mut self, ignore_before: Option<time::SystemTime>) {
@@ -425,7 +472,7 @@ impl Checkpointer {
                 }
             }
             let (fng, pos) = self.decode(&path);
-            self.checkpoints.insert(fng, pos);
+            self.checkpoints.update(fng, pos);
         }
     }
 }
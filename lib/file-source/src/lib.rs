@@ -9,11 +9,13 @@ mod internal_events;
 mod metadata_ext;
 pub mod paths_provider;
 
-pub use self::file_server::{FileServer, Fingerprinter, Shutdown as FileServerShutdown};
+pub use self::file_server::{
+    CheckpointsView, FileServer, Fingerprinter, Line, Shutdown as FileServerShutdown,
+};
 pub use self::internal_events::FileSourceInternalEvents;
 
-type FileFingerprint = u64;
-type FilePosition = u64;
+pub type FileFingerprint = u64;
+pub type FilePosition = u64;
 
 #[cfg(test)]
 mod test {
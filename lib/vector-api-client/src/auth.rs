@@ -0,0 +1,21 @@
+/// Credentials for an API server that requires them, sent along with every query and
+/// subscription
+#[derive(Debug, Clone)]
+pub enum Auth {
+    /// HTTP basic auth
+    Basic { user: String, password: String },
+    /// A bearer token
+    Bearer { token: String },
+}
+
+impl Auth {
+    /// Returns the value of the `Authorization` header carrying the credentials
+    pub fn header_value(&self) -> String {
+        match self {
+            Self::Basic { user, password } => {
+                format!("Basic {}", base64::encode(format!("{}:{}", user, password)))
+            }
+            Self::Bearer { token } => format!("Bearer {}", token),
+        }
+    }
+}
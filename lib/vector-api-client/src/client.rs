@@ -1,3 +1,4 @@
+use crate::{Auth, Tls};
 use anyhow::Context;
 use graphql_client::GraphQLQuery;
 use url::Url;
@@ -10,12 +11,31 @@ pub type QueryResult<T> =
 #[derive(Debug)]
 pub struct Client {
     url: Url,
+    auth: Option<Auth>,
+    tls: Tls,
 }
 
 impl Client {
     /// Returns a new GraphQL query client, bound to the provided URL
     pub fn new(url: Url) -> Self {
-        Self { url }
+        Self {
+            url,
+            auth: None,
+            tls: Tls::default(),
+        }
+    }
+
+    /// Returns the client, sending `auth` along with every query
+    pub fn with_auth(self, auth: Auth) -> Self {
+        Self {
+            auth: Some(auth),
+            ..self
+        }
+    }
+
+    /// Returns the client, verifying the certificate of `https://` URLs with `tls`
+    pub fn with_tls(self, tls: Tls) -> Self {
+        Self { tls, ..self }
     }
 
     /// Issue a GraphQL query using Reqwest, serializing the response to the associated
@@ -24,11 +44,19 @@ impl Client {
         &self,
         request_body: &graphql_client::QueryBody<T::Variables>,
     ) -> QueryResult<T> {
-        let client = reqwest::Client::new();
+        let mut client = reqwest::Client::builder().danger_accept_invalid_certs(self.tls.insecure);
+        if let Some(ca) = self.tls.ca().context("Couldn't read the CA file")? {
+            let ca = reqwest::Certificate::from_pem(&ca).context("Invalid CA certificate")?;
+            client = client.add_root_certificate(ca);
+        }
+        let client = client.build().context("Couldn't build the HTTP client")?;
+
+        let mut request = client.post(self.url.clone()).json(request_body);
+        if let Some(auth) = &self.auth {
+            request = request.header(reqwest::header::AUTHORIZATION, auth.header_value());
+        }
 
-        client
-            .post(self.url.clone())
-            .json(request_body)
+        request
             .send()
             .await
             .with_context(|| {
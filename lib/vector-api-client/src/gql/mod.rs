@@ -2,8 +2,10 @@
 
 mod health;
 mod metrics;
+mod tap;
 mod topology;
 
 pub use health::*;
 pub use metrics::*;
+pub use tap::*;
 pub use topology::*;
@@ -0,0 +1,46 @@
+//! Tap subscriptions
+
+use crate::SubscriptionResult;
+use async_trait::async_trait;
+use graphql_client::GraphQLQuery;
+
+/// TapSubscription returns sampled copies of the events leaving the components
+/// whose names match the given patterns
+#[derive(GraphQLQuery, Debug, Copy, Clone)]
+#[graphql(
+    schema_path = "graphql/schema.json",
+    query_path = "graphql/subscriptions/tap.graphql",
+    response_derives = "Debug"
+)]
+pub struct TapSubscription;
+
+/// Extension methods for tap subscriptions
+#[async_trait]
+pub trait TapSubscriptionExt {
+    /// Executes a tap subscription
+    async fn tap_subscription(
+        &self,
+        patterns: Vec<String>,
+        interval: i64,
+        limit: i64,
+    ) -> crate::SubscriptionResult<TapSubscription>;
+}
+
+#[async_trait]
+impl TapSubscriptionExt for crate::SubscriptionClient {
+    /// Executes a tap subscription
+    async fn tap_subscription(
+        &self,
+        patterns: Vec<String>,
+        interval: i64,
+        limit: i64,
+    ) -> SubscriptionResult<TapSubscription> {
+        let request_body = TapSubscription::build_query(tap_subscription::Variables {
+            patterns,
+            interval,
+            limit,
+        });
+
+        self.start::<TapSubscription>(&request_body).await
+    }
+}
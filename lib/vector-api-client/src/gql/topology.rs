@@ -30,7 +30,7 @@ impl std::fmt::Display for topology_query::TopologyQueryTopologyOn {
         let res = match self {
             topology_query::TopologyQueryTopologyOn::Source => "source",
             topology_query::TopologyQueryTopologyOn::Transform => "transform",
-            topology_query::TopologyQueryTopologyOn::Sink => "sink",
+            topology_query::TopologyQueryTopologyOn::Sink(_) => "sink",
         };
 
         write!(f, "{}", res)
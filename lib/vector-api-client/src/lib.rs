@@ -3,18 +3,25 @@
 //! Contains:
 //!
 //! 1. A GraphQL query client, for queries/mutations over HTTP(s)
-//! 2. A GraphQL subscription client, for long-lived, multiplexed subscriptions over WebSockets
-//! 3. GraphQL queries/mutations/subscriptions, defined in `graphql/**/*.graphql` files
-//! 4. Extension methods for each client, for executing queries/subscriptions, and returning
+//! 2. A GraphQL subscription client, for long-lived, multiplexed subscriptions over WebSockets,
+//! with or without TLS
+//! 3. Credentials and TLS settings, for API servers that require auth or serve certificates
+//! the system doesn't trust
+//! 4. GraphQL queries/mutations/subscriptions, defined in `graphql/**/*.graphql` files
+//! 5. Extension methods for each client, for executing queries/subscriptions, and returning
 //! deserialized JSON responses
 //!
 
 #![deny(missing_debug_implementations, missing_copy_implementations)]
 
+mod auth;
 mod client;
 /// GraphQL queries
 pub mod gql;
 mod subscription;
+mod tls;
 
+pub use auth::*;
 pub use client::*;
 pub use subscription::*;
+pub use tls::*;
@@ -1,3 +1,4 @@
+use crate::{Auth, Tls};
 use futures::{SinkExt, Stream};
 use graphql_client::GraphQLQuery;
 use serde::{Deserialize, Serialize};
@@ -8,6 +9,7 @@ use std::{
     sync::{Arc, RwLock, Weak},
 };
 use tokio::{
+    net::TcpStream,
     select,
     stream::StreamExt,
     sync::{
@@ -16,8 +18,13 @@ use tokio::{
     },
 };
 use tokio_tungstenite::{
-    connect_async,
-    tungstenite::{self, Message},
+    client_async, connect_async,
+    tungstenite::{
+        self,
+        client::IntoClientRequest,
+        http::{header::AUTHORIZATION, HeaderValue},
+        Message,
+    },
     WebSocketStream,
 };
 use url::Url;
@@ -237,7 +244,7 @@ impl SubscriptionClient {
 }
 
 /// Connect to a GraphQL subscription endpoint and return an active client. Can be used for
-/// multiple subscriptions
+/// multiple subscriptions. `wss://` URLs are connected to over TLS
 pub async fn connect_subscription_client(
     url: &Url,
 ) -> Result<SubscriptionClient, tungstenite::error::Error> {
@@ -246,3 +253,54 @@ pub async fn connect_subscription_client(
 
     Ok(client)
 }
+
+/// Connect to a GraphQL subscription endpoint that requires auth, sending `auth` along with
+/// the WebSocket handshake
+pub async fn connect_subscription_client_with_auth(
+    url: &Url,
+    auth: &Auth,
+) -> Result<SubscriptionClient, tungstenite::error::Error> {
+    connect_subscription_client_with_tls(url, Some(auth), &Tls::default()).await
+}
+
+/// Connect to a GraphQL subscription endpoint, sending `auth` along with the WebSocket
+/// handshake if set, and verifying the certificate of `wss://` URLs with `tls`
+pub async fn connect_subscription_client_with_tls(
+    url: &Url,
+    auth: Option<&Auth>,
+    tls: &Tls,
+) -> Result<SubscriptionClient, tungstenite::error::Error> {
+    use tungstenite::error::Error;
+
+    let mut request = url.as_str().into_client_request()?;
+    if let Some(auth) = auth {
+        let header = HeaderValue::from_str(&auth.header_value())
+            .map_err(|error| Error::HttpFormat(error.into()))?;
+        request.headers_mut().insert(AUTHORIZATION, header);
+    }
+
+    if url.scheme() != "wss" {
+        let (tx, _) = connect_async(request).await?;
+        return Ok(SubscriptionClient::new(tx));
+    }
+
+    // tokio-tungstenite can't be handed a TLS connector, so the TLS stream is set up here
+    let host = url
+        .host_str()
+        .ok_or_else(|| Error::Url("No host name in the URL".into()))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    let mut connector = native_tls::TlsConnector::builder();
+    connector
+        .danger_accept_invalid_certs(tls.insecure)
+        .danger_accept_invalid_hostnames(tls.insecure);
+    if let Some(ca) = tls.ca()? {
+        connector.add_root_certificate(native_tls::Certificate::from_pem(&ca).map_err(Error::Tls)?);
+    }
+    let connector = tokio_tls::TlsConnector::from(connector.build().map_err(Error::Tls)?);
+
+    let stream = TcpStream::connect((host, port)).await?;
+    let stream = connector.connect(host, stream).await.map_err(Error::Tls)?;
+    let (tx, _) = client_async(request, stream).await?;
+
+    Ok(SubscriptionClient::new(tx))
+}
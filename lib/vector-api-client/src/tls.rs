@@ -0,0 +1,18 @@
+use std::{fs, io, path::PathBuf};
+
+/// TLS settings for an API server served over HTTPS, for certificates that the system doesn't
+/// trust
+#[derive(Debug, Clone, Default)]
+pub struct Tls {
+    /// A CA certificate, in PEM format, to verify the certificate of the server with
+    pub ca_file: Option<PathBuf>,
+    /// Skips verifying the certificate and host name of the server
+    pub insecure: bool,
+}
+
+impl Tls {
+    /// Returns the contents of `ca_file`, if set
+    pub fn ca(&self) -> io::Result<Option<Vec<u8>>> {
+        self.ca_file.as_ref().map(fs::read).transpose()
+    }
+}
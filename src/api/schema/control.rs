@@ -0,0 +1,53 @@
+use crate::topology::{Control, ControlRequest};
+use async_graphql::{Context, FieldResult, Object};
+use tokio::sync::{mpsc, oneshot};
+
+/// Marks a request carrying the credentials of the API's `auth`.
+pub struct Authorized;
+
+#[derive(Default)]
+pub struct ControlMutation;
+
+#[Object]
+impl ControlMutation {
+    /// Reloads the configuration, returning `true` once the new one is running
+    async fn reload(&self, ctx: &Context<'_>) -> FieldResult<bool> {
+        send(ctx, Control::Reload).await
+    }
+
+    /// Holds back the events of a source until it's resumed
+    async fn pause_source(&self, ctx: &Context<'_>, name: String) -> FieldResult<bool> {
+        send(ctx, Control::PauseSource(name)).await
+    }
+
+    /// Resumes a paused source
+    async fn resume_source(&self, ctx: &Context<'_>, name: String) -> FieldResult<bool> {
+        send(ctx, Control::ResumeSource(name)).await
+    }
+
+    /// Sends the batches a sink holds right away
+    async fn flush_sink(&self, ctx: &Context<'_>, name: String) -> FieldResult<bool> {
+        send(ctx, Control::FlushSink(name)).await
+    }
+}
+
+/// Passes `control` on to the topology, if the request is authorized, and
+/// waits for it to go through.
+async fn send(ctx: &Context<'_>, control: Control) -> FieldResult<bool> {
+    if ctx.data_opt::<Authorized>().is_none() {
+        return Err(
+            "Mutations require the API's `auth` to be configured and the request to carry its credentials."
+                .into(),
+        );
+    }
+    let control_tx = ctx
+        .data_opt::<mpsc::UnboundedSender<ControlRequest>>()
+        .ok_or("The topology can't be controlled through this API.")?;
+
+    let (reply_tx, reply_rx) = oneshot::channel();
+    control_tx
+        .send((control, reply_tx))
+        .map_err(|_| "Vector is shutting down.")?;
+    reply_rx.await.map_err(|_| "Vector is shutting down.")??;
+    Ok(true)
+}
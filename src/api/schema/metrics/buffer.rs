@@ -0,0 +1,50 @@
+use crate::event::{Metric, MetricValue};
+use async_graphql::Object;
+
+pub struct BufferMetrics(Vec<Metric>);
+
+impl BufferMetrics {
+    pub fn new(metrics: Vec<Metric>) -> Self {
+        Self(metrics)
+    }
+
+    fn value(&self, name: &str) -> Option<f64> {
+        self.0
+            .iter()
+            .find(|m| m.name == name)
+            .map(|m| match m.value {
+                MetricValue::Gauge { value } => value,
+                MetricValue::Counter { value } => value,
+                _ => 0.00,
+            })
+    }
+}
+
+#[Object]
+/// Sink buffer metrics
+impl BufferMetrics {
+    /// Number of events in the buffer
+    async fn events(&self) -> Option<f64> {
+        self.value("buffer_events")
+    }
+
+    /// Bytes in the buffer, estimated from the size of the events for memory buffers
+    async fn byte_size(&self) -> Option<f64> {
+        self.value("buffer_byte_size")
+    }
+
+    /// Maximum number of events in the buffer (memory buffers only)
+    async fn max_event_size(&self) -> Option<f64> {
+        self.value("buffer_max_event_size")
+    }
+
+    /// Maximum bytes in the buffer (disk buffers only)
+    async fn max_byte_size(&self) -> Option<f64> {
+        self.value("buffer_max_byte_size")
+    }
+
+    /// Total number of events dropped because the buffer was full
+    async fn discarded_events_total(&self) -> Option<f64> {
+        self.value("buffer_discarded_events_total")
+    }
+}
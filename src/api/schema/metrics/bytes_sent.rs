@@ -0,0 +1,33 @@
+use crate::event::{Metric, MetricValue};
+use async_graphql::Object;
+use chrono::{DateTime, Utc};
+
+pub struct BytesSentTotal(Metric);
+
+impl BytesSentTotal {
+    pub fn new(m: Metric) -> Self {
+        Self(m)
+    }
+}
+
+#[Object]
+impl BytesSentTotal {
+    /// Metric timestamp
+    pub async fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.0.timestamp
+    }
+
+    /// Total number of bytes sent
+    pub async fn bytes_sent_total(&self) -> f64 {
+        match self.0.value {
+            MetricValue::Counter { value } => value,
+            _ => 0.00,
+        }
+    }
+}
+
+impl From<Metric> for BytesSentTotal {
+    fn from(m: Metric) -> Self {
+        Self(m)
+    }
+}
@@ -1,21 +1,34 @@
+mod buffer;
 mod bytes_processed;
+mod bytes_sent;
+mod errors;
+mod events_in;
+mod events_out;
 mod events_processed;
 mod host;
+mod request_latency;
 mod uptime;
 
-use crate::event::{Event, Metric};
+use crate::event::{Event, Metric, MetricKind, MetricValue};
 use crate::metrics::{capture_metrics, get_controller, Controller};
-use async_graphql::{validators::IntRange, Interface, Object, Subscription};
+use async_graphql::{validators::IntRange, Interface, Object, SimpleObject, Subscription};
 use async_stream::stream;
 use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::stream::{Stream, StreamExt};
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 
+pub use buffer::BufferMetrics;
 pub use bytes_processed::ProcessedBytesTotal;
+pub use bytes_sent::BytesSentTotal;
+pub use errors::ErrorsTotal;
+pub use events_in::EventsInTotal;
+pub use events_out::EventsOutTotal;
 pub use events_processed::EventsProcessedTotal;
 pub use host::HostMetrics;
+pub use request_latency::RequestLatency;
 pub use uptime::Uptime;
 
 lazy_static! {
@@ -31,6 +44,38 @@ pub enum MetricType {
     ProcessedBytesTotal(ProcessedBytesTotal),
 }
 
+#[derive(SimpleObject)]
+pub struct ComponentErrorsTotal {
+    /// Component name
+    name: String,
+    /// Errors of the component
+    metric: ErrorsTotal,
+}
+
+#[derive(SimpleObject)]
+pub struct ComponentBytesSentTotal {
+    /// Sink name
+    name: String,
+    /// Bytes sent by the sink
+    metric: BytesSentTotal,
+}
+
+#[derive(SimpleObject)]
+pub struct ComponentThroughput {
+    /// Component name
+    name: String,
+    /// Events per second, over the last interval
+    throughput: f64,
+}
+
+#[derive(SimpleObject)]
+pub struct ComponentRequestLatency {
+    /// Sink name
+    name: String,
+    /// Latency of the requests the sink completed over the last interval
+    metric: RequestLatency,
+}
+
 #[derive(Default)]
 pub struct MetricsQuery;
 
@@ -80,6 +125,87 @@ impl MetricsSubscription {
         })
     }
 
+    /// Errors of each component, summed over the counters of the errors components run into
+    async fn component_errors_totals(
+        &self,
+        #[graphql(default = 1000, validator(IntRange(min = "100", max = "60_000")))] interval: i32,
+    ) -> impl Stream<Item = Vec<ComponentErrorsTotal>> {
+        get_all_metrics(interval).map(|metrics| {
+            sum_by_component(&metrics, is_error)
+                .into_iter()
+                .map(|(name, value)| ComponentErrorsTotal {
+                    metric: ErrorsTotal::new(counter("errors_total", &name, value)),
+                    name,
+                })
+                .collect()
+        })
+    }
+
+    /// Events taken in per second by each transform and sink
+    async fn component_events_in_throughputs(
+        &self,
+        #[graphql(default = 1000, validator(IntRange(min = "100", max = "60_000")))] interval: i32,
+    ) -> impl Stream<Item = Vec<ComponentThroughput>> {
+        throughputs(interval, "events_in_total")
+    }
+
+    /// Events sent on per second by each source and transform
+    async fn component_events_out_throughputs(
+        &self,
+        #[graphql(default = 1000, validator(IntRange(min = "100", max = "60_000")))] interval: i32,
+    ) -> impl Stream<Item = Vec<ComponentThroughput>> {
+        throughputs(interval, "events_out_total")
+    }
+
+    /// Bytes sent by each sink
+    async fn component_bytes_sent_totals(
+        &self,
+        #[graphql(default = 1000, validator(IntRange(min = "100", max = "60_000")))] interval: i32,
+    ) -> impl Stream<Item = Vec<ComponentBytesSentTotal>> {
+        get_all_metrics(interval).map(|metrics| {
+            sum_by_component(&metrics, is_bytes_sent)
+                .into_iter()
+                .map(|(name, value)| ComponentBytesSentTotal {
+                    metric: BytesSentTotal::new(counter("bytes_sent_total", &name, value)),
+                    name,
+                })
+                .collect()
+        })
+    }
+
+    /// Latency of the requests of each sink, over each interval
+    async fn component_request_latencies(
+        &self,
+        #[graphql(default = 1000, validator(IntRange(min = "100", max = "60_000")))] interval: i32,
+    ) -> impl Stream<Item = Vec<ComponentRequestLatency>> {
+        // The number of requests of each sink already reported.
+        let mut reported = BTreeMap::new();
+        get_all_metrics(interval).map(move |metrics| {
+            metrics
+                .into_iter()
+                .filter(is_request_latency)
+                .filter_map(|mut metric| {
+                    let name = component_name(&metric)?.to_owned();
+                    if let MetricValue::Distribution {
+                        values,
+                        sample_rates,
+                        ..
+                    } = &mut metric.value
+                    {
+                        let seen = reported.insert(name.clone(), values.len()).unwrap_or(0);
+                        let seen = seen.min(values.len());
+                        values.drain(..seen);
+                        sample_rates.drain(..seen);
+                    }
+                    Some(ComponentRequestLatency {
+                        name,
+                        metric: RequestLatency::new(metric),
+                    })
+                })
+                .collect()
+        })
+    }
+
     /// All metrics
     async fn metrics(
         &self,
@@ -111,6 +237,188 @@ fn get_metrics(interval: i32) -> impl Stream<Item = Metric> {
     }
 }
 
+/// Returns a stream of all the `Metric`s, collected together at the provided millisecond
+/// interval
+fn get_all_metrics(interval: i32) -> impl Stream<Item = Vec<Metric>> {
+    let controller = get_controller().unwrap();
+    let mut interval = tokio::time::interval(Duration::from_millis(interval as u64));
+
+    stream! {
+        loop {
+            interval.tick().await;
+            yield capture_metrics(&controller)
+                .filter_map(|ev| match ev {
+                    Event::Metric(m) => Some(m),
+                    _ => None,
+                })
+                .collect();
+        }
+    }
+}
+
+/// The events per second of each component, counted by the counters named `name`, over
+/// each interval after the first.
+fn throughputs(interval: i32, name: &'static str) -> impl Stream<Item = Vec<ComponentThroughput>> {
+    let mut last: Option<(Instant, BTreeMap<String, f64>)> = None;
+    get_all_metrics(interval).filter_map(move |metrics| {
+        let now = Instant::now();
+        let totals = sum_by_component(&metrics, |m| m.name == name);
+        let throughputs = last.as_ref().map(|(at, previous)| {
+            let secs = now.duration_since(*at).as_secs_f64();
+            totals
+                .iter()
+                .map(|(component, total)| ComponentThroughput {
+                    name: component.clone(),
+                    throughput: (total - previous.get(component).unwrap_or(&0.0)).max(0.0) / secs,
+                })
+                .collect()
+        });
+        last = Some((now, totals));
+        throughputs
+    })
+}
+
+/// The name of the component a metric was recorded by.
+fn component_name(m: &Metric) -> Option<&str> {
+    m.tags.as_ref()?.get("component_name").map(String::as_str)
+}
+
+/// The counters of the errors components run into, as their internal events
+/// name them. A test checks that the internal events count no other errors,
+/// but for those of Vector itself.
+const ERROR_COUNTERS: &[&str] = &[
+    "bson_parse_error_total",
+    "checkpoint_write_errors_total",
+    "checksum_errors",
+    "communication_errors_total",
+    "connection_errors_total",
+    "connection_failures_total",
+    "connection_flush_errors_total",
+    "connection_read_errors_total",
+    "connection_send_errors_total",
+    "connections_failed_total",
+    "consumer_offset_updates_failed_total",
+    "container_metadata_fetch_errors_total",
+    "encode_errors_total",
+    "events_failed_total",
+    "events_not_delivered_total",
+    "file_delete_errors",
+    "file_watch_errors",
+    "fingerprint_read_errors",
+    "http_error_response_total",
+    "http_request_errors_total",
+    "logging_driver_errors_total",
+    "parse_errors_total",
+    "processing_errors",
+    "processing_errors_total",
+    "protobuf_decode_errors_total",
+    "request_error_total",
+    "request_errors_total",
+    "request_read_errors_total",
+    "stdin_reads_failed_total",
+    "timestamp_parse_errors_total",
+];
+
+fn is_error(m: &Metric) -> bool {
+    ERROR_COUNTERS.contains(&m.name.as_str()) && matches!(m.value, MetricValue::Counter { .. })
+}
+
+fn is_bytes_sent(m: &Metric) -> bool {
+    m.name == "processed_bytes_total" && m.tag_matches("component_kind", "sink")
+}
+
+fn is_request_latency(m: &Metric) -> bool {
+    m.name == "request_duration_nanoseconds" && m.tag_matches("component_kind", "sink")
+}
+
+/// Sums the counters accepted by `filter` of each component, by component name.
+fn sum_by_component<'a>(
+    metrics: impl IntoIterator<Item = &'a Metric>,
+    filter: impl Fn(&Metric) -> bool,
+) -> BTreeMap<String, f64> {
+    let mut sums = BTreeMap::new();
+    for m in metrics.into_iter().filter(|&m| filter(m)) {
+        if let (Some(name), MetricValue::Counter { value }) = (component_name(m), &m.value) {
+            *sums.entry(name.to_owned()).or_insert(0.0) += value;
+        }
+    }
+    sums
+}
+
+/// A counter named `name` of the component `component_name`.
+fn counter(name: &str, component_name: &str, value: f64) -> Metric {
+    let mut tags = BTreeMap::new();
+    tags.insert("component_name".to_owned(), component_name.to_owned());
+    Metric {
+        name: name.to_owned(),
+        timestamp: Some(Utc::now()),
+        tags: Some(tags),
+        kind: MetricKind::Absolute,
+        value: MetricValue::Counter { value },
+    }
+}
+
+/// Sums the counters accepted by `filter` of the component `topology_name`, if it has any.
+fn topology_counter(
+    topology_name: &str,
+    name: &str,
+    filter: impl Fn(&Metric) -> bool,
+) -> Option<Metric> {
+    let metrics = capture_metrics(&GLOBAL_CONTROLLER)
+        .filter_map(|ev| match ev {
+            Event::Metric(m) if m.tag_matches("component_name", topology_name) => Some(m),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    sum_by_component(&metrics, filter)
+        .get(topology_name)
+        .map(|value| counter(name, topology_name, *value))
+}
+
+/// Get the errors of a component by topology component name, of which there are none until
+/// one is counted
+pub fn topology_errors_total(topology_name: String) -> ErrorsTotal {
+    ErrorsTotal::new(
+        topology_counter(&topology_name, "errors_total", is_error)
+            .unwrap_or_else(|| counter("errors_total", &topology_name, 0.0)),
+    )
+}
+
+/// Get the events taken in by topology component name
+pub fn topology_events_in_total(topology_name: String) -> Option<EventsInTotal> {
+    topology_counter(&topology_name, "events_in_total", |m| {
+        m.name == "events_in_total"
+    })
+    .map(EventsInTotal::new)
+}
+
+/// Get the events sent on by topology component name
+pub fn topology_events_out_total(topology_name: String) -> Option<EventsOutTotal> {
+    topology_counter(&topology_name, "events_out_total", |m| {
+        m.name == "events_out_total"
+    })
+    .map(EventsOutTotal::new)
+}
+
+/// Get the bytes sent by a sink by topology component name
+pub fn topology_bytes_sent_total(topology_name: String) -> Option<BytesSentTotal> {
+    topology_counter(&topology_name, "bytes_sent_total", is_bytes_sent).map(BytesSentTotal::new)
+}
+
+/// Get the latency of the requests of a sink by topology component name, since it started
+pub fn topology_request_latency(topology_name: String) -> Option<RequestLatency> {
+    capture_metrics(&GLOBAL_CONTROLLER)
+        .find_map(|ev| match ev {
+            Event::Metric(m)
+                if is_request_latency(&m) && m.tag_matches("component_name", &topology_name) =>
+            {
+                Some(m)
+            }
+            _ => None,
+        })
+        .map(RequestLatency::new)
+}
+
 /// Get the events processed by topology component name
 pub fn topology_events_processed_total(topology_name: String) -> Option<EventsProcessedTotal> {
     let key = String::from("component_name");
@@ -127,3 +435,128 @@ pub fn topology_events_processed_total(topology_name: String) -> Option<EventsPr
         })
         .map(|ev| EventsProcessedTotal::new(ev.into_metric()))
 }
+
+/// Get the buffer metrics of a sink by topology component name
+pub fn topology_buffer_metrics(topology_name: String) -> BufferMetrics {
+    let key = String::from("component_name");
+
+    BufferMetrics::new(
+        capture_metrics(&GLOBAL_CONTROLLER)
+            .filter_map(|ev| match ev {
+                Event::Metric(m)
+                    if m.name.starts_with("buffer_") && m.tag_matches(&key, &topology_name) =>
+                {
+                    Some(m)
+                }
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(name: &str, component_name: &str, component_kind: &str, value: f64) -> Metric {
+        let mut m = counter(name, component_name, value);
+        m.tags
+            .as_mut()
+            .unwrap()
+            .insert("component_kind".to_owned(), component_kind.to_owned());
+        m
+    }
+
+    #[test]
+    fn sums_counters_by_component() {
+        let metrics = vec![
+            metric("processing_errors_total", "parse", "transform", 2.0),
+            metric("connection_errors_total", "out", "sink", 1.0),
+            metric("http_request_errors_total", "out", "sink", 3.0),
+            metric("stdin_reads_failed_total", "in", "source", 1.0),
+            // Named like an error, but not one of a component.
+            metric("api_start_errors_total", "out", "sink", 5.0),
+            metric("processed_bytes_total", "in", "source", 100.0),
+            metric("processed_bytes_total", "out", "sink", 42.0),
+        ];
+
+        let errors = sum_by_component(&metrics, is_error);
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors["parse"], 2.0);
+        assert_eq!(errors["out"], 4.0);
+        assert_eq!(errors["in"], 1.0);
+
+        let bytes_sent = sum_by_component(&metrics, is_bytes_sent);
+        assert_eq!(
+            bytes_sent.into_iter().collect::<Vec<_>>(),
+            vec![("out".to_owned(), 42.0)]
+        );
+    }
+
+    #[test]
+    fn request_latency_is_the_request_duration_of_sinks() {
+        assert!(is_request_latency(&metric(
+            "request_duration_nanoseconds",
+            "out",
+            "sink",
+            1.0
+        )));
+        // Scraping sources time their requests too.
+        assert!(!is_request_latency(&metric(
+            "request_duration_nanoseconds",
+            "in",
+            "source",
+            1.0
+        )));
+        assert!(!is_request_latency(&metric(
+            "auto_concurrency_observed_rtt",
+            "out",
+            "sink",
+            1.0
+        )));
+    }
+
+    #[test]
+    fn error_counters_cover_internal_events() {
+        // Errors of Vector itself, rather than of one of its components.
+        const PROCESS_ERROR_COUNTERS: &[&str] = &[
+            "api_start_errors_total",
+            "config_load_errors_total",
+            "enrichment_table_reload_errors_total",
+            "remote_config_fetch_errors_total",
+            "vector_recover_errors_total",
+            "vector_reload_errors_total",
+        ];
+
+        fn counters(dir: &std::path::Path, names: &mut Vec<String>) {
+            let counter = regex::Regex::new(r#"counter!\(\s*"([a-z_]+)""#).unwrap();
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    counters(&path, names);
+                } else {
+                    let text = std::fs::read_to_string(&path).unwrap();
+                    names.extend(counter.captures_iter(&text).map(|caps| caps[1].to_owned()));
+                }
+            }
+        }
+
+        let mut names = Vec::new();
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/internal_events");
+        counters(&dir, &mut names);
+        assert!(names.iter().any(|name| name == "processing_errors_total"));
+
+        for name in names.iter().filter(|name| {
+            ["error", "fail", "not_delivered"]
+                .iter()
+                .any(|word| name.contains(word))
+        }) {
+            assert!(
+                ERROR_COUNTERS.contains(&name.as_str())
+                    || PROCESS_ERROR_COUNTERS.contains(&name.as_str()),
+                "{:?} is missing from the error counters",
+                name
+            );
+        }
+    }
+}
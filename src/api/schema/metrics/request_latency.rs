@@ -0,0 +1,59 @@
+use crate::event::{Metric, MetricValue};
+use async_graphql::Object;
+use chrono::{DateTime, Utc};
+
+/// The durations of the requests sent by a sink, in nanoseconds.
+pub struct RequestLatency(Metric);
+
+impl RequestLatency {
+    pub fn new(m: Metric) -> Self {
+        Self(m)
+    }
+
+    fn values(&self) -> &[f64] {
+        match &self.0.value {
+            MetricValue::Distribution { values, .. } => values,
+            _ => &[],
+        }
+    }
+}
+
+#[Object]
+impl RequestLatency {
+    /// Metric timestamp
+    pub async fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.0.timestamp
+    }
+
+    /// Number of requests measured
+    pub async fn requests(&self) -> i64 {
+        self.values().len() as i64
+    }
+
+    /// Mean duration of the requests, in milliseconds
+    pub async fn mean_ms(&self) -> Option<f64> {
+        let values = self.values();
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.iter().sum::<f64>() / values.len() as f64 / 1_000_000.0)
+        }
+    }
+
+    /// Longest duration of the requests, in milliseconds
+    pub async fn max_ms(&self) -> Option<f64> {
+        self.values()
+            .iter()
+            .cloned()
+            .fold(None, |max: Option<f64>, value| {
+                Some(max.map_or(value, |max| max.max(value)))
+            })
+            .map(|max| max / 1_000_000.0)
+    }
+}
+
+impl From<Metric> for RequestLatency {
+    fn from(m: Metric) -> Self {
+        Self(m)
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    topology::{
+        tap::{Tap, Tapped},
+        Control, ControlRequest,
+    },
+    Event,
+};
+use async_graphql::{validators::IntRange, Context, FieldResult, SimpleObject, Subscription};
+use futures::{stream, Stream, StreamExt};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::Duration,
+};
+
+#[derive(SimpleObject)]
+pub struct TapEvent {
+    /// Name of the output the event left, which is that of its component unless it's a named
+    /// output
+    component_name: String,
+    /// The event, encoded as JSON
+    json: String,
+}
+
+impl From<Tapped> for TapEvent {
+    fn from(tapped: Tapped) -> Self {
+        let json = match &tapped.event {
+            Event::Log(log) => serde_json::to_string(log),
+            Event::Metric(metric) => serde_json::to_string(metric),
+        };
+        Self {
+            component_name: tapped.output,
+            json: json.unwrap_or_else(|error| format!("{{\"error\":{:?}}}", error.to_string())),
+        }
+    }
+}
+
+/// Removes the tap once the subscription ends.
+struct Untap {
+    id: usize,
+    control_tx: mpsc::UnboundedSender<ControlRequest>,
+}
+
+impl Drop for Untap {
+    fn drop(&mut self) {
+        let (reply_tx, _) = oneshot::channel();
+        let _ = self.control_tx.send((Control::Untap(self.id), reply_tx));
+    }
+}
+
+#[derive(Default)]
+pub struct TapSubscription;
+
+#[Subscription]
+impl TapSubscription {
+    /// Copies of the events leaving the components whose names match any of the glob
+    /// `patterns`, sampled down to at most `limit` events every `interval` milliseconds. The
+    /// events left out are dropped, without holding up the components.
+    async fn tap(
+        &self,
+        ctx: &Context<'_>,
+        patterns: Vec<String>,
+        #[graphql(default = 500, validator(IntRange(min = "100", max = "60_000")))] interval: i32,
+        #[graphql(default = 100, validator(IntRange(min = "1", max = "10_000")))] limit: i32,
+    ) -> FieldResult<impl Stream<Item = TapEvent>> {
+        let control_tx = ctx
+            .data_opt::<mpsc::UnboundedSender<ControlRequest>>()
+            .ok_or("The topology can't be tapped through this API.")?
+            .clone();
+
+        let limit = limit as usize;
+        let (tx, mut rx) = mpsc::channel(limit);
+        let tap = Tap::new(&patterns, tx)?;
+        let untap = Untap {
+            id: tap.id(),
+            control_tx: control_tx.clone(),
+        };
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        control_tx
+            .send((Control::Tap(tap), reply_tx))
+            .map_err(|_| "Vector is shutting down.")?;
+        reply_rx.await.map_err(|_| "Vector is shutting down.")??;
+
+        Ok(
+            tokio::time::interval(Duration::from_millis(interval as u64)).flat_map(move |_| {
+                let _ = &untap;
+                let mut events = Vec::new();
+                while events.len() < limit {
+                    match rx.try_recv() {
+                        Ok(tapped) => events.push(TapEvent::from(tapped)),
+                        Err(_) => break,
+                    }
+                }
+                stream::iter(events)
+            }),
+        )
+    }
+}
@@ -1,5 +1,5 @@
 use super::{broker::Broker, metrics};
-use crate::config::{Config, DataType};
+use crate::config::{self, Config, DataType};
 use async_graphql::{Enum, Interface, Object, Subscription};
 use lazy_static::lazy_static;
 use std::{
@@ -69,12 +69,24 @@ impl Source {
     async fn events_processed_total(&self) -> Option<metrics::EventsProcessedTotal> {
         metrics::topology_events_processed_total(self.0.name.clone())
     }
+
+    /// Metric indicating errors of any kind of the current source
+    async fn errors_total(&self) -> metrics::ErrorsTotal {
+        metrics::topology_errors_total(self.0.name.clone())
+    }
+
+    /// Metric indicating events sent on by the current source
+    async fn events_out_total(&self) -> Option<metrics::EventsOutTotal> {
+        metrics::topology_events_out_total(self.0.name.clone())
+    }
 }
 
 #[derive(Clone)]
 pub struct InputsData {
     name: String,
+    /// The components read from, named outputs standing for their component.
     inputs: Vec<String>,
+    outputs: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -87,6 +99,11 @@ impl Transform {
         self.0.name.clone()
     }
 
+    /// Named outputs of the transform, referred to as `<transform>.<output>`
+    async fn named_outputs(&self) -> Vec<String> {
+        self.0.outputs.clone()
+    }
+
     /// Source inputs
     async fn sources(&self) -> Vec<Source> {
         self.0
@@ -114,6 +131,21 @@ impl Transform {
     async fn events_processed_total(&self) -> Option<metrics::EventsProcessedTotal> {
         metrics::topology_events_processed_total(self.0.name.clone())
     }
+
+    /// Metric indicating errors of any kind of the current transform
+    async fn errors_total(&self) -> metrics::ErrorsTotal {
+        metrics::topology_errors_total(self.0.name.clone())
+    }
+
+    /// Metric indicating events taken in by the current transform
+    async fn events_in_total(&self) -> Option<metrics::EventsInTotal> {
+        metrics::topology_events_in_total(self.0.name.clone())
+    }
+
+    /// Metric indicating events sent on by the current transform
+    async fn events_out_total(&self) -> Option<metrics::EventsOutTotal> {
+        metrics::topology_events_out_total(self.0.name.clone())
+    }
 }
 
 #[derive(Clone)]
@@ -126,6 +158,11 @@ impl Sink {
         self.0.name.clone()
     }
 
+    /// Named outputs of the sink, referred to as `<sink>.<output>`
+    async fn named_outputs(&self) -> Vec<String> {
+        self.0.outputs.clone()
+    }
+
     /// Source inputs
     async fn sources(&self) -> Vec<Source> {
         self.0
@@ -160,6 +197,31 @@ impl Sink {
     async fn events_processed_total(&self) -> Option<metrics::EventsProcessedTotal> {
         metrics::topology_events_processed_total(self.0.name.clone())
     }
+
+    /// Metric indicating errors of any kind of the current sink
+    async fn errors_total(&self) -> metrics::ErrorsTotal {
+        metrics::topology_errors_total(self.0.name.clone())
+    }
+
+    /// Metric indicating events taken in by the current sink
+    async fn events_in_total(&self) -> Option<metrics::EventsInTotal> {
+        metrics::topology_events_in_total(self.0.name.clone())
+    }
+
+    /// Metric indicating bytes sent by the current sink
+    async fn bytes_sent_total(&self) -> Option<metrics::BytesSentTotal> {
+        metrics::topology_bytes_sent_total(self.0.name.clone())
+    }
+
+    /// Latency of the requests of the current sink, for sinks sending requests
+    async fn request_latency(&self) -> Option<metrics::RequestLatency> {
+        metrics::topology_request_latency(self.0.name.clone())
+    }
+
+    /// Metrics of the buffer of the current sink
+    async fn buffer(&self) -> metrics::BufferMetrics {
+        metrics::topology_buffer_metrics(self.0.name.clone())
+    }
 }
 
 #[derive(Clone, Interface)]
@@ -168,7 +230,8 @@ impl Sink {
     field(
         name = "events_processed_total",
         type = "Option<metrics::EventsProcessedTotal>"
-    )
+    ),
+    field(name = "errors_total", type = "metrics::ErrorsTotal")
 )]
 pub enum Topology {
     Source(Source),
@@ -269,6 +332,29 @@ fn get_topology_names() -> HashSet<String> {
         .collect::<HashSet<String>>()
 }
 
+/// Maps inputs to the components they read from, which for a named output is
+/// the component it belongs to.
+fn input_components(config: &Config, inputs: &[String]) -> Vec<String> {
+    let mut components = Vec::new();
+    for input in inputs {
+        let component = match config::split_named_output(input) {
+            Some((name, "dead_letter"))
+                if config
+                    .sinks
+                    .get(name)
+                    .map_or(false, |sink| sink.dead_letter) =>
+            {
+                name
+            }
+            _ => config.named_output_transform(input).unwrap_or(input),
+        };
+        if !components.iter().any(|c| c == component) {
+            components.push(component.to_owned());
+        }
+    }
+    components
+}
+
 /// Update the 'global' configuration that will be consumed by topology queries
 pub fn update_config(config: &Config) {
     let mut new_topology = HashMap::new();
@@ -290,7 +376,8 @@ pub fn update_config(config: &Config) {
             name.to_string(),
             Topology::Transform(Transform(InputsData {
                 name: name.to_owned(),
-                inputs: transform.inputs.clone(),
+                inputs: input_components(config, &transform.inputs),
+                outputs: transform.inner.named_outputs(),
             })),
         );
     }
@@ -301,7 +388,12 @@ pub fn update_config(config: &Config) {
             name.to_string(),
             Topology::Sink(Sink(InputsData {
                 name: name.to_owned(),
-                inputs: sink.inputs.clone(),
+                inputs: input_components(config, &sink.inputs),
+                outputs: if sink.dead_letter {
+                    vec!["dead_letter".to_owned()]
+                } else {
+                    Vec::new()
+                },
             })),
         );
     }
@@ -1,14 +1,25 @@
-use super::{handler, schema};
-use crate::config;
+use super::{
+    handler,
+    schema::{self, control::Authorized},
+};
+use crate::{config, sinks::util::http::Auth, tls::MaybeTlsSettings, topology::ControlRequest};
 use async_graphql::{
     http::{playground_source, GraphQLPlaygroundConfig},
     Request, Schema,
 };
 use async_graphql_warp::{graphql_subscription, Response as GQLResponse};
+use http::{
+    header::{AUTHORIZATION, WWW_AUTHENTICATE},
+    Request as HttpRequest,
+};
 use std::{convert::Infallible, net::SocketAddr};
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 use warp::filters::BoxedFilter;
-use warp::{http::Response, Filter, Reply};
+use warp::{
+    http::{Response, StatusCode},
+    reject::Reject,
+    Filter, Rejection, Reply,
+};
 
 pub struct Server {
     _shutdown: oneshot::Sender<()>,
@@ -16,14 +27,27 @@ pub struct Server {
 }
 
 impl Server {
-    /// Start the API server. This creates the routes and spawns a Warp server. The server is
-    /// gracefully shut down when Self falls out of scope by way of the oneshot sender closing
-    pub fn start(config: &config::Config) -> Self {
-        let routes = make_routes(config.api.playground);
+    /// Start the API server. This creates the routes and spawns a Warp server, serving TLS if
+    /// the API is configured to. The server is gracefully shut down when Self falls out of scope
+    /// by way of the oneshot sender closing. Mutations are passed on to the topology through
+    /// `control`.
+    pub fn start(
+        config: &config::Config,
+        control: mpsc::UnboundedSender<ControlRequest>,
+    ) -> crate::Result<Self> {
+        let routes = make_routes(config.api.playground, config.api.auth.clone(), control);
+
+        // Bind right away, so that the server is listening once started
+        let tls = MaybeTlsSettings::from_config(&config.api.tls, true)?;
+        let listener =
+            std::net::TcpListener::bind(config.api.bind.expect("Invalid socket address"))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let listener = tls.listen(tokio::net::TcpListener::from_std(listener)?)?;
 
         let (_shutdown, rx) = oneshot::channel();
-        let (addr, server) = warp::serve(routes).bind_with_graceful_shutdown(
-            config.api.bind.expect("Invalid socket address"),
+        let server = warp::serve(routes).serve_incoming_with_graceful_shutdown(
+            listener.accept_stream(),
             async {
                 rx.await.ok();
             },
@@ -35,7 +59,7 @@ impl Server {
         // Spawn the server in the background
         tokio::spawn(server);
 
-        Self { addr, _shutdown }
+        Ok(Self { addr, _shutdown })
     }
 
     /// Returns a copy of the SocketAddr that the server was started on
@@ -51,9 +75,13 @@ impl Server {
     }
 }
 
-fn make_routes(playground: bool) -> BoxedFilter<(impl Reply,)> {
+fn make_routes(
+    playground: bool,
+    auth: Option<Auth>,
+    control: mpsc::UnboundedSender<ControlRequest>,
+) -> BoxedFilter<(impl Reply,)> {
     // Build the GraphQL schema
-    let schema = schema::build_schema().finish();
+    let schema = schema::build_schema().data(control).finish();
 
     // Routes...
 
@@ -63,14 +91,34 @@ fn make_routes(playground: bool) -> BoxedFilter<(impl Reply,)> {
     // 404
     let not_found = warp::any().and_then(|| async { Err(warp::reject::not_found()) });
 
+    // With `auth`, GraphQL requests have to carry its credentials, and are then allowed
+    // mutations. Without it, no request is; subscriptions can't carry out mutations either way.
+    let mutable = auth.is_some();
+    let authorized = warp::header::optional::<String>("authorization")
+        .and_then(move |authorization: Option<String>| {
+            let authorized = match &auth {
+                Some(auth) if !authorizes(auth, authorization.as_deref()) => {
+                    Err(warp::reject::custom(Unauthorized {
+                        basic: matches!(auth, Auth::Basic { .. }),
+                    }))
+                }
+                _ => Ok(()),
+            };
+            async move { authorized }
+        })
+        .untuple_one();
+
     // GraphQL query and subscription handler
-    let graphql_handler = warp::path("graphql").and(graphql_subscription(schema.clone()).or(
-        async_graphql_warp::graphql(schema).and_then(
-            |(schema, request): (Schema<_, _, _>, Request)| async move {
-                Ok::<_, Infallible>(GQLResponse::from(schema.execute(request).await))
+    let graphql_handler = warp::path("graphql").and(authorized).and(
+        graphql_subscription(schema.clone()).or(async_graphql_warp::graphql(schema).and_then(
+            move |(schema, mut request): (Schema<_, _, _>, Request)| {
+                if mutable {
+                    request = request.data(Authorized);
+                }
+                async move { Ok::<_, Infallible>(GQLResponse::from(schema.execute(request).await)) }
             },
-        ),
-    ));
+        )),
+    );
 
     // GraphQL playground
     let graphql_playground = if playground {
@@ -91,6 +139,7 @@ fn make_routes(playground: bool) -> BoxedFilter<(impl Reply,)> {
         .or(graphql_handler)
         .or(graphql_playground)
         .or(not_found)
+        .recover(unauthorized)
         .with(
             warp::cors()
                 .allow_any_origin()
@@ -103,6 +152,7 @@ fn make_routes(playground: bool) -> BoxedFilter<(impl Reply,)> {
                     "Access-Control-Allow-Origin",
                     "Access-Control-Request-Headers",
                     "Content-Type",
+                    "Authorization",
                     "X-Apollo-Tracing", // for Apollo GraphQL clients
                     "Pragma",
                     "Host",
@@ -113,3 +163,45 @@ fn make_routes(playground: bool) -> BoxedFilter<(impl Reply,)> {
         )
         .boxed()
 }
+
+/// Rejection of a request lacking the credentials of `auth`, which are basic auth ones when
+/// `basic` is set.
+#[derive(Debug)]
+struct Unauthorized {
+    basic: bool,
+}
+
+impl Reject for Unauthorized {}
+
+/// Answers the requests lacking the credentials of `auth` with a 401, challenging clients for
+/// them when they're basic auth ones.
+async fn unauthorized(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    match rejection.find::<Unauthorized>() {
+        Some(Unauthorized { basic }) => {
+            let mut response = Response::builder().status(StatusCode::UNAUTHORIZED);
+            if *basic {
+                response = response.header(WWW_AUTHENTICATE, r#"Basic realm="Vector API""#);
+            }
+            Ok(response.body("Missing or invalid credentials"))
+        }
+        None => Err(rejection),
+    }
+}
+
+/// Whether the `Authorization` header of a request carries the credentials of `auth`.
+fn authorizes(auth: &Auth, authorization: Option<&str>) -> bool {
+    let mut expected = HttpRequest::new(());
+    auth.apply(&mut expected);
+    match (expected.headers().get(AUTHORIZATION), authorization) {
+        (Some(expected), Some(authorization)) => {
+            constant_time_eq(expected.as_bytes(), authorization.as_bytes())
+        }
+        _ => false,
+    }
+}
+
+/// Compares `a` and `b` in a time depending only on their lengths, so as not to leak how much
+/// of the credentials a request got right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
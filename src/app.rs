@@ -1,8 +1,9 @@
 use crate::cli::{handle_config_errors, Color, LogFormat, Opts, RootOpts, SubCommand};
 use crate::signal::SignalTo;
-use crate::topology::RunningTopology;
+use crate::topology::{Control, ControlRequest, RunningTopology};
 use crate::{
-    config, generate, heartbeat, list, metrics, signal, topology, trace, unit_test, validate,
+    buffers, config, generate, graph, heartbeat, list, metrics, schema, signal, topology, trace,
+    unit_test, validate,
 };
 use std::cmp::max;
 use std::path::PathBuf;
@@ -13,10 +14,13 @@ use futures::{
 };
 use futures01::sync::mpsc;
 
-#[cfg(feature = "api-client")]
-use crate::top;
 #[cfg(feature = "api")]
-use crate::{api, internal_events::ApiStarted};
+use crate::{
+    api,
+    internal_events::{ApiStartFailed, ApiStarted},
+};
+#[cfg(feature = "api-client")]
+use crate::{tap, top};
 
 #[cfg(windows)]
 use crate::service;
@@ -25,11 +29,15 @@ use crate::internal_events::{
     VectorConfigLoadFailed, VectorQuit, VectorRecoveryFailed, VectorReloadFailed, VectorReloaded,
     VectorStarted, VectorStopped,
 };
+use std::time::Duration;
 use tokio::runtime;
 use tokio::runtime::Runtime;
+use tokio::time;
 
 pub struct ApplicationConfig {
     pub config_paths: Vec<PathBuf>,
+    /// Where the configuration comes from when it isn't read from files.
+    pub remote: Option<config::remote::Provider>,
     pub topology: RunningTopology,
     pub graceful_crash: mpsc::UnboundedReceiver<()>,
     #[cfg(feature = "api")]
@@ -106,8 +114,15 @@ impl Application {
                 .expect("Unable to create async runtime")
         };
 
+        config::set_strict_env_vars(root_opts.strict_env_vars);
+
         let config = {
             let config_paths = root_opts.config_paths.clone();
+            let config_dirs = root_opts.config_dirs.clone();
+            let config_format = root_opts.config_format;
+            let config_url = root_opts.config_url.clone();
+            let config_auth_token = root_opts.config_auth_token.clone();
+            let config_cache = root_opts.config_cache.clone();
             let watch_config = root_opts.watch_config;
             let require_healthy = root_opts.require_healthy;
 
@@ -116,10 +131,15 @@ impl Application {
                     let code = match s {
                         SubCommand::Validate(v) => validate::validate(&v, color).await,
                         SubCommand::List(l) => list::cmd(&l),
+                        SubCommand::Graph(g) => graph::cmd(&g),
+                        SubCommand::Schema(s) => schema::cmd(&s),
+                        SubCommand::Buffer(b) => buffers::cmd::cmd(&b).await,
                         SubCommand::Test(t) => unit_test::cmd(&t).await,
                         SubCommand::Generate(g) => generate::cmd(&g),
                         #[cfg(feature = "api-client")]
                         SubCommand::Top(t) => top::cmd(&t).await,
+                        #[cfg(feature = "api-client")]
+                        SubCommand::Tap(t) => tap::cmd(&t).await,
                         #[cfg(windows)]
                         SubCommand::Service(s) => service::cmd(&s),
                     };
@@ -129,23 +149,53 @@ impl Application {
 
                 info!("Log level {:?} is enabled.", level);
 
-                let config_paths = config::process_paths(&config_paths).ok_or(exitcode::CONFIG)?;
-
-                if watch_config {
-                    // Start listening for config changes immediately.
-                    config::watcher::spawn_thread(&config_paths, None).map_err(|error| {
-                        error!(message = "Unable to start config watcher.", %error);
+                let (config_paths, mut remote, config) = if let Some(url) = config_url {
+                    let mut remote = config::remote::Provider::new(
+                        url,
+                        config_auth_token,
+                        config_format,
+                        config_cache,
+                    )
+                    .map_err(|error| {
+                        error!(message = "Unable to create the config provider.", %error);
                         exitcode::CONFIG
                     })?;
-                }
 
-                info!(
-                    message = "Loading configs.",
-                    path = ?config_paths
-                );
+                    info!(message = "Loading remote config.", url = %remote.url());
+
+                    let config = remote.load().await.map_err(handle_config_errors)?;
+                    (Vec::new(), Some(remote), config)
+                } else {
+                    let config_paths = config::process_paths_with_dirs(&config_paths, &config_dirs)
+                        .ok_or(exitcode::CONFIG)?;
+
+                    if watch_config {
+                        // Start listening for config changes immediately.
+                        let watched_paths = config_paths
+                            .iter()
+                            .chain(&config_dirs)
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        config::watcher::spawn_thread(&watched_paths, None).map_err(|error| {
+                            error!(message = "Unable to start config watcher.", %error);
+                            exitcode::CONFIG
+                        })?;
+                    }
 
-                let config =
-                    config::load_from_paths(&config_paths).map_err(handle_config_errors)?;
+                    info!(
+                        message = "Loading configs.",
+                        path = ?config_paths,
+                        dirs = ?config_dirs
+                    );
+
+                    let config = config::load_from_paths_and_dirs(
+                        &config_paths,
+                        &config_dirs,
+                        config_format,
+                    )
+                    .map_err(handle_config_errors)?;
+                    (config_paths, None, config)
+                };
 
                 config::LOG_SCHEMA
                     .set(config.global.log_schema.clone())
@@ -157,13 +207,17 @@ impl Application {
                     .ok_or(exitcode::CONFIG)?;
 
                 #[cfg(feature = "api")]
-                let api = config.api;
+                let api = config.api.clone();
 
                 let result = topology::start_validated(config, diff, pieces, require_healthy).await;
                 let (topology, graceful_crash) = result.ok_or(exitcode::CONFIG)?;
+                if let Some(remote) = remote.as_mut() {
+                    remote.applied();
+                }
 
                 Ok(ApplicationConfig {
                     config_paths,
+                    remote,
                     topology,
                     graceful_crash,
                     #[cfg(feature = "api")]
@@ -186,6 +240,7 @@ impl Application {
         let mut topology = self.config.topology;
 
         let mut config_paths = self.config.config_paths;
+        let mut remote = self.config.remote;
 
         let opts = self.opts;
 
@@ -196,15 +251,32 @@ impl Application {
             emit!(VectorStarted);
             tokio::spawn(heartbeat::heartbeat());
 
+            // Changes asked of the topology through the API.
+            let (control_tx, mut control_rx) =
+                tokio::sync::mpsc::unbounded_channel::<ControlRequest>();
+            #[cfg(not(feature = "api"))]
+            drop(control_tx);
+
             #[cfg(feature = "api")]
             // assigned to prevent the API terminating when falling out of scope
             let api_server = if api_config.enabled {
-                emit!(ApiStarted {
-                    addr: api_config.bind.unwrap(),
-                    playground: api_config.playground
-                });
-
-                Some(api::Server::start(topology.config()))
+                match api::Server::start(topology.config(), control_tx) {
+                    Ok(api_server) => {
+                        emit!(ApiStarted {
+                            addr: api_server.addr(),
+                            playground: api_config.playground,
+                            tls: api_config
+                                .tls
+                                .as_ref()
+                                .map_or(false, |tls| tls.enabled.unwrap_or(false)),
+                        });
+                        Some(api_server)
+                    }
+                    Err(error) => {
+                        emit!(ApiStartFailed { error });
+                        None
+                    }
+                }
             } else {
                 None
             };
@@ -213,50 +285,97 @@ impl Application {
             tokio::pin!(signals);
             let mut sources_finished = topology.sources_finished();
             let mut graceful_crash = graceful_crash.compat();
+            let poll_period = Duration::from_secs(opts.config_poll_interval_secs.max(1));
+            let mut poll_remote =
+                time::interval_at(time::Instant::now() + poll_period, poll_period);
 
             let signal = loop {
-                tokio::select! {
+                // Where to answer a reload asked through the API.
+                let mut reload_reply = None;
+                let new_config = tokio::select! {
                 Some(signal) = signals.next() => {
-                    if signal == SignalTo::Reload {
-                        // Reload paths
-                        config_paths = config::process_paths(&opts.config_paths).unwrap_or(config_paths);
-                        // Reload config
-                        let new_config = config::load_from_paths(&config_paths).map_err(handle_config_errors).ok();
-
-                        if let Some(new_config) = new_config {
-                            match topology
-                                .reload_config_and_respawn(new_config, opts.require_healthy)
-                                .await
-                            {
-                                Ok(true) => {
-                                    #[cfg(feature="api")]
-                                    if let Some(ref api_server) = api_server {
-                                        api_server.update_config(topology.config())
-                                    }
-
-                                    emit!(VectorReloaded { config_paths: &config_paths })
-                                },
-                                Ok(false) => emit!(VectorReloadFailed),
-                                // Trigger graceful shutdown for what remains of the topology
-                                Err(()) => {
-                                    emit!(VectorReloadFailed);
-                                    emit!(VectorRecoveryFailed);
-                                    break SignalTo::Shutdown;
-                                }
-                            }
-                            sources_finished = topology.sources_finished();
-                        } else {
-                            emit!(VectorConfigLoadFailed);
-                        }
-                    } else {
+                    if signal != SignalTo::Reload {
                         break signal;
                     }
+                    reload_config(remote.as_mut(), &mut config_paths, &opts).await
                 }
+                Some((control, reply)) = control_rx.recv() => match control {
+                    Control::Reload => {
+                        let new_config = reload_config(remote.as_mut(), &mut config_paths, &opts).await;
+                        if new_config.is_some() {
+                            reload_reply = Some(reply);
+                        } else {
+                            let _ = reply.send(Err("No new configuration was loaded, as it's either unchanged or invalid.".into()));
+                        }
+                        new_config
+                    }
+                    Control::PauseSource(name) => {
+                        let _ = reply.send(topology.pause_source(&name));
+                        None
+                    }
+                    Control::ResumeSource(name) => {
+                        let _ = reply.send(topology.resume_source(&name));
+                        None
+                    }
+                    Control::FlushSink(name) => {
+                        let _ = reply.send(topology.flush_sink(&name));
+                        None
+                    }
+                    Control::Tap(tap) => {
+                        topology.tap(tap);
+                        let _ = reply.send(Ok(()));
+                        None
+                    }
+                    Control::Untap(id) => {
+                        topology.untap(id);
+                        let _ = reply.send(Ok(()));
+                        None
+                    }
+                },
+                _ = poll_remote.tick(), if remote.is_some() => match remote.as_mut() {
+                    Some(remote) => remote.fetch().await,
+                    None => None,
+                },
                 // Trigger graceful shutdown if a component crashed, or all sources have ended.
                 _ = graceful_crash.next() => break SignalTo::Shutdown,
                 _ = &mut sources_finished => break SignalTo::Shutdown,
                 else => unreachable!("Signal streams never end"),
-            }
+                };
+
+                if let Some(new_config) = new_config {
+                    let reloaded = topology
+                        .reload_config_and_respawn(new_config, opts.require_healthy)
+                        .await;
+                    if let Some(reply) = reload_reply {
+                        let _ = reply.send(match reloaded {
+                            Ok(true) => Ok(()),
+                            Ok(false) => Err("The new configuration failed to start, so the previous one was restored.".into()),
+                            Err(()) => Err("The new configuration failed to start, and so did the previous one, so Vector is shutting down.".into()),
+                        });
+                    }
+                    match reloaded {
+                        Ok(true) => {
+                            if let Some(remote) = remote.as_mut() {
+                                remote.applied();
+                            }
+
+                            #[cfg(feature="api")]
+                            if let Some(ref api_server) = api_server {
+                                api_server.update_config(topology.config())
+                            }
+
+                            emit!(VectorReloaded { config_paths: &config_paths })
+                        },
+                        Ok(false) => emit!(VectorReloadFailed),
+                        // Trigger graceful shutdown for what remains of the topology
+                        Err(()) => {
+                            emit!(VectorReloadFailed);
+                            emit!(VectorRecoveryFailed);
+                            break SignalTo::Shutdown;
+                        }
+                    }
+                    sources_finished = topology.sources_finished();
+                }
             };
 
             match signal {
@@ -281,3 +400,35 @@ impl Application {
         });
     }
 }
+
+/// Loads the configuration again, polling the server it comes from right away,
+/// or else reading the paths it comes from.
+async fn reload_config(
+    remote: Option<&mut config::remote::Provider>,
+    config_paths: &mut Vec<PathBuf>,
+    opts: &RootOpts,
+) -> Option<config::Config> {
+    match remote {
+        Some(remote) => remote.fetch().await,
+        None => {
+            // Reload paths
+            if let Some(paths) =
+                config::process_paths_with_dirs(&opts.config_paths, &opts.config_dirs)
+            {
+                *config_paths = paths;
+            }
+            // Reload config
+            let new_config = config::load_from_paths_and_dirs(
+                config_paths,
+                &opts.config_dirs,
+                opts.config_format,
+            )
+            .map_err(handle_config_errors)
+            .ok();
+            if new_config.is_none() {
+                emit!(VectorConfigLoadFailed);
+            }
+            new_config
+        }
+    }
+}
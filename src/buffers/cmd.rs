@@ -0,0 +1,347 @@
+//! The `vector buffer` subcommand, for looking into the disk buffers left under
+//! the data directory without starting the pipeline.
+
+use super::disk::{self, segment_buffer};
+use crate::{
+    config::{self, log_schema},
+    event::{Event, Value},
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::{compat::Stream01CompatExt, StreamExt};
+use serde::Serialize;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Opts {
+    /// The directory holding the buffers. Defaults to the `data_dir` of the
+    /// configuration.
+    #[structopt(long)]
+    data_dir: Option<PathBuf>,
+
+    /// Read configuration from one or more files, to find the `data_dir` in. If
+    /// zero files are specified the default config path
+    /// `/etc/vector/vector.toml` will be targeted.
+    #[structopt(name = "config", short, long, env = "VECTOR_CONFIG")]
+    config_paths: Vec<PathBuf>,
+
+    #[structopt(subcommand)]
+    action: Action,
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+enum Action {
+    /// List the disk buffers with their number of events, size and oldest event.
+    List {
+        /// Format the list in an encoding scheme.
+        #[structopt(long, default_value = "text", possible_values = &["text", "json"])]
+        format: Format,
+
+        /// Only list the buffers of these sinks.
+        sinks: Vec<String>,
+    },
+
+    /// Print the events in the buffer of a sink as newline delimited JSON,
+    /// leaving them in the buffer.
+    Dump {
+        /// Stop after this many events.
+        #[structopt(long)]
+        limit: Option<usize>,
+
+        sink: String,
+    },
+
+    /// Print the events in the buffer of a sink as newline delimited JSON,
+    /// removing them from the buffer. Vector must not be running.
+    Replay { sink: String },
+
+    /// Delete the buffer of a sink along with all of its events. Vector must
+    /// not be running.
+    Delete { sink: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            s => Err(format!(
+                "{} is not a valid option, expected `text` or `json`",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct BufferStats {
+    sink: String,
+    events: usize,
+    bytes: usize,
+    oldest: Option<DateTime<Utc>>,
+}
+
+pub async fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    let data_dir = match data_dir(opts) {
+        Ok(data_dir) => data_dir,
+        Err(code) => return code,
+    };
+
+    let result = match &opts.action {
+        Action::List { format, sinks } => list(&data_dir, sinks, format),
+        Action::Dump { limit, sink } => dump(&data_dir, sink, *limit),
+        Action::Replay { sink } => replay(&data_dir, sink).await,
+        Action::Delete { sink } => delete(&data_dir, sink),
+    };
+
+    match result {
+        Ok(()) => exitcode::OK,
+        Err(code) => code,
+    }
+}
+
+fn data_dir(opts: &Opts) -> Result<PathBuf, exitcode::ExitCode> {
+    if let Some(data_dir) = &opts.data_dir {
+        return Ok(data_dir.clone());
+    }
+
+    let paths = config::process_paths(&opts.config_paths).ok_or(exitcode::CONFIG)?;
+    let config = config::load_from_paths(&paths, None).map_err(crate::cli::handle_config_errors)?;
+    config.global.data_dir.ok_or_else(|| {
+        error!("No data_dir configured, please pass one with `--data-dir`.");
+        exitcode::CONFIG
+    })
+}
+
+/// The buffer directory of `sink`, if it has one.
+fn existing_buffer(data_dir: &Path, sink: &str) -> Result<PathBuf, exitcode::ExitCode> {
+    let path = disk::buffer_dir(data_dir, sink);
+    if path.is_dir() {
+        Ok(path)
+    } else {
+        error!(message = "No disk buffer found.", %sink, ?data_dir);
+        Err(exitcode::NOINPUT)
+    }
+}
+
+/// Names of the sinks with a disk buffer in `data_dir`, sorted.
+fn buffered_sinks(data_dir: &Path) -> io::Result<Vec<String>> {
+    let mut sinks = Vec::new();
+    for entry in fs::read_dir(data_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(sink) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_suffix("_disk_buffer"))
+        {
+            sinks.push(sink.to_string());
+        }
+    }
+    sinks.sort();
+    Ok(sinks)
+}
+
+fn timestamp(event: &Event) -> Option<DateTime<Utc>> {
+    match event {
+        Event::Log(log) => match log.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => Some(*timestamp),
+            _ => None,
+        },
+        Event::Metric(metric) => metric.timestamp,
+    }
+}
+
+fn stats(data_dir: &Path, sink: &str) -> io::Result<BufferStats> {
+    let mut stats = BufferStats {
+        sink: sink.to_string(),
+        ..Default::default()
+    };
+    segment_buffer::scan(&disk::buffer_dir(data_dir, sink), |event, size| {
+        stats.events += 1;
+        stats.bytes += size;
+        if let Some(timestamp) = timestamp(&event) {
+            stats.oldest = Some(
+                stats
+                    .oldest
+                    .map_or(timestamp, |oldest| oldest.min(timestamp)),
+            );
+        }
+        true
+    })?;
+    Ok(stats)
+}
+
+fn list(data_dir: &Path, sinks: &[String], format: &Format) -> Result<(), exitcode::ExitCode> {
+    let sinks = if sinks.is_empty() {
+        buffered_sinks(data_dir).map_err(|error| {
+            error!(message = "Unable to read data_dir.", ?data_dir, %error);
+            exitcode::IOERR
+        })?
+    } else {
+        for sink in sinks {
+            existing_buffer(data_dir, sink)?;
+        }
+        sinks.to_vec()
+    };
+
+    let stats = sinks
+        .iter()
+        .map(|sink| stats(data_dir, sink))
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(|error| {
+            error!(message = "Unable to read disk buffer.", %error);
+            exitcode::IOERR
+        })?;
+
+    match format {
+        Format::Text => {
+            println!("{:<30} {:>12} {:>14}  OLDEST", "SINK", "EVENTS", "BYTES");
+            for stats in stats {
+                let oldest = stats
+                    .oldest
+                    .map(|oldest| oldest.to_rfc3339_opts(SecondsFormat::Secs, true))
+                    .unwrap_or_else(|| "-".into());
+                println!(
+                    "{:<30} {:>12} {:>14}  {}",
+                    stats.sink, stats.events, stats.bytes, oldest
+                );
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string(&stats).unwrap()),
+    }
+
+    Ok(())
+}
+
+fn write_event(out: &mut impl Write, event: &Event) -> io::Result<()> {
+    match event {
+        Event::Log(log) => serde_json::to_writer(&mut *out, log)?,
+        Event::Metric(metric) => serde_json::to_writer(&mut *out, metric)?,
+    }
+    out.write_all(b"\n")
+}
+
+fn dump(data_dir: &Path, sink: &str, limit: Option<usize>) -> Result<(), exitcode::ExitCode> {
+    let path = existing_buffer(data_dir, sink)?;
+    let limit = limit.unwrap_or(usize::MAX);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut written = 0;
+    let mut result = Ok(());
+    segment_buffer::scan(&path, |event, _| {
+        if written >= limit {
+            return false;
+        }
+        result = write_event(&mut out, &event);
+        written += 1;
+        result.is_ok()
+    })
+    .map_err(|error| {
+        error!(message = "Unable to read disk buffer.", ?path, %error);
+        exitcode::IOERR
+    })?;
+
+    result
+        .and_then(|()| out.flush())
+        .or_else(ignore_broken_pipe)
+}
+
+/// Reads the buffer like the sink would, acknowledging each event once it is
+/// written out so that the buffer is left empty.
+async fn replay(data_dir: &Path, sink: &str) -> Result<(), exitcode::ExitCode> {
+    existing_buffer(data_dir, sink)?;
+
+    let (writer, reader, acker) = disk::open(data_dir, sink, usize::MAX).map_err(|error| {
+        error!(message = "Unable to open disk buffer.", %error);
+        exitcode::IOERR
+    })?;
+    // Without a writer the reader ends once it has caught up.
+    drop(writer);
+
+    let stdout = io::stdout();
+    let mut events = reader.compat();
+    while let Some(Ok(event)) = events.next().await {
+        let mut out = stdout.lock();
+        write_event(&mut out, &event)
+            .and_then(|()| out.flush())
+            .or_else(ignore_broken_pipe)?;
+        acker.ack(1);
+    }
+
+    Ok(())
+}
+
+fn delete(data_dir: &Path, sink: &str) -> Result<(), exitcode::ExitCode> {
+    let path = existing_buffer(data_dir, sink)?;
+    fs::remove_dir_all(&path).map_err(|error| {
+        error!(message = "Unable to delete disk buffer.", ?path, %error);
+        exitcode::IOERR
+    })?;
+    info!(message = "Deleted disk buffer.", %sink);
+    Ok(())
+}
+
+fn ignore_broken_pipe(error: io::Error) -> Result<(), exitcode::ExitCode> {
+    if error.kind() == io::ErrorKind::BrokenPipe {
+        Ok(())
+    } else {
+        error!(message = "Unable to write events.", %error);
+        Err(exitcode::IOERR)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::buffers::BufferConfig;
+    use futures01::{stream, Future, Sink};
+    use tempfile::tempdir;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn lists_buffered_sinks() {
+        let dir = tempdir().unwrap();
+        let data_dir = Some(dir.path().to_path_buf());
+        let config = BufferConfig::Disk {
+            max_size: 1_000_000,
+            when_full: Default::default(),
+            max_age: None,
+        };
+
+        let (tx, _rx, _acker) = config.build(&data_dir, "out").unwrap();
+        let events = vec![Event::from("one"), Event::from("two")];
+        let first = timestamp(&events[0]);
+        let sink = tx
+            .get()
+            .send_all(stream::iter_ok::<_, ()>(events))
+            .wait()
+            .unwrap()
+            .0;
+        drop(sink);
+        fs::create_dir(dir.path().join("unrelated")).unwrap();
+
+        assert_eq!(buffered_sinks(dir.path()).unwrap(), vec!["out"]);
+        let stats = stats(dir.path(), "out").unwrap();
+        assert_eq!(stats.events, 2);
+        assert!(stats.bytes > 0);
+        assert!(first.is_some());
+        assert_eq!(stats.oldest, first);
+    }
+}
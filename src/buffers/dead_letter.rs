@@ -0,0 +1,175 @@
+//! Routing of events a sink permanently failed to deliver.
+//!
+//! A sink configured with `dead_letter = true` gets an extra output, named
+//! `<sink>.dead_letter`, that other components can use as an input. The events
+//! a sink reads are copied into a queue, in the order it reads them, and when
+//! the sink rejects some of them through its `Acker` the copies are annotated
+//! with the reason, the sink's name and the number of attempts, then sent to
+//! that output.
+
+use super::Acker;
+use crate::{
+    config,
+    event::{Event, EventStatus, Value},
+    internal_events::{DeadLetterEventDropped, DeadLetterEventSent},
+};
+use futures01::{sync::mpsc, try_ready, Async, Poll, Stream};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+/// Why a sink gave up on delivering some events.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rejection {
+    pub reason: String,
+    pub attempts: usize,
+}
+
+impl Rejection {
+    pub fn new(reason: impl Into<String>, attempts: usize) -> Self {
+        Self {
+            reason: reason.into(),
+            attempts,
+        }
+    }
+}
+
+/// The name of the dead-letter output of the sink `name`.
+pub fn output_name(name: &str) -> String {
+    config::named_output(name, "dead_letter")
+}
+
+/// Copies of the events a sink has read but not yet acknowledged.
+#[derive(Debug)]
+pub struct DeadLetters {
+    sink: String,
+    pending: Mutex<VecDeque<Event>>,
+    output: Mutex<mpsc::Sender<Event>>,
+    inner: Acker,
+}
+
+impl DeadLetters {
+    fn push(&self, event: Event) {
+        self.pending.lock().unwrap().push_back(event);
+    }
+
+    pub(super) fn ack(&self, num: usize, status: EventStatus) {
+        if status == EventStatus::Errored {
+            self.reject(num, Rejection::new("Delivery failed.", 1));
+        } else {
+            self.take(num);
+            self.inner.ack_with_status(num, status);
+        }
+    }
+
+    pub(super) fn reject(&self, num: usize, rejection: Rejection) {
+        let events = self.take(num);
+        self.inner.ack_with_status(num, EventStatus::Errored);
+
+        let mut output = self.output.lock().unwrap();
+        for mut event in events {
+            annotate(&mut event, &self.sink, &rejection);
+            match output.try_send(event) {
+                Ok(()) => emit!(DeadLetterEventSent {
+                    sink: &self.sink,
+                    reason: &rejection.reason,
+                }),
+                Err(_) => emit!(DeadLetterEventDropped { sink: &self.sink }),
+            }
+        }
+    }
+
+    fn take(&self, num: usize) -> Vec<Event> {
+        let mut pending = self.pending.lock().unwrap();
+        let num = num.min(pending.len());
+        pending.drain(..num).collect()
+    }
+}
+
+fn annotate(event: &mut Event, sink: &str, rejection: &Rejection) {
+    match event {
+        Event::Log(log) => {
+            log.insert("dead_letter.reason", rejection.reason.clone());
+            log.insert("dead_letter.sink", sink.to_string());
+            log.insert(
+                "dead_letter.attempts",
+                Value::Integer(rejection.attempts as i64),
+            );
+        }
+        Event::Metric(metric) => {
+            let tags = metric.tags.get_or_insert_with(Default::default);
+            tags.insert("dead_letter_reason".into(), rejection.reason.clone());
+            tags.insert("dead_letter_sink".into(), sink.to_string());
+            tags.insert(
+                "dead_letter_attempts".into(),
+                rejection.attempts.to_string(),
+            );
+        }
+    }
+}
+
+/// Keeps a copy of every event the sink reads, so that the events it rejects
+/// through the returned `Acker` can be sent to `output`.
+pub fn track_dead_letters(
+    stream: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+    acker: Acker,
+    sink: &str,
+    output: mpsc::Sender<Event>,
+) -> (Box<dyn Stream<Item = Event, Error = ()> + Send>, Acker) {
+    let dead_letters = Arc::new(DeadLetters {
+        sink: sink.to_string(),
+        pending: Mutex::new(VecDeque::new()),
+        output: Mutex::new(output),
+        inner: acker,
+    });
+    let stream = DeadLetterTracker {
+        inner: stream,
+        dead_letters: Arc::clone(&dead_letters),
+    };
+    (Box::new(stream), Acker::DeadLetter(dead_letters))
+}
+
+struct DeadLetterTracker<S> {
+    inner: S,
+    dead_letters: Arc<DeadLetters>,
+}
+
+impl<S: Stream<Item = Event, Error = ()>> Stream for DeadLetterTracker<S> {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let event = try_ready!(self.inner.poll());
+        if let Some(event) = &event {
+            self.dead_letters.push(event.clone());
+        }
+        Ok(Async::Ready(event))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures01::{stream, Future};
+
+    #[test]
+    fn rejected_events_are_annotated_and_sent() {
+        let (tx, rx) = mpsc::channel(10);
+        let input = stream::iter_ok(vec![Event::from("one"), Event::from("two")]);
+        let (input, acker) = track_dead_letters(Box::new(input), Acker::Null, "out", tx);
+
+        assert_eq!(input.collect().wait().unwrap().len(), 2);
+        acker.ack(1);
+        acker.reject(1, Rejection::new("bad request", 3));
+        drop(acker);
+
+        let dead = rx.collect().wait().unwrap();
+        assert_eq!(dead.len(), 1);
+        let log = dead[0].as_log();
+        assert_eq!(log["message"], "two".into());
+        assert_eq!(log["dead_letter.reason"], "bad request".into());
+        assert_eq!(log["dead_letter.sink"], "out".into());
+        assert_eq!(log["dead_letter.attempts"], Value::Integer(3));
+    }
+}
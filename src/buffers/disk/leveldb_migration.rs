@@ -0,0 +1,40 @@
+//! Moves the contents of a buffer written by the leveldb based disk buffer of
+//! earlier releases into the segment files of the current one.
+
+use super::segment_buffer;
+use leveldb::database::{
+    iterator::Iterable,
+    options::{Options, ReadOptions},
+    Database,
+};
+use std::{convert::TryInto, mem::size_of, path::Path};
+
+#[derive(Copy, Clone, Debug)]
+struct Key(pub usize);
+
+impl db_key::Key for Key {
+    fn from_u8(key: &[u8]) -> Self {
+        let bytes: [u8; size_of::<usize>()] = key.try_into().expect("Key should be the right size");
+
+        Self(usize::from_be_bytes(bytes))
+    }
+
+    fn as_slice<T, F: Fn(&[u8]) -> T>(&self, f: F) -> T {
+        let bytes = self.0.to_be_bytes();
+        f(&bytes)
+    }
+}
+
+/// Copies every unacknowledged event of the leveldb buffer at `from` into a
+/// new segment buffer at `to`. Events are stored in the same encoding by both,
+/// so they are copied as is. Returns the number of events copied.
+pub fn migrate(from: &Path, to: &Path) -> Result<usize, String> {
+    let mut options = Options::new();
+    options.create_if_missing = false;
+
+    let db: Database<Key> = Database::open(from, options).map_err(|error| error.to_string())?;
+
+    std::fs::create_dir_all(to).map_err(|error| error.to_string())?;
+    segment_buffer::write_segment(to, 0, db.value_iter(ReadOptions::new()))
+        .map_err(|error| error.to_string())
+}
@@ -1,12 +1,13 @@
-#![cfg(feature = "leveldb")]
-
 use crate::event::Event;
 use futures01::{Async, AsyncSink, Poll, Sink, Stream};
 use snafu::Snafu;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{atomic::AtomicUsize, Arc};
 
-pub mod leveldb_buffer;
+#[cfg(feature = "leveldb")]
+mod leveldb_migration;
+pub mod segment_buffer;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -19,11 +20,10 @@ pub enum Error {
         data_dir: PathBuf,
         source: std::io::Error,
     },
-    #[snafu(display("Unable to open data_dir {:?}", data_dir))]
-    DataDirOpenError {
-        data_dir: PathBuf,
-        source: leveldb::database::error::Error,
-    },
+    #[snafu(display("Unable to open disk buffer at {:?}: {}", path, source))]
+    BufferOpenError { path: PathBuf, source: io::Error },
+    #[snafu(display("Unable to migrate disk buffer at {:?}: {}", path, message))]
+    MigrationError { path: PathBuf, message: String },
 }
 
 pub trait DiskBuffer {
@@ -38,7 +38,17 @@ pub trait DiskBuffer {
 
 #[derive(Clone)]
 pub struct Writer {
-    inner: leveldb_buffer::Writer,
+    inner: segment_buffer::Writer,
+}
+
+impl Writer {
+    pub fn recovered_events(&self) -> usize {
+        self.inner.recovered_events()
+    }
+
+    pub fn current_size(&self) -> Arc<AtomicUsize> {
+        self.inner.current_size()
+    }
 }
 
 impl Sink for Writer {
@@ -61,6 +71,17 @@ impl Sink for Writer {
     }
 }
 
+/// The directory holding the disk buffer of the sink `name`.
+pub fn buffer_dir(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join(format!("{}_disk_buffer", name))
+}
+
+/// The directory the leveldb based disk buffer of earlier releases used for
+/// the sink `name`.
+pub fn legacy_buffer_dir(data_dir: &Path, name: &str) -> PathBuf {
+    data_dir.join(format!("{}_buffer", name))
+}
+
 pub fn open(
     data_dir: &Path,
     name: &str,
@@ -73,7 +94,7 @@ pub fn open(
     ),
     Error,
 > {
-    let path = data_dir.join(name);
+    let path = buffer_dir(data_dir, name);
 
     // Check data dir
     std::fs::metadata(&data_dir)
@@ -99,6 +120,57 @@ pub fn open(
             }
         })?;
 
-    let (writer, reader, acker) = leveldb_buffer::Buffer::build(path, max_size)?;
+    #[cfg(feature = "leveldb")]
+    migrate_legacy_buffer(data_dir, name, &path)?;
+    #[cfg(not(feature = "leveldb"))]
+    {
+        let legacy = legacy_buffer_dir(data_dir, name);
+        if legacy.is_dir() && !path.exists() {
+            warn!(
+                message = "Found a leveldb disk buffer of an earlier release, which this build can't migrate. Build with the `leveldb` feature to carry its events over.",
+                path = ?legacy
+            );
+        }
+    }
+
+    let (writer, reader, acker) = segment_buffer::Buffer::build(path, max_size)?;
     Ok((Writer { inner: writer }, Box::new(reader), acker))
 }
+
+/// Carries over the events left in a leveldb buffer by an earlier release. The
+/// events are written to a staging directory first so an interrupted migration
+/// is simply started over on the next launch.
+#[cfg(feature = "leveldb")]
+fn migrate_legacy_buffer(data_dir: &Path, name: &str, path: &Path) -> Result<(), Error> {
+    let legacy = legacy_buffer_dir(data_dir, name);
+    if !legacy.is_dir() || path.exists() {
+        return Ok(());
+    }
+
+    let staging = data_dir.join(format!("{}_disk_buffer.migrating", name));
+    let migrated = (|| -> Result<usize, String> {
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging).map_err(|error| error.to_string())?;
+        }
+        let count = leveldb_migration::migrate(&legacy, &staging)?;
+        std::fs::rename(&staging, path).map_err(|error| error.to_string())?;
+        std::fs::remove_dir_all(&legacy).map_err(|error| error.to_string())?;
+        Ok(count)
+    })();
+
+    match migrated {
+        Ok(count) => {
+            info!(
+                message = "Migrated leveldb disk buffer.",
+                from = ?legacy,
+                to = ?path,
+                count
+            );
+            Ok(())
+        }
+        Err(message) => Err(Error::MigrationError {
+            path: legacy,
+            message,
+        }),
+    }
+}
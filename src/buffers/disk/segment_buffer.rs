@@ -0,0 +1,849 @@
+//! A disk buffer built from append-only segment files.
+//!
+//! Events are encoded as protobuf and appended to the newest segment as records
+//! of `[length: u32][crc32: u32][payload]`, with both integers little endian.
+//! Once a segment grows past its target size the writer starts a new one. The
+//! reader walks the segments in order and removes a segment once every record
+//! in it has been acknowledged. The position of the first unacknowledged record
+//! is kept in a checkpoint file, so acknowledged records aren't replayed after a
+//! restart.
+//!
+//! On startup every segment is scanned. A record that is cut short or fails its
+//! checksum marks where a previous run stopped mid-write, and the segment is
+//! truncated there.
+
+use crate::event::{proto, Event, EventFinalizers, EventStatus};
+use bytes::Bytes;
+use futures01::{
+    task::{self, AtomicTask, Task},
+    Async, AsyncSink, Poll, Sink, Stream,
+};
+use prost::Message;
+use snafu::ResultExt;
+use std::{
+    collections::VecDeque,
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use super::{BufferOpenError, Error};
+use crate::buffers::Acker;
+
+/// Size of the length and checksum preceding every record.
+const HEADER_SIZE: usize = 8;
+const MIN_SEGMENT_SIZE: u64 = 64 * 1024;
+const MAX_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+const SEGMENT_EXTENSION: &str = "segment";
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+pub(super) fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", id, SEGMENT_EXTENSION))
+}
+
+/// Ids of the segments in `dir`, oldest first.
+pub(super) fn segment_ids(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some(SEGMENT_EXTENSION) {
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+pub(super) fn encode_record(payload: &[u8], buf: &mut Vec<u8>) {
+    buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buf.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+    buf.extend_from_slice(payload);
+}
+
+/// Reads the record at the current position. Returns `None` at a clean end of
+/// the file, and an `InvalidData` error for a truncated or corrupt record.
+pub(super) fn read_record<R: Read>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut header = [0; HEADER_SIZE];
+    let mut read = 0;
+    while read < HEADER_SIZE {
+        match reader.read(&mut header[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "truncated header",
+                ))
+            }
+            Ok(n) => read += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    let len = u32::from_le_bytes(header[..4].try_into().unwrap()) as usize;
+    let checksum = u32::from_le_bytes(header[4..].try_into().unwrap());
+
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload).map_err(|error| {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            io::Error::new(io::ErrorKind::InvalidData, "truncated record")
+        } else {
+            error
+        }
+    })?;
+
+    if crc32fast::hash(&payload) != checksum {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "checksum mismatch",
+        ));
+    }
+
+    Ok(Some(payload))
+}
+
+/// Writes all of `records` into a fresh segment.
+pub(super) fn write_segment(
+    dir: &Path,
+    id: u64,
+    records: impl Iterator<Item = Vec<u8>>,
+) -> io::Result<usize> {
+    let mut file = io::BufWriter::new(File::create(segment_path(dir, id))?);
+    let mut buf = Vec::new();
+    let mut count = 0;
+    for record in records {
+        buf.clear();
+        encode_record(&record, &mut buf);
+        file.write_all(&buf)?;
+        count += 1;
+    }
+    file.into_inner()?.sync_all()?;
+    Ok(count)
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct Position {
+    segment: u64,
+    offset: u64,
+}
+
+impl Position {
+    fn read(dir: &Path) -> io::Result<Option<Self>> {
+        match fs::read(dir.join(CHECKPOINT_FILE)) {
+            Ok(bytes) if bytes.len() == 16 => Ok(Some(Self {
+                segment: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+                offset: u64::from_le_bytes(bytes[8..].try_into().unwrap()),
+            })),
+            Ok(_) => {
+                warn!(message = "Ignoring malformed disk buffer checkpoint.", ?dir);
+                Ok(None)
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn write(&self, dir: &Path) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(16);
+        bytes.extend_from_slice(&self.segment.to_le_bytes());
+        bytes.extend_from_slice(&self.offset.to_le_bytes());
+
+        let tmp = dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        fs::write(&tmp, &bytes)?;
+        fs::rename(tmp, dir.join(CHECKPOINT_FILE))
+    }
+}
+
+/// The end of the newest segment, up to which records are visible to the reader.
+struct Head {
+    segment: u64,
+    offset: u64,
+    file: File,
+}
+
+impl Head {
+    fn open(dir: &Path, segment: u64) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(dir, segment))?;
+        let offset = file.metadata()?.len();
+        Ok(Self {
+            segment,
+            offset,
+            file,
+        })
+    }
+}
+
+struct Shared {
+    dir: PathBuf,
+    segment_size: u64,
+    /// Number of records left unacknowledged by a previous run.
+    recovered: usize,
+    head: Mutex<Head>,
+}
+
+impl Shared {
+    fn head_position(&self) -> Position {
+        let head = self.head.lock().unwrap();
+        Position {
+            segment: head.segment,
+            offset: head.offset,
+        }
+    }
+}
+
+pub struct Writer {
+    shared: Arc<Shared>,
+    write_notifier: Arc<AtomicTask>,
+    blocked_write_tasks: Arc<Mutex<Vec<Task>>>,
+    batch: Vec<u8>,
+    batch_size: usize,
+    max_size: usize,
+    current_size: Arc<AtomicUsize>,
+    finalizers: EventFinalizers,
+}
+
+impl Clone for Writer {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+            write_notifier: Arc::clone(&self.write_notifier),
+            blocked_write_tasks: Arc::clone(&self.blocked_write_tasks),
+            batch: Vec::new(),
+            batch_size: 0,
+            max_size: self.max_size,
+            current_size: Arc::clone(&self.current_size),
+            finalizers: EventFinalizers::default(),
+        }
+    }
+}
+
+impl Sink for Writer {
+    type SinkItem = Event;
+    type SinkError = ();
+
+    fn start_send(
+        &mut self,
+        mut event: Self::SinkItem,
+    ) -> Result<AsyncSink<Self::SinkItem>, Self::SinkError> {
+        let finalizers = event.take_finalizers();
+        let mut value = vec![];
+        proto::EventWrapper::from(event).encode(&mut value).unwrap(); // This will not error when writing to a Vec
+        let record_size = value.len() + HEADER_SIZE;
+
+        if self.current_size.fetch_add(record_size, Ordering::Relaxed) + (record_size / 2)
+            > self.max_size
+        {
+            self.blocked_write_tasks
+                .lock()
+                .unwrap()
+                .push(task::current());
+
+            self.current_size.fetch_sub(record_size, Ordering::Relaxed);
+
+            self.poll_complete()?;
+
+            let buf = Bytes::from(value);
+            let mut event: Event = proto::EventWrapper::decode(buf).unwrap().into();
+            event.add_finalizers(finalizers);
+            return Ok(AsyncSink::NotReady(event));
+        }
+
+        encode_record(&value, &mut self.batch);
+        self.batch_size += 1;
+        self.finalizers.merge(finalizers);
+
+        if self.batch_size >= 100 {
+            self.poll_complete()?;
+        }
+
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, Self::SinkError> {
+        // Appending goes to the page cache and doesn't need to be wrapped with
+        // `blocking`; it survives a process crash, just not a power loss.
+        if self.batch_size > 0 {
+            self.write_batch().map_err(|error| {
+                error!(message = "Error writing to disk buffer.", %error);
+            })?;
+        }
+
+        Ok(Async::Ready(()))
+    }
+}
+
+impl Writer {
+    /// Number of events that were already in the buffer when it was opened.
+    pub fn recovered_events(&self) -> usize {
+        self.shared.recovered
+    }
+
+    /// Total size of the records in the buffer, including the ones that were
+    /// read but not acknowledged yet.
+    pub fn current_size(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.current_size)
+    }
+
+    fn write_batch(&mut self) -> io::Result<()> {
+        {
+            let mut head = self.shared.head.lock().unwrap();
+            if head.offset >= self.shared.segment_size {
+                *head = Head::open(&self.shared.dir, head.segment + 1)?;
+            }
+            head.file.write_all(&self.batch)?;
+            head.offset += self.batch.len() as u64;
+        }
+
+        self.batch.clear();
+        self.batch_size = 0;
+        // Once the events are on disk they survive a restart, which is as far
+        // as the sources need to wait for.
+        self.finalizers.take().update_status(EventStatus::Delivered);
+        self.write_notifier.notify();
+        Ok(())
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        if self.batch_size > 0 {
+            if let Err(error) = self.write_batch() {
+                error!(message = "Error writing to disk buffer.", %error);
+            }
+        }
+
+        // We need to wake up the reader so it can return None if there are no more writers
+        self.write_notifier.notify();
+    }
+}
+
+pub struct Reader {
+    shared: Arc<Shared>,
+    read: Position,
+    acked: Position,
+    oldest_segment: u64,
+    file: Option<BufReader<File>>,
+    write_notifier: Arc<AtomicTask>,
+    blocked_write_tasks: Arc<Mutex<Vec<Task>>>,
+    current_size: Arc<AtomicUsize>,
+    ack_counter: Arc<AtomicUsize>,
+    /// End position and size of every record read but not yet acknowledged.
+    unacked: VecDeque<(Position, usize)>,
+}
+
+impl Stream for Reader {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.delete_acked();
+
+        // If there's nothing left to read, we return NotReady and rely on Writer
+        // using write_notifier to wake this task up after the next write.
+        self.write_notifier.register();
+
+        loop {
+            // Checked before reading, so that nothing can be written in between.
+            let no_writers = Arc::strong_count(&self.shared) == 1;
+
+            // This will usually complete instantly, but in the case of a large queue (or a fresh launch of
+            // the app), this will have to go to disk.
+            match tokio::task::block_in_place(|| self.next_record()) {
+                Ok(Some(value)) => match proto::EventWrapper::decode(Bytes::from(value)) {
+                    Ok(event) => return Ok(Async::Ready(Some(Event::from(event)))),
+                    Err(err) => {
+                        error!("Error deserializing proto: {:?}", err);
+                        debug_assert!(false);
+                    }
+                },
+                Ok(None) if no_writers => return Ok(Async::Ready(None)),
+                Ok(None) => return Ok(Async::NotReady),
+                Err(error) => {
+                    error!(message = "Error reading from disk buffer.", %error);
+                    return Err(());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        self.delete_acked();
+    }
+}
+
+impl Reader {
+    /// Reads the next record that is visible to the reader, moving on to the
+    /// next segment once the current one is exhausted.
+    fn next_record(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            let head = self.shared.head_position();
+            if self.read.segment == head.segment && self.read.offset >= head.offset {
+                return Ok(None);
+            }
+
+            if self.file.is_none() {
+                let mut file = File::open(segment_path(&self.shared.dir, self.read.segment))?;
+                file.seek(SeekFrom::Start(self.read.offset))?;
+                self.file = Some(BufReader::new(file));
+            }
+
+            match read_record(self.file.as_mut().unwrap())? {
+                Some(value) => {
+                    let size = value.len() + HEADER_SIZE;
+                    self.read.offset += size as u64;
+                    self.unacked.push_back((self.read, size));
+                    return Ok(Some(value));
+                }
+                None if self.read.segment < head.segment => {
+                    self.read = Position {
+                        segment: self.read.segment + 1,
+                        offset: 0,
+                    };
+                    self.file = None;
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn delete_acked(&mut self) {
+        let num_to_delete = self.ack_counter.swap(0, Ordering::Relaxed);
+
+        if num_to_delete > 0 {
+            assert!(
+                num_to_delete <= self.unacked.len(),
+                "Tried to ack beyond read offset"
+            );
+
+            let mut size_deleted = 0;
+            for (position, size) in self.unacked.drain(..num_to_delete) {
+                self.acked = position;
+                size_deleted += size;
+            }
+
+            if let Err(error) = self.acked.write(&self.shared.dir) {
+                error!(message = "Error writing disk buffer checkpoint.", %error);
+            }
+
+            // Segments before the one holding the oldest unacknowledged
+            // record are done with.
+            while self.oldest_segment < self.acked.segment {
+                let path = segment_path(&self.shared.dir, self.oldest_segment);
+                if let Err(error) = fs::remove_file(&path) {
+                    error!(message = "Error removing disk buffer segment.", ?path, %error);
+                }
+                self.oldest_segment += 1;
+            }
+
+            self.current_size.fetch_sub(size_deleted, Ordering::Relaxed);
+        }
+
+        for task in self.blocked_write_tasks.lock().unwrap().drain(..) {
+            task.notify();
+        }
+    }
+}
+
+/// Scans the segments of a buffer from `start` on, truncating any record torn
+/// by a crash. Returns the total size and the number of the records found.
+fn recover(dir: &Path, ids: &[u64], start: Position) -> io::Result<(usize, usize)> {
+    let mut size = 0;
+    let mut count = 0;
+
+    for &id in ids.iter().filter(|&&id| id >= start.segment) {
+        let path = segment_path(dir, id);
+        let offset = if id == start.segment { start.offset } else { 0 };
+
+        let mut file = File::open(&path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut reader = BufReader::new(file);
+
+        let mut valid = offset;
+        loop {
+            match read_record(&mut reader) {
+                Ok(Some(value)) => {
+                    let record_size = value.len() + HEADER_SIZE;
+                    valid += record_size as u64;
+                    size += record_size;
+                    count += 1;
+                }
+                Ok(None) => break,
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                    warn!(
+                        message = "Truncating damaged disk buffer segment.",
+                        ?path,
+                        offset = valid,
+                        %error
+                    );
+                    OpenOptions::new().write(true).open(&path)?.set_len(valid)?;
+                    break;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    Ok((size, count))
+}
+
+/// The position of the oldest record not acknowledged yet. That is the
+/// checkpoint, unless its segment is gone, in which case everything that is
+/// left has yet to be acknowledged.
+fn first_unacked(dir: &Path, ids: &[u64]) -> io::Result<Position> {
+    Ok(match Position::read(dir)? {
+        Some(checkpoint) if ids.contains(&checkpoint.segment) => checkpoint,
+        Some(checkpoint) => Position {
+            segment: ids
+                .iter()
+                .copied()
+                .find(|&id| id > checkpoint.segment)
+                .unwrap_or(checkpoint.segment + 1),
+            offset: 0,
+        },
+        None => Position {
+            segment: ids.first().copied().unwrap_or(0),
+            offset: 0,
+        },
+    })
+}
+
+/// Reads the unacknowledged events of the buffer in `dir` without changing
+/// anything on disk, handing each of them to `f` along with the size of its
+/// record until `f` returns `false`. A damaged record ends the scan, as it
+/// would be truncated when the buffer is next opened.
+///
+/// Unlike `Buffer::build` this is safe to use on a buffer that may be in use.
+pub fn scan(dir: &Path, mut f: impl FnMut(Event, usize) -> bool) -> io::Result<()> {
+    let ids = segment_ids(dir)?;
+    let start = first_unacked(dir, &ids)?;
+
+    for &id in ids.iter().filter(|&&id| id >= start.segment) {
+        let mut file = match File::open(segment_path(dir, id)) {
+            Ok(file) => file,
+            // Removed by a reader acknowledging it in the meantime.
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+        if id == start.segment {
+            file.seek(SeekFrom::Start(start.offset))?;
+        }
+        let mut reader = BufReader::new(file);
+
+        loop {
+            match read_record(&mut reader) {
+                Ok(Some(value)) => {
+                    let record_size = value.len() + HEADER_SIZE;
+                    match proto::EventWrapper::decode(Bytes::from(value)) {
+                        Ok(event) => {
+                            if !f(Event::from(event), record_size) {
+                                return Ok(());
+                            }
+                        }
+                        Err(error) => {
+                            warn!(message = "Skipping undecodable disk buffer record.", %error)
+                        }
+                    }
+                }
+                Ok(None) => break,
+                Err(error) if error.kind() == io::ErrorKind::InvalidData => return Ok(()),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn target_segment_size(max_size: usize) -> u64 {
+    (max_size as u64 / 8)
+        .max(MIN_SEGMENT_SIZE)
+        .min(MAX_SEGMENT_SIZE)
+}
+
+pub struct Buffer;
+
+impl super::DiskBuffer for Buffer {
+    type Writer = Writer;
+    type Reader = Reader;
+
+    fn build(path: PathBuf, max_size: usize) -> Result<(Self::Writer, Self::Reader, Acker), Error> {
+        Self::build_with_segment_size(path, max_size, target_segment_size(max_size))
+    }
+}
+
+impl Buffer {
+    fn build_with_segment_size(
+        path: PathBuf,
+        max_size: usize,
+        segment_size: u64,
+    ) -> Result<(Writer, Reader, Acker), Error> {
+        let (shared, acked, initial_size) =
+            open(&path, segment_size).with_context(|| BufferOpenError { path: path.clone() })?;
+        let shared = Arc::new(shared);
+
+        let current_size = Arc::new(AtomicUsize::new(initial_size));
+
+        let write_notifier = Arc::new(AtomicTask::new());
+
+        let blocked_write_tasks = Arc::new(Mutex::new(Vec::new()));
+
+        let ack_counter = Arc::new(AtomicUsize::new(0));
+        let acker = Acker::Disk(Arc::clone(&ack_counter), Arc::clone(&write_notifier));
+
+        let writer = Writer {
+            shared: Arc::clone(&shared),
+            write_notifier: Arc::clone(&write_notifier),
+            blocked_write_tasks: Arc::clone(&blocked_write_tasks),
+            batch: Vec::new(),
+            batch_size: 0,
+            max_size,
+            current_size: Arc::clone(&current_size),
+            finalizers: EventFinalizers::default(),
+        };
+
+        let reader = Reader {
+            shared,
+            read: acked,
+            acked,
+            oldest_segment: acked.segment,
+            file: None,
+            write_notifier,
+            blocked_write_tasks,
+            current_size,
+            ack_counter,
+            unacked: VecDeque::new(),
+        };
+
+        Ok((writer, reader, acker))
+    }
+}
+
+/// Opens the buffer directory, recovering whatever a previous run left behind.
+fn open(path: &Path, segment_size: u64) -> io::Result<(Shared, Position, usize)> {
+    fs::create_dir_all(path)?;
+
+    let ids = segment_ids(path)?;
+    let acked = first_unacked(path, &ids)?;
+
+    // Leftovers from before the checkpoint have already been acknowledged.
+    for &id in ids.iter().filter(|&&id| id < acked.segment) {
+        fs::remove_file(segment_path(path, id))?;
+    }
+
+    let (initial_size, recovered) = recover(path, &ids, acked)?;
+
+    let head_segment = ids.last().copied().unwrap_or(0).max(acked.segment);
+    let shared = Shared {
+        dir: path.to_path_buf(),
+        segment_size,
+        recovered,
+        head: Mutex::new(Head::open(path, head_segment)?),
+    };
+
+    Ok((shared, acked, initial_size))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        buffers::disk::DiskBuffer,
+        event::{BatchNotifier, BatchStatus},
+    };
+    use futures::FutureExt;
+    use futures01::Future;
+    use tempfile::tempdir;
+
+    fn event(message: &str) -> Event {
+        let mut event = Event::new_empty_log();
+        event.as_mut_log().insert("message", message);
+        event
+    }
+
+    fn message(event: &Event) -> String {
+        event.as_log()["message"].to_string_lossy()
+    }
+
+    fn write(writer: Writer, messages: &[&str]) {
+        let events = messages
+            .iter()
+            .map(|message| event(message))
+            .collect::<Vec<_>>();
+        let writer = writer
+            .send_all(futures01::stream::iter_ok::<_, ()>(events))
+            .wait()
+            .unwrap()
+            .0;
+        drop(writer);
+    }
+
+    fn read(reader: Reader) -> Vec<String> {
+        reader
+            .collect()
+            .wait()
+            .unwrap()
+            .iter()
+            .map(message)
+            .collect()
+    }
+
+    #[test]
+    fn record_round_trip() {
+        let mut buf = Vec::new();
+        encode_record(b"hello", &mut buf);
+        encode_record(b"world", &mut buf);
+
+        let mut reader = &buf[..];
+        assert_eq!(read_record(&mut reader).unwrap(), Some(b"hello".to_vec()));
+        assert_eq!(read_record(&mut reader).unwrap(), Some(b"world".to_vec()));
+        assert_eq!(read_record(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn record_checksum_mismatch() {
+        let mut buf = Vec::new();
+        encode_record(b"hello", &mut buf);
+        *buf.last_mut().unwrap() = b'O';
+
+        let error = read_record(&mut &buf[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn reads_back_in_order() {
+        let dir = tempdir().unwrap();
+        let (writer, reader, _acker) = Buffer::build(dir.path().join("buffer"), 10_000).unwrap();
+
+        write(writer, &["one", "two", "three"]);
+        assert_eq!(read(reader), vec!["one", "two", "three"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn acknowledged_records_are_not_replayed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+
+        {
+            let (writer, mut reader, acker) = Buffer::build(path.clone(), 10_000).unwrap();
+            write(writer, &["one", "two", "three"]);
+
+            let first = futures01::future::poll_fn(|| reader.poll()).wait().unwrap();
+            assert_eq!(first.map(|event| message(&event)), Some("one".into()));
+            acker.ack(1);
+        }
+
+        let (writer, reader, _acker) = Buffer::build(path, 10_000).unwrap();
+        drop(writer);
+        assert_eq!(read(reader), vec!["two", "three"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn scan_skips_acknowledged_records() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+
+        let (writer, mut reader, acker) = Buffer::build(path.clone(), 10_000).unwrap();
+        write(writer, &["one", "two", "three"]);
+        futures01::future::poll_fn(|| reader.poll()).wait().unwrap();
+        acker.ack(1);
+        drop(reader);
+
+        let mut scanned = Vec::new();
+        scan(&path, |event, size| {
+            assert!(size > HEADER_SIZE);
+            scanned.push(message(&event));
+            true
+        })
+        .unwrap();
+        assert_eq!(scanned, vec!["two", "three"]);
+
+        let mut first = None;
+        scan(&path, |event, _| {
+            first = Some(message(&event));
+            false
+        })
+        .unwrap();
+        assert_eq!(first, Some("two".into()));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn truncates_torn_record() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+
+        {
+            let (writer, _reader, _acker) = Buffer::build(path.clone(), 10_000).unwrap();
+            write(writer, &["one", "two"]);
+        }
+
+        // Simulate a crash in the middle of appending a record.
+        let segment = segment_path(&path, 0);
+        let len = fs::metadata(&segment).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&segment)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let (writer, reader, _acker) = Buffer::build(path, 10_000).unwrap();
+        write(writer, &["three"]);
+        assert_eq!(read(reader), vec!["one", "three"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rolls_and_removes_segments() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+        let (mut writer, mut reader, acker) =
+            Buffer::build_with_segment_size(path.clone(), 10_000, 1).unwrap();
+
+        for message in &["one", "two", "three"] {
+            writer.start_send(event(message)).unwrap();
+            writer.poll_complete().unwrap();
+        }
+        drop(writer);
+        assert_eq!(segment_ids(&path).unwrap(), vec![0, 1, 2]);
+
+        for _ in 0..3 {
+            futures01::future::poll_fn(|| reader.poll()).wait().unwrap();
+        }
+        acker.ack(3);
+        assert_eq!(
+            futures01::future::poll_fn(|| reader.poll()).wait().unwrap(),
+            None
+        );
+        assert_eq!(segment_ids(&path).unwrap(), vec![2]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn finalizes_events_once_written() {
+        let dir = tempdir().unwrap();
+        let (mut writer, _reader, _acker) =
+            Buffer::build(dir.path().join("buffer"), 10_000).unwrap();
+
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let mut event = event("one");
+        event.add_batch_notifier(batch);
+
+        writer.start_send(event).unwrap();
+        writer.poll_complete().unwrap();
+        assert_eq!(receiver.now_or_never(), Some(BatchStatus::Delivered));
+    }
+}
@@ -0,0 +1,195 @@
+//! Dropping of buffered events older than the buffer's `max_age`.
+//!
+//! The age of an event is taken from its timestamp, so events without one
+//! never expire. Expired events are dropped as they are read, before the sink
+//! sees them. Since sinks acknowledge events by position, the expired events
+//! are acknowledged along with the events around them, in their original
+//! order, so that disk buffers can delete them.
+
+use super::Acker;
+use crate::{
+    config::log_schema,
+    event::{Event, EventStatus, Value},
+    internal_events::BufferEventsExpired,
+};
+use chrono::{DateTime, Utc};
+use futures01::{Async, Poll, Stream};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Drops the events of `stream` older than `max_age`, returning the `Acker`
+/// the sink should use in place of `acker`.
+pub fn expire_events(
+    stream: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+    acker: Acker,
+    max_age: Duration,
+) -> (Box<dyn Stream<Item = Event, Error = ()> + Send>, Acker) {
+    let max_age =
+        chrono::Duration::from_std(max_age).unwrap_or_else(|_| chrono::Duration::max_value());
+
+    // Memory buffers have nothing to acknowledge.
+    let (pending, acker) = match acker {
+        Acker::Null => (None, Acker::Null),
+        acker => {
+            let pending = Arc::new(PendingExpiry {
+                pending: Mutex::new(VecDeque::new()),
+                inner: acker,
+            });
+            (Some(Arc::clone(&pending)), Acker::Expiry(pending))
+        }
+    };
+
+    let stream = ExpiringReader {
+        inner: stream,
+        max_age,
+        pending,
+    };
+    (Box::new(stream), acker)
+}
+
+fn timestamp(event: &Event) -> Option<DateTime<Utc>> {
+    match event {
+        Event::Log(log) => match log.get(log_schema().timestamp_key()) {
+            Some(Value::Timestamp(timestamp)) => Some(*timestamp),
+            _ => None,
+        },
+        Event::Metric(metric) => metric.timestamp,
+    }
+}
+
+struct ExpiringReader<S> {
+    inner: S,
+    max_age: chrono::Duration,
+    pending: Option<Arc<PendingExpiry>>,
+}
+
+impl<S: Stream<Item = Event, Error = ()>> Stream for ExpiringReader<S> {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let now = Utc::now();
+        let mut expired = 0;
+
+        let result = loop {
+            let event = match self.inner.poll() {
+                Ok(Async::Ready(Some(event))) => event,
+                other => break other,
+            };
+
+            let is_expired =
+                timestamp(&event).map_or(false, |timestamp| now - timestamp > self.max_age);
+            if let Some(pending) = &self.pending {
+                pending.push(is_expired);
+            }
+            if !is_expired {
+                break Ok(Async::Ready(Some(event)));
+            }
+            expired += 1;
+        };
+
+        if expired > 0 {
+            emit!(BufferEventsExpired { count: expired });
+        }
+        result
+    }
+}
+
+/// The order of the events read from the buffer, telling apart the events
+/// handed to the sink from the expired ones.
+#[derive(Debug)]
+pub struct PendingExpiry {
+    /// `true` for an expired event.
+    pending: Mutex<VecDeque<bool>>,
+    inner: Acker,
+}
+
+impl PendingExpiry {
+    fn push(&self, expired: bool) {
+        let mut pending = self.pending.lock().unwrap();
+        if expired && pending.is_empty() {
+            // Nothing is waiting on the sink, so there is no one to wait for.
+            drop(pending);
+            self.inner.ack_with_status(1, EventStatus::Dropped);
+        } else {
+            pending.push_back(expired);
+        }
+    }
+
+    pub(super) fn ack(&self, num: usize, status: EventStatus) {
+        // Runs of acknowledgements to pass on, in order.
+        let mut runs: Vec<(usize, EventStatus)> = Vec::new();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            let mut remaining = num;
+            while let Some(&expired) = pending.front() {
+                let status = if expired {
+                    EventStatus::Dropped
+                } else if remaining > 0 {
+                    remaining -= 1;
+                    status
+                } else {
+                    break;
+                };
+                pending.pop_front();
+                match runs.last_mut() {
+                    Some((count, last)) if *last == status => *count += 1,
+                    _ => runs.push((1, status)),
+                }
+            }
+        }
+        for (count, status) in runs {
+            self.inner.ack_with_status(count, status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures01::{stream, Future};
+    use std::sync::atomic::Ordering;
+
+    fn event(age_secs: i64) -> Event {
+        let mut event = Event::from(format!("{}", age_secs));
+        event.as_mut_log().insert(
+            log_schema().timestamp_key(),
+            Utc::now() - chrono::Duration::seconds(age_secs),
+        );
+        event
+    }
+
+    #[test]
+    fn drops_expired_events_and_acks_them_in_order() {
+        let (acker, acked) = Acker::new_for_testing();
+        let input = stream::iter_ok(vec![event(100), event(1), event(100), event(1), event(100)]);
+        let (output, acker) = expire_events(Box::new(input), acker, Duration::from_secs(10));
+
+        let events = output.collect().wait().unwrap();
+        let messages = events
+            .iter()
+            .map(|event| event.as_log()["message"].to_string_lossy())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, vec!["1", "1"]);
+
+        // The first event expired before anything was handed to the sink.
+        assert_eq!(acked.load(Ordering::Relaxed), 1);
+        acker.ack(1);
+        assert_eq!(acked.load(Ordering::Relaxed), 3);
+        acker.ack(1);
+        assert_eq!(acked.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn events_without_timestamp_never_expire() {
+        let mut event = Event::from("message");
+        event.as_mut_log().remove(log_schema().timestamp_key());
+        let input = stream::iter_ok(vec![event]);
+        let (output, _acker) = expire_events(Box::new(input), Acker::Null, Duration::from_secs(0));
+
+        assert_eq!(output.collect().wait().unwrap().len(), 1);
+    }
+}
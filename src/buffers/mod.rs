@@ -1,14 +1,25 @@
-use crate::Event;
-use futures01::{sync::mpsc, task::AtomicTask, AsyncSink, Poll, Sink, StartSend, Stream};
+use crate::{
+    event::{EventFinalizers, EventStatus},
+    internal_events::BufferUsageReported,
+    Event,
+};
+use futures01::{
+    sync::mpsc, task::AtomicTask, try_ready, Async, AsyncSink, Poll, Sink, StartSend, Stream,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex,
 };
+use std::time::Duration;
 
-#[cfg(feature = "leveldb")]
+pub mod cmd;
+pub mod dead_letter;
 pub mod disk;
+pub mod expiry;
+pub mod overflow;
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(tag = "type")]
@@ -19,12 +30,20 @@ pub enum BufferConfig {
         max_events: usize,
         #[serde(default)]
         when_full: WhenFull,
+        /// Size of the disk buffer events spill over to with `WhenFull::Overflow`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_size: Option<usize>,
+        /// Age in seconds past which events are dropped instead of being read.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_age: Option<u64>,
     },
-    #[cfg(feature = "leveldb")]
     Disk {
         max_size: usize,
         #[serde(default)]
         when_full: WhenFull,
+        /// Age in seconds past which events are dropped instead of being read.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_age: Option<u64>,
     },
 }
 
@@ -33,6 +52,8 @@ impl Default for BufferConfig {
         BufferConfig::Memory {
             max_events: BufferConfig::memory_max_events(),
             when_full: Default::default(),
+            max_size: None,
+            max_age: None,
         }
     }
 }
@@ -42,6 +63,9 @@ impl Default for BufferConfig {
 pub enum WhenFull {
     Block,
     DropNewest,
+    /// Only for memory buffers: write events to a disk buffer while the
+    /// memory buffer is full.
+    Overflow,
 }
 
 impl Default for WhenFull {
@@ -51,33 +75,65 @@ impl Default for WhenFull {
 }
 
 pub enum BufferInputCloner {
-    Memory(mpsc::Sender<Event>, WhenFull),
-    #[cfg(feature = "leveldb")]
-    Disk(disk::Writer, WhenFull),
+    Memory(mpsc::Sender<Event>, WhenFull, Arc<BufferUsage>),
+    Disk(disk::Writer, WhenFull, Arc<BufferUsage>),
+    Overflow(overflow::Writer, Arc<BufferUsage>),
+    /// The input of a transform, which takes batches of events as they come.
+    Batches(mpsc::Sender<Vec<Event>>),
 }
 
 impl BufferInputCloner {
     pub fn get(&self) -> Box<dyn Sink<SinkItem = Event, SinkError = ()> + Send> {
         match self {
-            BufferInputCloner::Memory(tx, when_full) => {
+            BufferInputCloner::Memory(tx, when_full, usage) => {
                 let inner = tx.clone().sink_map_err(|e| error!("sender error: {:?}", e));
+                let inner = UsageTracker::new(inner, usage);
                 if when_full == &WhenFull::DropNewest {
-                    Box::new(DropWhenFull { inner })
+                    Box::new(DropWhenFull::new(inner, usage))
                 } else {
                     Box::new(inner)
                 }
             }
 
-            #[cfg(feature = "leveldb")]
-            BufferInputCloner::Disk(writer, when_full) => {
+            BufferInputCloner::Disk(writer, when_full, usage) => {
+                let inner = UsageTracker::new(writer.clone(), usage);
                 if when_full == &WhenFull::DropNewest {
-                    Box::new(DropWhenFull {
-                        inner: writer.clone(),
-                    })
+                    Box::new(DropWhenFull::new(inner, usage))
                 } else {
-                    Box::new(writer.clone())
+                    Box::new(inner)
                 }
             }
+
+            BufferInputCloner::Overflow(writer, usage) => {
+                Box::new(UsageTracker::new(writer.clone(), usage))
+            }
+
+            BufferInputCloner::Batches(tx) => Box::new(
+                tx.clone()
+                    .sink_map_err(|e| error!("sender error: {:?}", e))
+                    .with(|event| Ok::<_, ()>(vec![event])),
+            ),
+        }
+    }
+
+    /// Like `get`, for the batches of events passed around the topology.
+    pub fn get_batched(&self) -> Box<dyn Sink<SinkItem = Vec<Event>, SinkError = ()> + Send> {
+        match self {
+            BufferInputCloner::Batches(tx) => {
+                Box::new(tx.clone().sink_map_err(|e| error!("sender error: {:?}", e)))
+            }
+            // Buffers hold events one by one.
+            _ => Box::new(self.get().with_flat_map(futures01::stream::iter_ok)),
+        }
+    }
+
+    /// The usage of the buffer, if this is the input of one.
+    pub fn usage(&self) -> Option<&Arc<BufferUsage>> {
+        match self {
+            BufferInputCloner::Memory(_, _, usage)
+            | BufferInputCloner::Disk(_, _, usage)
+            | BufferInputCloner::Overflow(_, usage) => Some(usage),
+            BufferInputCloner::Batches(_) => None,
         }
     }
 }
@@ -88,7 +144,14 @@ impl BufferConfig {
         500
     }
 
-    #[cfg_attr(not(feature = "leveldb"), allow(unused))]
+    fn max_age(&self) -> Option<Duration> {
+        match self {
+            BufferConfig::Memory { max_age, .. } | BufferConfig::Disk { max_age, .. } => {
+                max_age.map(Duration::from_secs)
+            }
+        }
+    }
+
     pub fn build(
         &self,
         data_dir: &Option<PathBuf>,
@@ -100,32 +163,96 @@ impl BufferConfig {
             Acker,
         ),
         String,
+    > {
+        let (tx, rx, acker) = self.build_buffer(data_dir, sink_name)?;
+        match self.max_age() {
+            Some(max_age) => {
+                let (rx, acker) = expiry::expire_events(rx, acker, max_age);
+                Ok((tx, rx, acker))
+            }
+            None => Ok((tx, rx, acker)),
+        }
+    }
+
+    fn build_buffer(
+        &self,
+        data_dir: &Option<PathBuf>,
+        sink_name: &str,
+    ) -> Result<
+        (
+            BufferInputCloner,
+            Box<dyn Stream<Item = Event, Error = ()> + Send>,
+            Acker,
+        ),
+        String,
     > {
         match &self {
+            BufferConfig::Memory {
+                max_events,
+                when_full: WhenFull::Overflow,
+                max_size,
+                ..
+            } => {
+                let data_dir = data_dir
+                    .as_ref()
+                    .ok_or_else(|| "Must set data_dir to use overflow buffering.".to_string())?;
+                let max_size = max_size
+                    .ok_or_else(|| "Must set max_size to use overflow buffering.".to_string())?;
+
+                let (disk_tx, disk_rx, disk_acker) =
+                    disk::open(&data_dir, sink_name, max_size).map_err(|err| err.to_string())?;
+                let usage = Arc::new(BufferUsage {
+                    events: AtomicUsize::new(disk_tx.recovered_events()),
+                    disk_size: Some(disk_tx.current_size()),
+                    max_events: Some(*max_events),
+                    max_byte_size: Some(max_size),
+                    ..Default::default()
+                });
+                let (tx, rx, acker) = overflow::build(*max_events, disk_tx, disk_rx, disk_acker);
+                let rx = Box::new(UsageTracker::new(rx, &usage));
+                let tx = BufferInputCloner::Overflow(tx, usage);
+                Ok((tx, rx, acker))
+            }
+
             BufferConfig::Memory {
                 max_events,
                 when_full,
+                ..
             } => {
                 let (tx, rx) = mpsc::channel(*max_events);
-                let tx = BufferInputCloner::Memory(tx, *when_full);
-                let rx = Box::new(rx);
+                let usage = Arc::new(BufferUsage {
+                    max_events: Some(*max_events),
+                    ..Default::default()
+                });
+                let rx = Box::new(UsageTracker::new(rx, &usage));
+                let tx = BufferInputCloner::Memory(tx, *when_full, usage);
                 Ok((tx, rx, Acker::Null))
             }
 
-            #[cfg(feature = "leveldb")]
+            BufferConfig::Disk {
+                when_full: WhenFull::Overflow,
+                ..
+            } => Err("Overflow is only supported by memory buffers.".to_string()),
+
             BufferConfig::Disk {
                 max_size,
                 when_full,
+                ..
             } => {
                 let data_dir = data_dir
                     .as_ref()
                     .ok_or_else(|| "Must set data_dir to use on-disk buffering.".to_string())?;
-                let buffer_dir = format!("{}_buffer", sink_name);
 
-                let (tx, rx, acker) = disk::open(&data_dir, buffer_dir.as_ref(), *max_size)
-                    .map_err(|err| err.to_string())?;
-                let tx = BufferInputCloner::Disk(tx, *when_full);
-                let rx = Box::new(rx);
+                let (tx, rx, acker) =
+                    disk::open(&data_dir, sink_name, *max_size).map_err(|err| err.to_string())?;
+                let usage = Arc::new(BufferUsage {
+                    events: AtomicUsize::new(tx.recovered_events()),
+                    disk_size: Some(tx.current_size()),
+                    max_byte_size: Some(*max_size),
+                    ..Default::default()
+                });
+                let rx = Box::new(UsageTracker::new(rx, &usage));
+                let tx = BufferInputCloner::Disk(tx, *when_full, usage);
                 Ok((tx, rx, acker))
             }
         }
@@ -135,6 +262,10 @@ impl BufferConfig {
 #[derive(Debug, Clone)]
 pub enum Acker {
     Disk(Arc<AtomicUsize>, Arc<AtomicTask>),
+    Finalizers(Arc<PendingFinalizers>),
+    Overflow(Arc<overflow::PendingOverflow>),
+    Expiry(Arc<expiry::PendingExpiry>),
+    DeadLetter(Arc<dead_letter::DeadLetters>),
     Null,
 }
 
@@ -146,6 +277,13 @@ impl Acker {
     // This is primary used by the on-disk buffer to know which events are okay to
     // delete from disk.
     pub fn ack(&self, num: usize) {
+        self.ack_with_status(num, EventStatus::Delivered);
+    }
+
+    // Same as `ack`, but lets the sink report whether the events were actually
+    // delivered. The status is passed on to the sources that asked to be
+    // notified about the delivery of these events.
+    pub fn ack_with_status(&self, num: usize, status: EventStatus) {
         // Only ack items if the amount to ack is larger than zero.
         if num > 0 {
             match self {
@@ -154,6 +292,22 @@ impl Acker {
                     counter.fetch_add(num, Ordering::Relaxed);
                     notifier.notify();
                 }
+                Acker::Finalizers(pending) => pending.finalize(num, status),
+                Acker::Overflow(pending) => pending.ack(num, status),
+                Acker::Expiry(pending) => pending.ack(num, status),
+                Acker::DeadLetter(dead_letters) => dead_letters.ack(num, status),
+            }
+        }
+    }
+
+    // Same as `ack_with_status` with `EventStatus::Errored`, for events the sink
+    // has given up on for good. If the sink has a dead-letter output, the events
+    // are sent there along with the reason.
+    pub fn reject(&self, num: usize, rejection: dead_letter::Rejection) {
+        if num > 0 {
+            match self {
+                Acker::DeadLetter(dead_letters) => dead_letters.reject(num, rejection),
+                _ => self.ack_with_status(num, EventStatus::Errored),
             }
         }
     }
@@ -167,8 +321,81 @@ impl Acker {
     }
 }
 
+/// Finalizers of the events a sink has pulled from its buffer but not yet
+/// acknowledged, in the order the sink received them.
+#[derive(Debug, Default)]
+pub struct PendingFinalizers(Mutex<VecDeque<EventFinalizers>>);
+
+impl PendingFinalizers {
+    fn push(&self, finalizers: EventFinalizers) {
+        self.0.lock().unwrap().push_back(finalizers);
+    }
+
+    fn finalize(&self, num: usize, status: EventStatus) {
+        let mut pending = self.0.lock().unwrap();
+        let num = num.min(pending.len());
+        for finalizers in pending.drain(..num) {
+            finalizers.update_status(status);
+        }
+    }
+}
+
+impl Drop for PendingFinalizers {
+    fn drop(&mut self) {
+        // Whatever the sink didn't acknowledge before going away was not delivered.
+        if let Ok(pending) = self.0.get_mut() {
+            for finalizers in pending.drain(..) {
+                finalizers.update_status(EventStatus::Errored);
+            }
+        }
+    }
+}
+
+/// Takes the finalizers off the events a sink reads, so that the sink can
+/// resolve them through the returned `Acker` once it has delivered them.
+pub fn track_finalizers(
+    stream: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+) -> (Box<dyn Stream<Item = Event, Error = ()> + Send>, Acker) {
+    let pending = Arc::new(PendingFinalizers::default());
+    let stream = FinalizerTracker {
+        inner: stream,
+        pending: Arc::clone(&pending),
+    };
+    (Box::new(stream), Acker::Finalizers(pending))
+}
+
+struct FinalizerTracker<S> {
+    inner: S,
+    pending: Arc<PendingFinalizers>,
+}
+
+impl<S: Stream<Item = Event, Error = ()>> Stream for FinalizerTracker<S> {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let event = try_ready!(self.inner.poll()).map(|mut event| {
+            // Every event gets an entry, even without finalizers, since
+            // acknowledgements are positional.
+            self.pending.push(event.take_finalizers());
+            event
+        });
+        Ok(Async::Ready(event))
+    }
+}
+
 pub struct DropWhenFull<S> {
     inner: S,
+    usage: Arc<BufferUsage>,
+}
+
+impl<S> DropWhenFull<S> {
+    pub fn new(inner: S, usage: &Arc<BufferUsage>) -> Self {
+        Self {
+            inner,
+            usage: Arc::clone(usage),
+        }
+    }
 }
 
 impl<S: Sink> Sink for DropWhenFull<S> {
@@ -182,6 +409,7 @@ impl<S: Sink> Sink for DropWhenFull<S> {
                     message = "Shedding load; dropping event.",
                     rate_limit_secs = 10
                 );
+                self.usage.discarded.fetch_add(1, Ordering::Relaxed);
                 Ok(AsyncSink::Ready)
             }
             other => other,
@@ -193,12 +421,123 @@ impl<S: Sink> Sink for DropWhenFull<S> {
     }
 }
 
+/// How full the buffer of a sink is. The writers count the events going into
+/// the buffer and the reader the events coming out of it, along with their
+/// estimated size, while the size of disk buffers is kept by the disk buffer
+/// itself.
+#[derive(Debug, Default)]
+pub struct BufferUsage {
+    events: AtomicUsize,
+    estimated_size: AtomicUsize,
+    discarded: AtomicUsize,
+    disk_size: Option<Arc<AtomicUsize>>,
+    max_events: Option<usize>,
+    max_byte_size: Option<usize>,
+}
+
+impl BufferUsage {
+    /// Emits the current usage as internal metrics. Memory buffers report the
+    /// estimated size of their events, as they have no encoded size.
+    pub fn report(&self) {
+        let byte_size = match &self.disk_size {
+            Some(size) => size.load(Ordering::Relaxed),
+            None => self.estimated_size.load(Ordering::Relaxed),
+        };
+        emit!(BufferUsageReported {
+            events: self.events.load(Ordering::Relaxed),
+            byte_size,
+            max_events: self.max_events,
+            max_byte_size: self.max_byte_size,
+            discarded: self.discarded.swap(0, Ordering::Relaxed),
+        });
+    }
+}
+
+/// Reports the usage of a buffer every second, for as long as it is polled.
+/// Meant to run within the task of the sink reading the buffer, so that the
+/// metrics are tagged with the sink.
+pub async fn report_usage(usage: Arc<BufferUsage>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        interval.tick().await;
+        usage.report();
+    }
+}
+
+/// Counts the events going into a buffer, and their estimated size, when
+/// wrapping one of its writers, and the events coming out of it when wrapping
+/// its reader.
+pub struct UsageTracker<S> {
+    inner: S,
+    usage: Arc<BufferUsage>,
+}
+
+impl<S> UsageTracker<S> {
+    fn new(inner: S, usage: &Arc<BufferUsage>) -> Self {
+        Self {
+            inner,
+            usage: Arc::clone(usage),
+        }
+    }
+}
+
+impl<S: Sink<SinkItem = Event>> Sink for UsageTracker<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        // Counted before sending, since the reader may see the event right away.
+        let size = item.estimated_size();
+        self.usage.events.fetch_add(1, Ordering::Relaxed);
+        self.usage.estimated_size.fetch_add(size, Ordering::Relaxed);
+        let result = self.inner.start_send(item);
+        if !matches!(result, Ok(AsyncSink::Ready)) {
+            self.usage.events.fetch_sub(1, Ordering::Relaxed);
+            self.usage.estimated_size.fetch_sub(size, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.close()
+    }
+}
+
+impl<S: Stream<Item = Event>> Stream for UsageTracker<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let item = try_ready!(self.inner.poll());
+        if let Some(event) = &item {
+            self.usage.events.fetch_sub(1, Ordering::Relaxed);
+            self.usage
+                .estimated_size
+                .fetch_sub(event.estimated_size(), Ordering::Relaxed);
+        }
+        Ok(Async::Ready(item))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Acker, BufferConfig, DropWhenFull, WhenFull};
-    use futures::compat::Future01CompatExt;
-    use futures01::{future, sync::mpsc, task::AtomicTask, Async, AsyncSink, Sink, Stream};
-    use std::sync::{atomic::AtomicUsize, Arc};
+    use super::{track_finalizers, Acker, BufferConfig, BufferUsage, DropWhenFull, WhenFull};
+    use crate::{
+        event::{BatchNotifier, BatchStatus, EventStatus},
+        Event,
+    };
+    use futures::{compat::Future01CompatExt, FutureExt};
+    use futures01::{
+        future, stream, sync::mpsc, task::AtomicTask, Async, AsyncSink, Future, Sink, Stream,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
     use tokio01_test::task::MockTask;
 
     #[tokio::test]
@@ -206,7 +545,8 @@ mod test {
         future::lazy(|| {
             let (tx, mut rx) = mpsc::channel(2);
 
-            let mut tx = DropWhenFull { inner: tx };
+            let usage = Arc::new(BufferUsage::default());
+            let mut tx = DropWhenFull::new(tx, &usage);
 
             assert_eq!(tx.start_send(1), Ok(AsyncSink::Ready));
             assert_eq!(tx.start_send(2), Ok(AsyncSink::Ready));
@@ -217,6 +557,7 @@ mod test {
             assert_eq!(rx.poll(), Ok(Async::Ready(Some(2))));
             assert_eq!(rx.poll(), Ok(Async::Ready(Some(3))));
             assert_eq!(rx.poll(), Ok(Async::NotReady));
+            assert_eq!(usage.discarded.load(Ordering::Relaxed), 1);
 
             future::ok::<(), ()>(())
         })
@@ -225,6 +566,36 @@ mod test {
         .unwrap();
     }
 
+    #[test]
+    fn usage_counts_buffered_events() {
+        let config = BufferConfig::Memory {
+            max_events: 10,
+            when_full: WhenFull::Block,
+            max_size: None,
+            max_age: None,
+        };
+        let (tx, mut rx, _acker) = config.build(&None, "sink").unwrap();
+        let usage = Arc::clone(tx.usage().unwrap());
+
+        let sink = tx
+            .get()
+            .send_all(stream::iter_ok::<_, ()>(vec![
+                Event::from("one"),
+                Event::from("two"),
+            ]))
+            .wait()
+            .unwrap()
+            .0;
+        drop(sink);
+        assert_eq!(usage.events.load(Ordering::Relaxed), 2);
+        let size = usage.estimated_size.load(Ordering::Relaxed);
+        assert!(size > 0);
+
+        future::poll_fn(|| rx.poll()).wait().unwrap();
+        assert_eq!(usage.events.load(Ordering::Relaxed), 1);
+        assert!(usage.estimated_size.load(Ordering::Relaxed) < size);
+    }
+
     #[test]
     fn ack_with_none() {
         let counter = Arc::new(AtomicUsize::new(0));
@@ -242,6 +613,22 @@ mod test {
         assert!(mock.is_notified());
     }
 
+    #[test]
+    fn ack_resolves_tracked_finalizers() {
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let mut event = Event::new_empty_log();
+        event.add_batch_notifier(batch);
+
+        let (rx, acker) = track_finalizers(Box::new(stream::iter_ok(vec![event])));
+        let events = rx.collect().wait().unwrap();
+        assert_eq!(events.len(), 1);
+        drop(events);
+
+        assert_eq!((&mut receiver).now_or_never(), None);
+        acker.ack_with_status(1, EventStatus::Errored);
+        assert_eq!(receiver.now_or_never(), Some(BatchStatus::Errored));
+    }
+
     #[test]
     fn config_default_values() {
         fn check(source: &str, config: BufferConfig) {
@@ -256,6 +643,8 @@ mod test {
             BufferConfig::Memory {
                 max_events: 500,
                 when_full: WhenFull::Block,
+                max_size: None,
+                max_age: None,
             },
         );
 
@@ -267,6 +656,8 @@ mod test {
             BufferConfig::Memory {
                 max_events: 100,
                 when_full: WhenFull::Block,
+                max_size: None,
+                max_age: None,
             },
         );
 
@@ -278,10 +669,25 @@ mod test {
             BufferConfig::Memory {
                 max_events: 500,
                 when_full: WhenFull::DropNewest,
+                max_size: None,
+                max_age: None,
+            },
+        );
+
+        check(
+            r#"
+          type = "memory"
+          when_full = "overflow"
+          max_size = 1024
+          "#,
+            BufferConfig::Memory {
+                max_events: 500,
+                when_full: WhenFull::Overflow,
+                max_size: Some(1024),
+                max_age: None,
             },
         );
 
-        #[cfg(feature = "leveldb")]
         check(
             r#"
           type = "disk"
@@ -290,6 +696,7 @@ mod test {
             BufferConfig::Disk {
                 max_size: 1024,
                 when_full: WhenFull::Block,
+                max_age: None,
             },
         );
     }
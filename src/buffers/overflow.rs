@@ -0,0 +1,266 @@
+//! A memory buffer that spills over to a disk buffer.
+//!
+//! Events go through the in-memory channel for as long as it has room. Once it
+//! fills up, new events are written to the disk buffer instead, and keep going
+//! there until the reader has caught up with everything on disk. Since nothing
+//! is added to the channel while there are events on disk, the reader can
+//! simply empty the channel before moving on to the disk buffer, and the
+//! original order of the events is kept.
+
+use super::{disk, Acker};
+use crate::event::{Event, EventFinalizers, EventStatus};
+use futures01::{sync::mpsc, Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+pub fn build(
+    max_events: usize,
+    disk_writer: disk::Writer,
+    disk_reader: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+    disk_acker: Acker,
+) -> (Writer, Reader, Acker) {
+    let (tx, rx) = mpsc::channel(max_events);
+    // Events left on disk by a previous run come before anything new.
+    let on_disk = Arc::new(AtomicUsize::new(disk_writer.recovered_events()));
+    let pending = Arc::new(PendingOverflow {
+        pending: Mutex::new(VecDeque::new()),
+        disk_acker,
+    });
+
+    let writer = Writer {
+        memory: tx,
+        disk: disk_writer,
+        on_disk: Arc::clone(&on_disk),
+    };
+    let reader = Reader {
+        memory: rx,
+        memory_done: false,
+        disk: disk_reader,
+        on_disk,
+        pending: Arc::clone(&pending),
+    };
+
+    (writer, reader, Acker::Overflow(pending))
+}
+
+#[derive(Clone)]
+pub struct Writer {
+    memory: mpsc::Sender<Event>,
+    disk: disk::Writer,
+    /// Number of events written to disk that haven't been read yet.
+    on_disk: Arc<AtomicUsize>,
+}
+
+impl Sink for Writer {
+    type SinkItem = Event;
+    type SinkError = ();
+
+    fn start_send(&mut self, event: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        let event = if self.on_disk.load(Ordering::Acquire) == 0 {
+            match self.memory.start_send(event) {
+                Ok(AsyncSink::Ready) => return Ok(AsyncSink::Ready),
+                Ok(AsyncSink::NotReady(event)) => event,
+                Err(error) => {
+                    error!("sender error: {:?}", error);
+                    return Err(());
+                }
+            }
+        } else {
+            event
+        };
+
+        // Counted before the write, since the reader may see the event as
+        // soon as the disk buffer flushes it.
+        self.on_disk.fetch_add(1, Ordering::AcqRel);
+        match self.disk.start_send(event) {
+            Ok(AsyncSink::Ready) => Ok(AsyncSink::Ready),
+            other => {
+                self.on_disk.fetch_sub(1, Ordering::AcqRel);
+                other
+            }
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        // The channel has nothing to flush, and waiting for it to have room
+        // again is exactly what spilling to disk avoids.
+        self.disk.poll_complete()
+    }
+}
+
+pub struct Reader {
+    memory: mpsc::Receiver<Event>,
+    memory_done: bool,
+    disk: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+    on_disk: Arc<AtomicUsize>,
+    pending: Arc<PendingOverflow>,
+}
+
+impl Stream for Reader {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if !self.memory_done {
+            match self.memory.poll()? {
+                Async::Ready(Some(mut event)) => {
+                    self.pending.push(Some(event.take_finalizers()));
+                    return Ok(Async::Ready(Some(event)));
+                }
+                Async::Ready(None) => self.memory_done = true,
+                Async::NotReady => {}
+            }
+        }
+
+        // The channel is empty, so whatever is on disk is next in line. This
+        // is polled even when nothing is on disk to let the disk buffer clean
+        // up acknowledged events.
+        match self.disk.poll()? {
+            Async::Ready(Some(event)) => {
+                self.on_disk.fetch_sub(1, Ordering::AcqRel);
+                self.pending.push(None);
+                Ok(Async::Ready(Some(event)))
+            }
+            Async::Ready(None) if self.memory_done => Ok(Async::Ready(None)),
+            Async::Ready(None) | Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// Acknowledgement state of the events handed out by the `Reader`, in order.
+/// Events read from memory keep their finalizers here until acknowledged,
+/// while events read from disk are counted towards the disk buffer's acker.
+#[derive(Debug)]
+pub struct PendingOverflow {
+    pending: Mutex<VecDeque<Option<EventFinalizers>>>,
+    disk_acker: Acker,
+}
+
+impl PendingOverflow {
+    fn push(&self, finalizers: Option<EventFinalizers>) {
+        self.pending.lock().unwrap().push_back(finalizers);
+    }
+
+    pub(super) fn ack(&self, num: usize, status: EventStatus) {
+        let mut disk_acks = 0;
+        {
+            let mut pending = self.pending.lock().unwrap();
+            let num = num.min(pending.len());
+            for entry in pending.drain(..num) {
+                match entry {
+                    Some(finalizers) => finalizers.update_status(status),
+                    None => disk_acks += 1,
+                }
+            }
+        }
+        self.disk_acker.ack(disk_acks);
+    }
+}
+
+impl Drop for PendingOverflow {
+    fn drop(&mut self) {
+        // Whatever the sink didn't acknowledge before going away was not delivered.
+        if let Ok(pending) = self.pending.get_mut() {
+            for finalizers in pending.drain(..).flatten() {
+                finalizers.update_status(EventStatus::Errored);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffers::{BufferConfig, WhenFull},
+        event::Event,
+    };
+    use futures01::{stream, Future, Sink, Stream};
+    use tempfile::tempdir;
+
+    fn events(range: std::ops::Range<usize>) -> Vec<Event> {
+        range.map(|i| Event::from(format!("{}", i))).collect()
+    }
+
+    fn messages(events: Vec<Event>) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| event.as_log()["message"].to_string_lossy())
+            .collect()
+    }
+
+    fn config() -> BufferConfig {
+        BufferConfig::Memory {
+            max_events: 2,
+            when_full: WhenFull::Overflow,
+            max_size: Some(1_000_000),
+            max_age: None,
+        }
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn spills_to_disk_in_order() {
+        let dir = tempdir().unwrap();
+        let (tx, rx, acker) = config()
+            .build(&Some(dir.path().to_path_buf()), "sink")
+            .unwrap();
+
+        let sink = tx
+            .get()
+            .send_all(stream::iter_ok::<_, ()>(events(0..100)))
+            .wait()
+            .unwrap()
+            .0;
+        drop(sink);
+        drop(tx);
+
+        let received = rx.collect().wait().unwrap();
+        acker.ack(received.len());
+        assert_eq!(
+            messages(received),
+            (0..100).map(|i| i.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn unacked_disk_events_survive_restart() {
+        let dir = tempdir().unwrap();
+        let data_dir = Some(dir.path().to_path_buf());
+
+        {
+            let (tx, _rx, _acker) = config().build(&data_dir, "sink").unwrap();
+            // Nothing is reading, so everything past the channel ends up on disk.
+            let sink = tx
+                .get()
+                .send_all(stream::iter_ok::<_, ()>(events(0..10)))
+                .wait()
+                .unwrap()
+                .0;
+            drop(sink);
+        }
+
+        let (tx, rx, _acker) = config().build(&data_dir, "sink").unwrap();
+        let sink = tx
+            .get()
+            .send_all(stream::iter_ok::<_, ()>(events(10..12)))
+            .wait()
+            .unwrap()
+            .0;
+        drop(sink);
+        drop(tx);
+
+        // Whatever made it to disk in the first run is read back, followed
+        // by the new events.
+        let received = messages(rx.collect().wait().unwrap());
+        assert!(received.len() > 2);
+        let first: usize = received[0].parse().unwrap();
+        assert_eq!(
+            received,
+            (first..12).map(|i| i.to_string()).collect::<Vec<_>>()
+        );
+    }
+}
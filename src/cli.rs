@@ -1,6 +1,6 @@
+use crate::{buffers, config, generate, get_version, graph, list, schema, unit_test, validate};
 #[cfg(feature = "api-client")]
-use crate::top;
-use crate::{generate, get_version, list, unit_test, validate};
+use crate::{tap, top};
 use std::path::PathBuf;
 use structopt::{clap::AppSettings, StructOpt};
 
@@ -32,7 +32,10 @@ impl Opts {
         let (quiet_level, verbose_level) = match self.sub_command {
             Some(SubCommand::Validate(_))
             | Some(SubCommand::Generate(_))
-            | Some(SubCommand::List(_)) => {
+            | Some(SubCommand::List(_))
+            | Some(SubCommand::Graph(_))
+            | Some(SubCommand::Schema(_))
+            | Some(SubCommand::Buffer(_)) => {
                 if self.root.verbose == 0 {
                     (self.root.quiet + 1, self.root.verbose)
                 } else {
@@ -63,6 +66,49 @@ pub struct RootOpts {
     #[structopt(name = "config", short, long, env = "VECTOR_CONFIG")]
     pub config_paths: Vec<PathBuf>,
 
+    /// Read the configuration files as `toml`, `yaml` or `json`. By default
+    /// the format of each file is given by its extension, falling back to
+    /// `toml`.
+    #[structopt(long, env = "VECTOR_CONFIG_FORMAT", possible_values = &["toml", "yaml", "json"])]
+    pub config_format: Option<config::Format>,
+
+    /// Read the components of the configuration from one or more directories,
+    /// each component in a file of its `sources`, `transforms` or `sinks`
+    /// directory named after the component, like `sinks/archive.toml`. Config
+    /// files are only read from the default path when neither `--config` nor
+    /// `--config-dir` is given.
+    #[structopt(long = "config-dir", env = "VECTOR_CONFIG_DIR")]
+    pub config_dirs: Vec<PathBuf>,
+
+    /// Fetch the configuration from an HTTP(S) endpoint instead of reading
+    /// config files, and poll it for changes. Changes are only applied once
+    /// they validate, and the last configuration applied is kept in
+    /// `--config-cache` to start from while the endpoint is unreachable.
+    #[structopt(long, env = "VECTOR_CONFIG_URL")]
+    pub config_url: Option<http::Uri>,
+
+    /// Seconds between two polls of `--config-url`.
+    #[structopt(long, env = "VECTOR_CONFIG_POLL_INTERVAL_SECS", default_value = "30")]
+    pub config_poll_interval_secs: u64,
+
+    /// Bearer token to authenticate to `--config-url` with.
+    #[structopt(long, env = "VECTOR_CONFIG_AUTH_TOKEN", hide_env_values = true)]
+    pub config_auth_token: Option<String>,
+
+    /// File to keep the last known good configuration fetched from
+    /// `--config-url` in.
+    #[structopt(
+        long,
+        env = "VECTOR_CONFIG_CACHE",
+        default_value = "/var/lib/vector/remote_config.json"
+    )]
+    pub config_cache: PathBuf,
+
+    /// Fail to load the configuration when it references environment
+    /// variables that aren't set, instead of replacing them by nothing.
+    #[structopt(long, env = "VECTOR_STRICT_ENV_VARS")]
+    pub strict_env_vars: bool,
+
     /// Exit on startup if any sinks fail healthchecks
     #[structopt(short, long, env = "VECTOR_REQUIRE_HEALTHY")]
     pub require_healthy: bool,
@@ -110,6 +156,15 @@ pub enum SubCommand {
     /// List available components, then exit.
     List(list::Opts),
 
+    /// Render the topology of the target config as a graph in DOT or Mermaid, then exit.
+    Graph(graph::Opts),
+
+    /// Print a JSON Schema of the configuration, then exit.
+    Schema(schema::Opts),
+
+    /// Inspect, dump, replay or delete the disk buffers of sinks, then exit.
+    Buffer(buffers::cmd::Opts),
+
     /// Run Vector config unit tests, then exit. This command is experimental and therefore subject to change.
     /// For guidance on how to write unit tests check out: https://vector.dev/docs/setup/guides/unit-testing/
     Test(unit_test::Opts),
@@ -118,6 +173,10 @@ pub enum SubCommand {
     #[cfg(feature = "api-client")]
     Top(top::Opts),
 
+    /// Print sampled events leaving the components of a local or remote Vector instance, as JSON
+    #[cfg(feature = "api-client")]
+    Tap(tap::Opts),
+
     /// Manage the vector service.
     #[cfg(windows)]
     Service(service::Opts),
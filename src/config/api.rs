@@ -1,7 +1,8 @@
+use crate::{sinks::util::http::Auth, tls::TlsConfig};
 use serde::{Deserialize, Serialize};
 use std::net::{Ipv4Addr, SocketAddr};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Copy, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 #[serde(default)]
 pub struct Options {
     #[serde(default = "default_enabled")]
@@ -12,6 +13,12 @@ pub struct Options {
 
     #[serde(default = "default_playground")]
     pub playground: bool,
+
+    /// The credentials requests have to carry to be served, which also allow them mutations.
+    pub auth: Option<Auth>,
+
+    /// Serves the API over TLS once enabled, with the certificate of `crt_file`.
+    pub tls: Option<TlsConfig>,
 }
 
 impl Default for Options {
@@ -20,6 +27,8 @@ impl Default for Options {
             enabled: default_enabled(),
             playground: default_playground(),
             bind: default_bind(),
+            auth: None,
+            tls: None,
         }
     }
 }
@@ -58,10 +67,26 @@ impl Options {
             },
         };
 
+        let auth = match (self.auth.take(), other.auth) {
+            (Some(a), Some(b)) if a != b => {
+                return Err("Conflicting `api` auth settings.".to_owned())
+            }
+            (a, b) => a.or(b),
+        };
+
+        let tls = match (self.tls.take(), other.tls) {
+            (Some(a), Some(b)) if a != b => {
+                return Err("Conflicting `api` tls settings.".to_owned())
+            }
+            (a, b) => a.or(b),
+        };
+
         let options = Options {
             bind,
             enabled: self.enabled | other.enabled,
             playground: self.playground & other.playground,
+            auth,
+            tls,
         };
 
         *self = options;
@@ -75,6 +100,8 @@ fn bool_merge() {
         enabled: true,
         bind: None,
         playground: false,
+        auth: None,
+        tls: None,
     };
 
     a.merge(Options::default()).unwrap();
@@ -85,6 +112,8 @@ fn bool_merge() {
             enabled: true,
             bind: default_bind(),
             playground: false,
+            auth: None,
+            tls: None,
         }
     );
 }
@@ -96,6 +125,8 @@ fn bind_merge() {
         enabled: true,
         bind: Some(address),
         playground: true,
+        auth: None,
+        tls: None,
     };
 
     a.merge(Options::default()).unwrap();
@@ -106,6 +137,8 @@ fn bind_merge() {
             enabled: true,
             bind: Some(address),
             playground: true,
+            auth: None,
+            tls: None,
         }
     );
 }
@@ -124,3 +157,31 @@ fn bind_conflict() {
 
     assert!(a.merge(b).is_err());
 }
+
+#[test]
+fn auth_and_tls_merge() {
+    let auth = Auth::Bearer {
+        token: "s3cr3t".to_owned(),
+    };
+    let mut a = Options {
+        auth: Some(auth.clone()),
+        ..Options::default()
+    };
+
+    a.merge(Options {
+        tls: Some(TlsConfig::enabled()),
+        ..Options::default()
+    })
+    .unwrap();
+
+    assert_eq!(a.auth, Some(auth));
+    assert_eq!(a.tls, Some(TlsConfig::enabled()));
+
+    let b = Options {
+        auth: Some(Auth::Bearer {
+            token: "other".to_owned(),
+        }),
+        ..Options::default()
+    };
+    assert!(a.merge(b).is_err());
+}
@@ -1,9 +1,13 @@
 #[cfg(feature = "api")]
 use super::api;
 use super::{
-    compiler, default_data_dir, Config, GlobalOptions, SinkConfig, SinkOuter, SourceConfig,
-    TestDefinition, TransformConfig, TransformOuter,
+    compiler, default_data_dir,
+    secret::{SecretBackend, Secrets},
+    template::{Pipeline, Template},
+    Config, GlobalOptions, SinkConfig, SinkOuter, SourceConfig, TestDefinition, TransformConfig,
+    TransformOuter,
 };
+use crate::enrichment_tables::EnrichmentTableConfig;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
@@ -22,7 +26,19 @@ pub struct ConfigBuilder {
     #[serde(default)]
     pub transforms: IndexMap<String, TransformOuter>,
     #[serde(default)]
+    pub templates: IndexMap<String, Template>,
+    #[serde(default)]
+    pub pipelines: IndexMap<String, Pipeline>,
+    #[serde(default)]
+    pub enrichment_tables: IndexMap<String, EnrichmentTableConfig>,
+    #[serde(default)]
+    pub secret: IndexMap<String, SecretBackend>,
+    #[serde(default)]
     pub tests: Vec<TestDefinition>,
+    /// The values of the secrets referenced by the config, to keep them out of
+    /// its errors.
+    #[serde(skip)]
+    pub(super) secrets: Secrets,
 }
 
 impl Clone for ConfigBuilder {
@@ -33,7 +49,9 @@ impl Clone for ConfigBuilder {
         // JSON. Originally we used TOML here but TOML does not
         // support serializing `None`.
         let json = serde_json::to_value(self).unwrap();
-        serde_json::from_value(json).unwrap()
+        let mut builder: Self = serde_json::from_value(json).unwrap();
+        builder.secrets = self.secrets.clone();
+        builder
     }
 }
 
@@ -56,6 +74,8 @@ impl ConfigBuilder {
         let sink = SinkOuter {
             buffer: Default::default(),
             healthcheck: true,
+            dead_letter: false,
+            isolation: Default::default(),
             inner: Box::new(sink),
             inputs,
         };
@@ -73,6 +93,8 @@ impl ConfigBuilder {
         let transform = TransformOuter {
             inner: Box::new(transform),
             inputs,
+            concurrency: None,
+            preserve_order: true,
         };
 
         self.transforms.insert(name.into(), transform);
@@ -117,6 +139,26 @@ impl ConfigBuilder {
                 errors.push(format!("duplicate transform name found: {}", k));
             }
         });
+        with.templates.keys().for_each(|k| {
+            if self.templates.contains_key(k) {
+                errors.push(format!("duplicate template name found: {}", k));
+            }
+        });
+        with.pipelines.keys().for_each(|k| {
+            if self.pipelines.contains_key(k) {
+                errors.push(format!("duplicate pipeline name found: {}", k));
+            }
+        });
+        with.enrichment_tables.keys().for_each(|k| {
+            if self.enrichment_tables.contains_key(k) {
+                errors.push(format!("duplicate enrichment table name found: {}", k));
+            }
+        });
+        with.secret.keys().for_each(|k| {
+            if self.secret.contains_key(k) {
+                errors.push(format!("duplicate secret backend name found: {}", k));
+            }
+        });
         with.tests.iter().for_each(|wt| {
             if self.tests.iter().any(|t| t.name == wt.name) {
                 errors.push(format!("duplicate test name found: {}", wt.name));
@@ -129,7 +171,12 @@ impl ConfigBuilder {
         self.sources.extend(with.sources);
         self.sinks.extend(with.sinks);
         self.transforms.extend(with.transforms);
+        self.templates.extend(with.templates);
+        self.pipelines.extend(with.pipelines);
+        self.enrichment_tables.extend(with.enrichment_tables);
+        self.secret.extend(with.secret);
         self.tests.extend(with.tests);
+        self.secrets.extend(with.secrets);
 
         Ok(())
     }
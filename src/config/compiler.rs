@@ -1,7 +1,15 @@
-use super::{builder::ConfigBuilder, validation, Config, TransformOuter};
+use super::{builder::ConfigBuilder, template, validation, Config, TransformOuter};
 use indexmap::IndexMap;
 
+/// Builds the config, keeping the values of its secrets out of the errors.
 pub fn compile(raw: ConfigBuilder) -> Result<Config, Vec<String>> {
+    let secrets = raw.secrets.clone();
+    compile_secret(raw).map_err(|errors| errors.iter().map(|error| secrets.redact(error)).collect())
+}
+
+fn compile_secret(mut raw: ConfigBuilder) -> Result<Config, Vec<String>> {
+    let pipelines = template::expand(&mut raw)?;
+
     let mut config = Config {
         global: raw.global,
         #[cfg(feature = "api")]
@@ -9,8 +17,10 @@ pub fn compile(raw: ConfigBuilder) -> Result<Config, Vec<String>> {
         sources: raw.sources,
         sinks: raw.sinks,
         transforms: raw.transforms,
+        enrichment_tables: raw.enrichment_tables,
         tests: raw.tests,
-        expansions: Default::default(),
+        expansions: pipelines,
+        secrets: raw.secrets,
     };
 
     let mut errors = Vec::new();
@@ -60,6 +70,8 @@ pub(super) fn expand_macros(config: &mut Config) -> Result<(), Vec<String>> {
                     full_name.clone(),
                     TransformOuter {
                         inputs: t.inputs.clone(),
+                        concurrency: t.concurrency,
+                        preserve_order: t.preserve_order,
                         inner: child,
                     },
                 );
@@ -75,7 +87,7 @@ pub(super) fn expand_macros(config: &mut Config) -> Result<(), Vec<String>> {
     if !errors.is_empty() {
         Err(errors)
     } else {
-        config.expansions = expansions;
+        config.expansions.extend(expansions);
         Ok(())
     }
 }
@@ -1,5 +1,6 @@
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
 use snafu::Snafu;
-use std::marker::PhantomData;
+use std::{fmt, marker::PhantomData};
 use toml::Value;
 
 use super::GenerateConfig;
@@ -17,6 +18,7 @@ pub enum ExampleError {
 pub struct ComponentDescription<T: Sized> {
     pub type_str: &'static str,
     example_value: fn() -> Option<Value>,
+    options: fn() -> Option<&'static [&'static str]>,
     component_type: PhantomData<T>,
 }
 
@@ -27,11 +29,13 @@ where
         std::iter::IntoIterator<Item = &'static ComponentDescription<T>>,
 {
     /// Creates a new component plugin description.
-    /// Configuration example is generated by the `GenerateConfig` trait.
-    pub fn new<B: GenerateConfig>(type_str: &'static str) -> Self {
+    /// Configuration example is generated by the `GenerateConfig` trait, and
+    /// the options are the fields of the config.
+    pub fn new<B: GenerateConfig + DeserializeOwned>(type_str: &'static str) -> Self {
         ComponentDescription {
             type_str,
             example_value: || Some(B::generate_config()),
+            options: options::<B>,
             component_type: PhantomData,
         }
     }
@@ -47,6 +51,18 @@ where
             .and_then(|t| (t.example_value)().ok_or(ExampleError::MissingExample))
     }
 
+    /// Returns the names of the options of a plugin identified by its type,
+    /// if its config declares them.
+    pub fn options(type_str: &str) -> Result<Option<&'static [&'static str]>, ExampleError> {
+        inventory::iter::<ComponentDescription<T>>
+            .into_iter()
+            .find(|t| t.type_str == type_str)
+            .ok_or_else(|| ExampleError::DoesNotExist {
+                type_str: type_str.to_owned(),
+            })
+            .map(|t| (t.options)())
+    }
+
     /// Returns a sorted Vec of all plugins registered of a type.
     pub fn types() -> Vec<&'static str> {
         let mut types = Vec::new();
@@ -57,3 +73,87 @@ where
         types
     }
 }
+
+/// The names of the fields of config `B`, as its `Deserialize` implementation
+/// declares them when deserializing a struct. Configs that aren't structs, or
+/// that flatten some of their fields, don't declare them.
+fn options<B: DeserializeOwned>() -> Option<&'static [&'static str]> {
+    let mut options = None;
+    let _ = B::deserialize(Fields(&mut options));
+    options
+}
+
+/// A deserializer recording the fields of the struct it is asked for, and
+/// failing to deserialize anything.
+struct Fields<'a>(&'a mut Option<&'static [&'static str]>);
+
+#[derive(Debug)]
+struct NoValue;
+
+impl fmt::Display for NoValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("no value to deserialize")
+    }
+}
+
+impl std::error::Error for NoValue {}
+
+impl de::Error for NoValue {
+    fn custom<M: fmt::Display>(_: M) -> Self {
+        NoValue
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for Fields<'a> {
+    type Error = NoValue;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, NoValue> {
+        Err(NoValue)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, NoValue> {
+        *self.0 = Some(fields);
+        Err(NoValue)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[test]
+    fn options_are_the_fields_of_the_config() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            include: Vec<String>,
+            #[serde(rename = "max_line_bytes")]
+            max_bytes: Option<usize>,
+        }
+
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Flattened {
+            #[serde(flatten)]
+            config: Config,
+        }
+
+        assert_eq!(
+            options::<Config>(),
+            Some(&["include", "max_line_bytes"][..])
+        );
+        assert_eq!(options::<Flattened>(), None);
+    }
+}
@@ -0,0 +1,81 @@
+//! The languages configuration files can be written in.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Toml
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(Format::Toml),
+            "yaml" => Ok(Format::Yaml),
+            "json" => Ok(Format::Json),
+            s => Err(format!(
+                "{} is not a valid option, expected `toml`, `yaml` or `json`",
+                s
+            )),
+        }
+    }
+}
+
+impl Format {
+    /// The format of a file given by its extension. Files without one of the
+    /// known extensions are read as TOML, as they always have been.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml") | Some("yml") => Format::Yaml,
+            Some("json") => Format::Json,
+            _ => Format::Toml,
+        }
+    }
+}
+
+pub fn deserialize<T: DeserializeOwned>(content: &str, format: Format) -> Result<T, Vec<String>> {
+    match format {
+        Format::Toml => toml::from_str(content).map_err(|e| vec![e.to_string()]),
+        Format::Yaml => serde_yaml::from_str(content).map_err(|e| vec![e.to_string()]),
+        Format::Json => serde_json::from_str(content).map_err(|e| vec![e.to_string()]),
+    }
+}
+
+pub fn serialize<T: Serialize>(value: &T, format: Format) -> Result<String, String> {
+    match format {
+        Format::Toml => toml::to_string(value).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format_from_extension() {
+        for (path, format) in &[
+            ("vector.toml", Format::Toml),
+            ("vector.yaml", Format::Yaml),
+            ("/etc/vector/vector.yml", Format::Yaml),
+            ("vector.json", Format::Json),
+            ("vector", Format::Toml),
+            ("vector.conf", Format::Toml),
+        ] {
+            assert_eq!(Format::from_path(Path::new(path)), *format, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,626 @@
+//! Checks for pipelines that load and typecheck, but likely don't do what they
+//! are meant to, reported by `vector validate`.
+
+use super::{split_named_output, Config, SourceConfig, TransformOuter};
+use crate::{
+    buffers::{BufferConfig, WhenFull},
+    mapping::parser,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// The name of the check that made the finding.
+    pub lint: &'static str,
+    pub components: Vec<String>,
+    pub message: String,
+}
+
+impl Finding {
+    fn warning(lint: &'static str, components: Vec<String>, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            lint,
+            components,
+            message,
+        }
+    }
+
+    fn error(lint: &'static str, components: Vec<String>, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            lint,
+            components,
+            message,
+        }
+    }
+}
+
+pub fn lint(config: &Config) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    dead_ends(config, &mut findings);
+    duplicate_buffered_sinks(config, &mut findings);
+    shared_file_paths(config, &mut findings);
+    unknown_fields(config, &mut findings);
+    listen_address_conflicts(config, &mut findings);
+    for finding in &mut findings {
+        finding.message = config.redact(&finding.message);
+    }
+    findings
+}
+
+/// The component whose events `input` refers to, be it one of its named
+/// outputs or the dead-letter output of a sink.
+fn producer<'a>(config: &Config, input: &'a str) -> &'a str {
+    if config.sources.contains_key(input) || config.transforms.contains_key(input) {
+        input
+    } else {
+        split_named_output(input).map_or(input, |(name, _)| name)
+    }
+}
+
+/// Sources and transforms whose events never make it to a sink.
+fn dead_ends(config: &Config, findings: &mut Vec<Finding>) {
+    let mut consumers = HashMap::<&str, Vec<&str>>::new();
+    let transform_inputs = config
+        .transforms
+        .iter()
+        .map(|(name, transform)| (name, &transform.inputs));
+    let sink_inputs = config.sinks.iter().map(|(name, sink)| (name, &sink.inputs));
+    for (name, inputs) in transform_inputs.chain(sink_inputs) {
+        for input in inputs {
+            consumers
+                .entry(producer(config, input))
+                .or_default()
+                .push(name);
+        }
+    }
+
+    let mut reaches = HashMap::new();
+    for name in config.sources.keys() {
+        if !reaches_sink(config, &consumers, name, &mut reaches) {
+            findings.push(Finding::warning(
+                "dead_end",
+                vec![name.clone()],
+                format!("Source {:?} sends its events to no sink.", name),
+            ));
+        }
+    }
+    for name in config.transforms.keys() {
+        if !consumers.contains_key(name.as_str()) {
+            findings.push(Finding::warning(
+                "unconsumed",
+                vec![name.clone()],
+                format!("Transform {:?} has no consumers.", name),
+            ));
+        } else if !reaches_sink(config, &consumers, name, &mut reaches) {
+            findings.push(Finding::warning(
+                "dead_end",
+                vec![name.clone()],
+                format!("Transform {:?} sends its events to no sink.", name),
+            ));
+        }
+    }
+}
+
+fn reaches_sink<'a>(
+    config: &Config,
+    consumers: &HashMap<&'a str, Vec<&'a str>>,
+    name: &'a str,
+    reaches: &mut HashMap<&'a str, bool>,
+) -> bool {
+    if config.sinks.contains_key(name) {
+        return true;
+    }
+    if let Some(&reached) = reaches.get(name) {
+        return reached;
+    }
+    // Guards against cycles, which are reported on their own.
+    reaches.insert(name, false);
+    let reached = consumers.get(name).map_or(false, |next| {
+        next.iter()
+            .any(|consumer| reaches_sink(config, consumers, consumer, reaches))
+    });
+    reaches.insert(name, reached);
+    reached
+}
+
+/// Sinks that are the same sink under different names, each keeping its own
+/// copy of every event in a disk buffer of the data directory.
+fn duplicate_buffered_sinks(config: &Config, findings: &mut Vec<Finding>) {
+    let sinks = config
+        .sinks
+        .iter()
+        .filter(|(_, sink)| buffers_on_disk(&sink.buffer))
+        .filter_map(|(name, sink)| {
+            let mut inputs = sink.inputs.clone();
+            inputs.sort();
+            Some((name, serde_json::to_value(&sink.inner).ok()?, inputs))
+        })
+        .collect::<Vec<_>>();
+
+    for (i, (name, options, inputs)) in sinks.iter().enumerate() {
+        for (other, other_options, other_inputs) in &sinks[i + 1..] {
+            if options == other_options && inputs == other_inputs {
+                findings.push(Finding::warning(
+                    "duplicate_sink",
+                    vec![(*name).clone(), (*other).clone()],
+                    format!(
+                        "Sinks {:?} and {:?} send the same events to the same place, each buffering them on disk in the data directory.",
+                        name, other
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn buffers_on_disk(buffer: &BufferConfig) -> bool {
+    matches!(
+        buffer,
+        BufferConfig::Disk { .. }
+            | BufferConfig::Memory {
+                when_full: WhenFull::Overflow,
+                ..
+            }
+    )
+}
+
+/// `file` sinks writing to the same files, interleaving their events.
+fn shared_file_paths(config: &Config, findings: &mut Vec<Finding>) {
+    let mut sinks = HashMap::<String, Vec<String>>::new();
+    for (name, sink) in &config.sinks {
+        if sink.inner.sink_type() != "file" {
+            continue;
+        }
+        if let Some(path) = serde_json::to_value(&sink.inner)
+            .ok()
+            .and_then(|options| options["path"].as_str().map(Into::into))
+        {
+            sinks.entry(path).or_default().push(name.clone());
+        }
+    }
+
+    let mut shared = sinks
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .collect::<Vec<_>>();
+    shared.sort();
+    for (path, names) in shared {
+        findings.push(Finding::warning(
+            "shared_file_path",
+            names.clone(),
+            format!("Sinks {:?} all write to {:?}.", names, path),
+        ));
+    }
+}
+
+/// Fields read by `remap` mappings that no component before them produces.
+/// Only pipelines where the fields of every event are known are checked, so
+/// from sources giving their events a message and a few fields of their own,
+/// through transforms adding known fields, such as parsers declaring the
+/// fields they parse.
+fn unknown_fields(config: &Config, findings: &mut Vec<Finding>) {
+    let mut known = HashMap::new();
+    for (name, transform) in &config.transforms {
+        if transform.inner.transform_type() != "remap" {
+            continue;
+        }
+        let reads = match mapping_fields(transform) {
+            Some(fields) => fields.reads,
+            None => continue,
+        };
+        let fields = match fields_of_inputs(config, &transform.inputs, &mut known) {
+            Some(fields) => fields,
+            None => continue,
+        };
+        for field in reads.into_iter().filter(|field| !fields.contains(field)) {
+            findings.push(Finding::warning(
+                "unknown_field",
+                vec![name.clone()],
+                format!(
+                    "Transform {:?} reads the field {:?}, which no component before it produces.",
+                    name, field
+                ),
+            ));
+        }
+    }
+}
+
+fn mapping_fields(transform: &TransformOuter) -> Option<parser::Fields> {
+    let options = serde_json::to_value(&transform.inner).ok()?;
+    parser::fields(options["mapping"].as_str()?).ok()
+}
+
+/// The top level fields events from `inputs` can have, if known.
+fn fields_of_inputs<'a>(
+    config: &'a Config,
+    inputs: &'a [String],
+    known: &mut HashMap<&'a str, Option<BTreeSet<String>>>,
+) -> Option<BTreeSet<String>> {
+    let mut fields = BTreeSet::new();
+    for input in inputs {
+        fields.extend(fields_of(config, producer(config, input), known)?);
+    }
+    Some(fields)
+}
+
+fn fields_of<'a>(
+    config: &'a Config,
+    name: &'a str,
+    known: &mut HashMap<&'a str, Option<BTreeSet<String>>>,
+) -> Option<BTreeSet<String>> {
+    if let Some(fields) = known.get(name) {
+        return fields.clone();
+    }
+    // Guards against cycles, which are reported on their own.
+    known.insert(name, None);
+    let fields = if let Some(source) = config.sources.get(name) {
+        source_fields(config, source.as_ref())
+    } else if let Some(transform) = config.transforms.get(name) {
+        fields_of_inputs(config, &transform.inputs, known)
+            .and_then(|fields| transform_fields(transform, fields))
+    } else {
+        // Dead-letter outputs annotate the events of their sink.
+        None
+    };
+    known.insert(name, fields.clone());
+    fields
+}
+
+fn source_fields(config: &Config, source: &dyn SourceConfig) -> Option<BTreeSet<String>> {
+    if !matches!(
+        source.source_type(),
+        "stdin" | "file" | "socket" | "generator"
+    ) {
+        return None;
+    }
+
+    let schema = &config.global.log_schema;
+    let mut fields = [
+        schema.message_key(),
+        schema.timestamp_key(),
+        schema.host_key(),
+        schema.source_type_key(),
+    ]
+    .iter()
+    .map(|key| top_level(key))
+    .collect::<BTreeSet<_>>();
+    // Such as `file_key` and `host_key`.
+    if let Ok(Value::Object(options)) = serde_json::to_value(source) {
+        fields.extend(
+            options
+                .iter()
+                .filter(|(option, _)| option.ends_with("_key"))
+                .filter_map(|(_, key)| key.as_str())
+                .map(top_level),
+        );
+    }
+    Some(fields)
+}
+
+fn transform_fields(
+    transform: &TransformOuter,
+    mut fields: BTreeSet<String>,
+) -> Option<BTreeSet<String>> {
+    let options = serde_json::to_value(&transform.inner).ok()?;
+    match transform.inner.transform_type() {
+        "filter" | "field_filter" | "sampler" | "dedupe" | "swimlane" | "ansi_stripper"
+        | "coercer" | "remove_fields" => {}
+        "add_fields" => fields.extend(
+            options["fields"]
+                .as_object()?
+                .keys()
+                .map(|key| top_level(key)),
+        ),
+        "enrich" => {
+            let target = options["target"]
+                .as_str()
+                .or_else(|| options["table"].as_str())?;
+            fields.insert(top_level(target));
+        }
+        "remap" => fields.extend(mapping_fields(transform)?.writes),
+        _ => fields.extend(
+            transform
+                .inner
+                .fields()?
+                .iter()
+                .map(|field| top_level(field)),
+        ),
+    }
+    Some(fields)
+}
+
+fn top_level(path: &str) -> String {
+    path.split('.').next().unwrap_or(path).to_owned()
+}
+
+/// Components listening on the same port, all but one of which fail to start.
+fn listen_address_conflicts(config: &Config, findings: &mut Vec<Finding>) {
+    let mut listeners = Vec::new();
+    for (name, source) in &config.sources {
+        if let Ok(options) = serde_json::to_value(source) {
+            if let Some(address) = options["address"].as_str().and_then(|a| a.parse().ok()) {
+                let protocol = if options["mode"] == "udp" {
+                    "udp"
+                } else {
+                    "tcp"
+                };
+                listeners.push((Some(name), format!("source {:?}", name), protocol, address));
+            }
+        }
+    }
+    for (name, sink) in &config.sinks {
+        if sink.inner.sink_type() == "prometheus" {
+            if let Some(address) = serde_json::to_value(&sink.inner)
+                .ok()
+                .and_then(|options| options["address"].as_str()?.parse().ok())
+            {
+                listeners.push((Some(name), format!("sink {:?}", name), "tcp", address));
+            }
+        }
+    }
+    if let Some(address) = api_address(config) {
+        listeners.push((None, "API".to_owned(), "tcp", address));
+    }
+
+    for (i, (name, listener, protocol, address)) in listeners.iter().enumerate() {
+        for (other_name, other, other_protocol, other_address) in &listeners[i + 1..] {
+            if protocol == other_protocol && overlaps(address, other_address) {
+                findings.push(Finding::error(
+                    "listen_address_conflict",
+                    name.iter()
+                        .chain(other_name)
+                        .map(|name| (*name).clone())
+                        .collect(),
+                    format!(
+                        "The {} and the {} both listen on {} port {}.",
+                        listener,
+                        other,
+                        protocol.to_uppercase(),
+                        address.port()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn overlaps(address: &SocketAddr, other: &SocketAddr) -> bool {
+    address.port() == other.port()
+        && (address.ip() == other.ip()
+            || address.ip().is_unspecified()
+            || other.ip().is_unspecified())
+}
+
+#[cfg(feature = "api")]
+fn api_address(config: &Config) -> Option<SocketAddr> {
+    config.api.bind.filter(|_| config.api.enabled)
+}
+
+#[cfg(not(feature = "api"))]
+fn api_address(_config: &Config) -> Option<SocketAddr> {
+    None
+}
+
+#[cfg(all(
+    test,
+    feature = "sources-stdin",
+    feature = "sources-socket",
+    feature = "sources-http",
+    feature = "transforms-remap",
+    feature = "transforms-json_parser",
+    feature = "transforms-regex_parser",
+    feature = "sinks-console",
+    feature = "sinks-file"
+))]
+mod tests {
+    use super::*;
+    use crate::config::{load_from_str, Format};
+
+    fn lints(config: &str) -> Vec<(Severity, &'static str, Vec<String>)> {
+        lint(&load_from_str(config, Format::Toml).unwrap())
+            .into_iter()
+            .map(|finding| (finding.severity, finding.lint, finding.components))
+            .collect()
+    }
+
+    #[test]
+    fn finds_dead_ends() {
+        let findings = lints(
+            r#"
+            [sources.in]
+            type = "stdin"
+
+            [sources.orphan]
+            type = "stdin"
+
+            [transforms.unused]
+            type = "remap"
+            inputs = ["orphan"]
+            mapping = ".a = 1"
+
+            [sinks.out]
+            type = "console"
+            inputs = ["in"]
+            encoding = "json"
+            "#,
+        );
+
+        assert_eq!(
+            findings,
+            vec![
+                (Severity::Warning, "dead_end", vec!["orphan".to_owned()]),
+                (Severity::Warning, "unconsumed", vec!["unused".to_owned()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_sinks_sharing_buffers_and_files() {
+        let sink = r#"
+            type = "file"
+            inputs = ["in"]
+            path = "/var/log/vector/{{ host }}.log"
+            encoding = "ndjson"
+            buffer.type = "disk"
+            buffer.max_size = 104900000
+            "#;
+        let findings = lints(&format!(
+            "[sources.in]\ntype = \"stdin\"\n[sinks.one]\n{}\n[sinks.two]\n{}",
+            sink, sink
+        ));
+
+        let names = vec!["one".to_owned(), "two".to_owned()];
+        assert_eq!(
+            findings,
+            vec![
+                (Severity::Warning, "duplicate_sink", names.clone()),
+                (Severity::Warning, "shared_file_path", names),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_unknown_fields() {
+        let findings = lint(
+            &load_from_str(
+                r#"
+                [sources.in]
+                type = "stdin"
+
+                [transforms.known]
+                type = "remap"
+                inputs = ["in"]
+                mapping = """
+                .level = "info"
+                .copy = .message
+                .status = .code
+                """
+
+                [transforms.parsed]
+                type = "json_parser"
+                inputs = ["known"]
+
+                [transforms.unknown]
+                type = "remap"
+                inputs = ["parsed"]
+                mapping = ".other = .code"
+
+                [sinks.out]
+                type = "console"
+                inputs = ["unknown"]
+                encoding = "json"
+                "#,
+                Format::Toml,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            findings,
+            vec![Finding::warning(
+                "unknown_field",
+                vec!["known".to_owned()],
+                "Transform \"known\" reads the field \"code\", which no component before it produces."
+                    .to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn finds_unknown_fields_after_parsers() {
+        let findings = lint(
+            &load_from_str(
+                r#"
+                [sources.in]
+                type = "stdin"
+
+                [transforms.parsed]
+                type = "regex_parser"
+                inputs = ["in"]
+                patterns = ['^(?P<level>\w+) (?P<status>\d+)']
+
+                [transforms.checked]
+                type = "remap"
+                inputs = ["parsed"]
+                mapping = """
+                .error = .level
+                .code = .status
+                .user = .user_id
+                """
+
+                [sinks.out]
+                type = "console"
+                inputs = ["checked"]
+                encoding = "json"
+                "#,
+                Format::Toml,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            findings,
+            vec![Finding::warning(
+                "unknown_field",
+                vec!["checked".to_owned()],
+                "Transform \"checked\" reads the field \"user_id\", which no component before it produces."
+                    .to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn finds_listen_address_conflicts() {
+        let findings = lint(
+            &load_from_str(
+                r#"
+                [sources.tcp]
+                type = "socket"
+                mode = "tcp"
+                address = "0.0.0.0:9000"
+
+                [sources.udp]
+                type = "socket"
+                mode = "udp"
+                address = "0.0.0.0:9000"
+
+                [sources.http]
+                type = "http"
+                address = "127.0.0.1:9000"
+
+                [sinks.out]
+                type = "console"
+                inputs = ["tcp", "udp", "http"]
+                encoding = "json"
+                "#,
+                Format::Toml,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            findings,
+            vec![Finding::error(
+                "listen_address_conflict",
+                vec!["tcp".to_owned(), "http".to_owned()],
+                "The source \"tcp\" and the source \"http\" both listen on TCP port 9000."
+                    .to_owned()
+            )]
+        );
+    }
+}
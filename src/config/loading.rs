@@ -1,11 +1,21 @@
-use super::{builder::ConfigBuilder, vars, Config};
+use super::{
+    builder::ConfigBuilder,
+    format,
+    secret::{self, SecretBackend, Secrets},
+    vars, Config, Format,
+};
 use glob::glob;
+use indexmap::IndexMap;
 use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
 use std::{
     collections::HashMap,
-    fs::File,
+    fs::{self, File},
     path::{Path, PathBuf},
-    sync::Mutex,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
 };
 
 lazy_static! {
@@ -19,6 +29,14 @@ lazy_static! {
     pub static ref CONFIG_PATHS: Mutex<Vec<PathBuf>> = Mutex::default();
 }
 
+static STRICT_ENV_VARS: AtomicBool = AtomicBool::new(false);
+
+/// Makes the configuration fail to load when it references environment
+/// variables that aren't set, instead of replacing them by nothing.
+pub fn set_strict_env_vars(strict: bool) {
+    STRICT_ENV_VARS.store(strict, Ordering::Relaxed);
+}
+
 /// Expand a list of paths (potentially containing glob patterns) into real
 /// config paths, replacing it with the default paths when empty.
 pub fn process_paths(config_paths: &[PathBuf]) -> Option<Vec<PathBuf>> {
@@ -66,19 +84,180 @@ pub fn process_paths(config_paths: &[PathBuf]) -> Option<Vec<PathBuf>> {
     Some(paths)
 }
 
-pub fn load_from_paths(config_paths: &[PathBuf]) -> Result<Config, Vec<String>> {
-    load_builder_from_paths(config_paths).and_then(|builder| builder.build())
+/// Same as `process_paths`, except that there are no default paths to fall
+/// back to when there are config directories to load.
+pub fn process_paths_with_dirs(
+    config_paths: &[PathBuf],
+    config_dirs: &[PathBuf],
+) -> Option<Vec<PathBuf>> {
+    if config_paths.is_empty() && !config_dirs.is_empty() {
+        Some(Vec::new())
+    } else {
+        process_paths(config_paths)
+    }
+}
+
+/// Loads the configuration from `config_paths`, read in `format` or, if it's
+/// `None`, in the format given by the extension of each file.
+pub fn load_from_paths(
+    config_paths: &[PathBuf],
+    format: Option<Format>,
+) -> Result<Config, Vec<String>> {
+    load_from_paths_and_dirs(config_paths, &[], format)
+}
+
+/// Same as `load_from_paths`, along with the components of `config_dirs`.
+pub fn load_from_paths_and_dirs(
+    config_paths: &[PathBuf],
+    config_dirs: &[PathBuf],
+    format: Option<Format>,
+) -> Result<Config, Vec<String>> {
+    let mut builder = load_builder_from_paths(config_paths, format)?;
+    let mut errors = Vec::new();
+
+    let backends = builder.secret.clone();
+    for dir in config_dirs {
+        if let Err(errs) = load_builder_from_dir(dir, &backends).and_then(|b| builder.append(b)) {
+            errors.extend(errs);
+        }
+    }
+
+    if errors.is_empty() {
+        builder.build()
+    } else {
+        Err(errors)
+    }
+}
+
+/// Loads a config directory, where each file of its `sources`, `transforms`
+/// and `sinks` directories holds a single component, named after the file.
+/// The format of each file is given by its extension, and files with other
+/// extensions are left alone. The files can reference the secrets of
+/// `backends`.
+pub(super) fn load_builder_from_dir(
+    dir: &Path,
+    backends: &IndexMap<String, SecretBackend>,
+) -> Result<ConfigBuilder, Vec<String>> {
+    if !dir.is_dir() {
+        return Err(vec![format!("Config directory not found: {:?}.", dir)]);
+    }
+
+    let mut builder = Config::builder();
+    let mut secrets = Secrets::default();
+    let mut errors = Vec::new();
+
+    // As for a config file that leaves it out, so as not to conflict with the
+    // one of the config files when appended to it.
+    builder.global.data_dir = super::default_data_dir();
+    builder.sources = load_components(&dir.join("sources"), backends, &mut secrets, &mut errors);
+    builder.transforms =
+        load_components(&dir.join("transforms"), backends, &mut secrets, &mut errors);
+    builder.sinks = load_components(&dir.join("sinks"), backends, &mut secrets, &mut errors);
+    builder.secrets = secrets;
+
+    if errors.is_empty() {
+        Ok(builder)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Loads the components of a directory, adding the secrets they reference to
+/// `secrets`.
+fn load_components<T: DeserializeOwned>(
+    dir: &Path,
+    backends: &IndexMap<String, SecretBackend>,
+    secrets: &mut Secrets,
+    errors: &mut Vec<String>,
+) -> IndexMap<String, T> {
+    let mut components = IndexMap::new();
+
+    let files = match component_files(dir) {
+        Ok(files) => files,
+        Err(error) => {
+            errors.push(error);
+            return components;
+        }
+    };
+    for (name, path) in files {
+        if components.contains_key(&name) {
+            errors.push(format!(
+                "More than one file of {:?} defines component {:?}.",
+                dir, name
+            ));
+            continue;
+        }
+
+        // Errors about missing environment variables name the file already.
+        let in_file = |errs: Vec<String>| {
+            errs.into_iter()
+                .map(|e| format!("{:?}: {}", path, e))
+                .collect::<Vec<_>>()
+        };
+        let component = File::open(&path)
+            .map_err(|error| in_file(vec![error.to_string()]))
+            .and_then(|file| read(file, Some(&path)))
+            .and_then(|text| {
+                let retrieved = Secrets::retrieve(backends, std::iter::once(text.as_str()))
+                    .map_err(&in_file)?;
+                let component =
+                    deserialize(&text, Format::from_path(&path), &retrieved).map_err(&in_file);
+                secrets.extend(retrieved);
+                component
+            });
+        match component {
+            Ok(component) => {
+                components.insert(name, component);
+            }
+            Err(errs) => errors.extend(errs),
+        }
+    }
+
+    components
+}
+
+/// The config files of a directory of components, sorted by the names of
+/// their components. A missing directory has none.
+fn component_files(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("Unable to read {:?}: {}.", dir, error)),
+    };
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|error| format!("Unable to read {:?}: {}.", dir, error))?
+            .path();
+        let is_config = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") | Some("yaml") | Some("yml") | Some("json") => true,
+            _ => false,
+        };
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .filter(|name| !name.starts_with('.'));
+        if let (true, Some(name)) = (is_config && path.is_file(), name) {
+            files.push((name.to_owned(), path));
+        }
+    }
+    files.sort();
+
+    Ok(files)
 }
 
 pub(super) fn load_builder_from_paths(
     config_paths: &[PathBuf],
+    format: Option<Format>,
 ) -> Result<ConfigBuilder, Vec<String>> {
     let mut inputs = Vec::new();
     let mut errors = Vec::new();
 
     for path in config_paths {
         if let Some(file) = open_config(&path) {
-            inputs.push(file);
+            let format = format.unwrap_or_else(|| Format::from_path(path));
+            inputs.push((file, format, Some(path.as_path())));
         } else {
             errors.push(format!("Config file not found in path: {:?}.", path));
         };
@@ -91,23 +270,39 @@ pub(super) fn load_builder_from_paths(
     }
 }
 
-pub fn load_from_str(input: &str) -> Result<Config, Vec<String>> {
-    load_from_inputs(std::iter::once(input.as_bytes())).and_then(|builder| builder.build())
+pub fn load_from_str(input: &str, format: Format) -> Result<Config, Vec<String>> {
+    load_from_inputs(std::iter::once((input.as_bytes(), format, None)))
+        .and_then(|builder| builder.build())
 }
 
-fn load_from_inputs(
-    inputs: impl IntoIterator<Item = impl std::io::Read>,
+/// Loads config files, each along with the path it was read from, if any.
+fn load_from_inputs<'a>(
+    inputs: impl IntoIterator<Item = (impl std::io::Read, Format, Option<&'a Path>)>,
 ) -> Result<ConfigBuilder, Vec<String>> {
     let mut config = Config::builder();
     let mut errors = Vec::new();
 
-    for input in inputs {
-        if let Err(errs) = load(input).and_then(|n| config.append(n)) {
+    let mut texts = Vec::new();
+    for (input, format, path) in inputs {
+        match read(input, path) {
+            Ok(text) => texts.push((text, format)),
+            Err(errs) => errors.extend(errs),
+        }
+    }
+
+    // Any file can reference the secrets of the backends declared by another.
+    let backends = secret::backends(texts.iter().map(|(text, format)| (text.as_str(), *format)));
+    let secrets = Secrets::retrieve(&backends, texts.iter().map(|(text, _)| text.as_str()))?;
+
+    for (text, format) in texts {
+        if let Err(errs) = deserialize(&text, format, &secrets).and_then(|n| config.append(n)) {
             // TODO: add back paths
             errors.extend(errs.iter().map(|e| e.to_string()));
         }
     }
 
+    config.secrets.extend(secrets);
+
     if errors.is_empty() {
         Ok(config)
     } else {
@@ -130,7 +325,9 @@ fn open_config(path: &Path) -> Option<File> {
     }
 }
 
-fn load(mut input: impl std::io::Read) -> Result<ConfigBuilder, Vec<String>> {
+/// Reads a config file, along with the environment variables it references.
+/// Errors name every variable missing from it, and the file at `path`.
+fn read(mut input: impl std::io::Read, path: Option<&Path>) -> Result<String, Vec<String>> {
     let mut source_string = String::new();
     input
         .read_to_string(&mut source_string)
@@ -142,7 +339,32 @@ fn load(mut input: impl std::io::Read) -> Result<ConfigBuilder, Vec<String>> {
             vars.insert("HOSTNAME".into(), hostname);
         }
     }
-    let with_vars = vars::interpolate(&source_string, &vars);
+    let strict = STRICT_ENV_VARS.load(Ordering::Relaxed);
+    vars::interpolate(&source_string, &vars, strict).map_err(|missing| {
+        missing
+            .into_iter()
+            .map(|var| {
+                let mut error = format!("Missing environment variable {:?}", var.name);
+                if let Some(path) = path {
+                    error.push_str(&format!(" in {:?}", path));
+                }
+                if let Some(message) = var.message {
+                    error.push_str(&format!(": {}", message));
+                }
+                error.push('.');
+                error
+            })
+            .collect()
+    })
+}
 
-    toml::from_str(&with_vars).map_err(|e| vec![e.to_string()])
+/// Deserializes a config file with the values of the secrets it references,
+/// which are kept out of the errors.
+fn deserialize<T: DeserializeOwned>(
+    text: &str,
+    format: Format,
+    secrets: &Secrets,
+) -> Result<T, Vec<String>> {
+    format::deserialize(&secrets.interpolate(text), format)
+        .map_err(|errors| errors.iter().map(|e| secrets.redact(e)).collect())
 }
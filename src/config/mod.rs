@@ -1,6 +1,6 @@
 use crate::{
-    buffers::Acker, conditions, dns::Resolver, event::Metric, shutdown::ShutdownSignal, sinks,
-    sources, transforms, Pipeline,
+    buffers::Acker, conditions, dns::Resolver, enrichment_tables::EnrichmentTableConfig,
+    event::Metric, shutdown::ShutdownSignal, sinks, sources, transforms, Pipeline,
 };
 use async_trait::async_trait;
 use component::ComponentDescription;
@@ -15,8 +15,13 @@ mod builder;
 mod compiler;
 pub mod component;
 mod diff;
+pub mod format;
+pub mod lint;
 mod loading;
 mod log_schema;
+pub mod remote;
+mod secret;
+mod template;
 mod unit_test;
 mod validation;
 mod vars;
@@ -24,7 +29,11 @@ pub mod watcher;
 
 pub use builder::ConfigBuilder;
 pub use diff::ConfigDiff;
-pub use loading::{load_from_paths, load_from_str, process_paths, CONFIG_PATHS};
+pub use format::Format;
+pub use loading::{
+    load_from_paths, load_from_paths_and_dirs, load_from_str, process_paths,
+    process_paths_with_dirs, set_strict_env_vars, CONFIG_PATHS,
+};
 pub use log_schema::{log_schema, LogSchema, LOG_SCHEMA};
 pub use unit_test::build_unit_tests_main as build_unit_tests;
 pub use validation::warnings;
@@ -37,8 +46,10 @@ pub struct Config {
     pub sources: IndexMap<String, Box<dyn SourceConfig>>,
     pub sinks: IndexMap<String, SinkOuter>,
     pub transforms: IndexMap<String, TransformOuter>,
+    pub enrichment_tables: IndexMap<String, EnrichmentTableConfig>,
     tests: Vec<TestDefinition>,
     expansions: IndexMap<String, Vec<String>>,
+    secrets: secret::Secrets,
 }
 
 #[derive(Default, Debug, Deserialize, Serialize)]
@@ -159,6 +170,13 @@ pub trait SourceConfig: core::fmt::Debug + Send + Sync {
     fn output_type(&self) -> DataType;
 
     fn source_type(&self) -> &'static str;
+
+    /// Whether this source waits for its events to be acknowledged by every
+    /// sink they reach before moving on, e.g. committing offsets or replying
+    /// to its client.
+    fn acknowledgements(&self) -> bool {
+        false
+    }
 }
 
 pub type SourceDescription = ComponentDescription<Box<dyn SourceConfig>>;
@@ -171,11 +189,38 @@ pub struct SinkOuter {
     pub buffer: crate::buffers::BufferConfig,
     #[serde(default = "healthcheck_default")]
     pub healthcheck: bool,
+    /// Exposes the events the sink rejects for good as an output named
+    /// `<sink>.dead_letter`.
+    #[serde(default)]
+    pub dead_letter: bool,
+    /// What the components feeding the sink do when it has no room for more
+    /// events.
+    #[serde(default)]
+    pub isolation: Isolation,
     pub inputs: Vec<String>,
     #[serde(flatten)]
     pub inner: Box<dyn SinkConfig>,
 }
 
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Isolation {
+    /// The components feeding the sink wait for it, and so do their other
+    /// outputs.
+    None,
+    /// The events the sink has no room for are dropped for it alone.
+    Skip,
+    /// The sink is detached once it has no room for more events, dropping
+    /// what it misses until it catches up.
+    Detach,
+}
+
+impl Default for Isolation {
+    fn default() -> Self {
+        Isolation::None
+    }
+}
+
 #[async_trait]
 #[typetag::serde(tag = "type")]
 pub trait SinkConfig: core::fmt::Debug + Send + Sync {
@@ -220,10 +265,24 @@ inventory::collect!(SinkDescription);
 #[derive(Deserialize, Serialize, Debug)]
 pub struct TransformOuter {
     pub inputs: Vec<String>,
+    /// Number of instances of a stateless transform processing events in
+    /// parallel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// Whether the events processed in parallel keep their order.
+    #[serde(default = "preserve_order_default")]
+    pub preserve_order: bool,
     #[serde(flatten)]
     pub inner: Box<dyn TransformConfig>,
 }
 
+impl TransformOuter {
+    /// Number of instances of the transform to run.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency.unwrap_or(1)
+    }
+}
+
 #[async_trait]
 #[typetag::serde(tag = "type")]
 pub trait TransformConfig: core::fmt::Debug + Send + Sync {
@@ -235,6 +294,25 @@ pub trait TransformConfig: core::fmt::Debug + Send + Sync {
 
     fn transform_type(&self) -> &'static str;
 
+    /// Whether the transform keeps no state from one event to the next, so
+    /// that several instances of it can process events in parallel.
+    fn stateless(&self) -> bool {
+        false
+    }
+
+    /// Names of the outputs this transform can send events to besides its
+    /// default output. Other components refer to them as `<transform>.<output>`.
+    fn named_outputs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The fields this transform adds to events, when its configuration alone
+    /// tells them, such as the captures of a parser. `vector validate` uses
+    /// them to check the fields later components read.
+    fn fields(&self) -> Option<Vec<String>> {
+        None
+    }
+
     /// Allows a transform configuration to expand itself into multiple "child"
     /// transformations to replace it. This allows a transform to act as a macro
     /// for various patterns.
@@ -320,20 +398,69 @@ impl Config {
     }
 
     /// Expand a logical component name (i.e. from the config file) into the names of the
-    /// components it was expanded to as part of the macro process. Does not check that the
-    /// identifier is otherwise valid.
+    /// components it was expanded to as part of the macro process, leaving out those taking
+    /// events from the others, like all but the first transform of a pipeline. Does not check
+    /// that the identifier is otherwise valid.
     pub fn get_inputs(&self, identifier: &str) -> Vec<String> {
-        self.expansions
-            .get(identifier)
-            .cloned()
-            .unwrap_or_else(|| vec![String::from(identifier)])
+        match self.expansions.get(identifier) {
+            Some(children) => children
+                .iter()
+                .filter(|child| {
+                    self.transforms.get(*child).map_or(true, |transform| {
+                        !transform
+                            .inputs
+                            .iter()
+                            .any(|input| children.contains(input))
+                    })
+                })
+                .cloned()
+                .collect(),
+            None => vec![String::from(identifier)],
+        }
+    }
+
+    /// The transforms that expanded into others, with the names of those.
+    pub fn expansions(&self) -> &IndexMap<String, Vec<String>> {
+        &self.expansions
+    }
+
+    /// Hides the values of the secrets referenced by the config in a message
+    /// about it.
+    pub fn redact(&self, message: &str) -> String {
+        self.secrets.redact(message)
     }
+
+    /// The transform whose named output `input` refers to, if any.
+    pub fn named_output_transform(&self, input: &str) -> Option<&str> {
+        let (name, output) = split_named_output(input)?;
+        self.transforms
+            .get(name)
+            .filter(|transform| transform.inner.named_outputs().iter().any(|o| o == output))
+            .map(|_| name)
+    }
+}
+
+/// The name other components use as an input to refer to the named output
+/// `output` of the component `name`.
+pub fn named_output(name: &str, output: &str) -> String {
+    format!("{}.{}", name, output)
+}
+
+/// Splits an input into the component name and the output name, if it refers
+/// to a named output. Component names can themselves contain dots.
+pub fn split_named_output(input: &str) -> Option<(&str, &str)> {
+    let dot = input.rfind('.')?;
+    Some((&input[..dot], &input[dot + 1..]))
 }
 
 fn healthcheck_default() -> bool {
     true
 }
 
+fn preserve_order_default() -> bool {
+    true
+}
+
 #[cfg(all(
     test,
     feature = "sources-file",
@@ -341,7 +468,9 @@ fn healthcheck_default() -> bool {
     feature = "transforms-json_parser"
 ))]
 mod test {
-    use super::{builder::ConfigBuilder, load_from_str};
+    use super::{
+        builder::ConfigBuilder, load_from_paths, load_from_paths_and_dirs, load_from_str, Format,
+    };
     use std::path::PathBuf;
 
     #[test]
@@ -357,6 +486,7 @@ mod test {
       inputs = ["in"]
       encoding = "json"
       "#,
+            Format::Toml,
         )
         .unwrap();
 
@@ -379,6 +509,7 @@ mod test {
       inputs = ["in"]
       encoding = "json"
       "#,
+            Format::Toml,
         )
         .unwrap();
 
@@ -411,6 +542,7 @@ mod test {
       inputs = ["in"]
       encoding = "json"
       "#,
+            Format::Toml,
         )
         .unwrap();
 
@@ -470,6 +602,218 @@ mod test {
         assert_eq!(config.tests.len(), 1);
     }
 
+    #[test]
+    fn load_yaml_and_json() {
+        let yaml = load_from_str(
+            r#"
+            data_dir: /foobar
+            sources:
+              in:
+                type: file
+                include: [/var/log/messages]
+            sinks:
+              out:
+                type: console
+                inputs: [in]
+                encoding: json
+            "#,
+            Format::Yaml,
+        )
+        .unwrap();
+        let json = load_from_str(
+            r#"
+            {
+              "data_dir": "/foobar",
+              "sources": {
+                "in": { "type": "file", "include": ["/var/log/messages"] }
+              },
+              "sinks": {
+                "out": { "type": "console", "inputs": ["in"], "encoding": "json" }
+              }
+            }
+            "#,
+            Format::Json,
+        )
+        .unwrap();
+
+        for config in &[yaml, json] {
+            assert_eq!(Some(PathBuf::from("/foobar")), config.global.data_dir);
+            assert!(config.sources.contains_key("in"));
+            assert_eq!(config.sinks["out"].inputs, vec!["in".to_string()]);
+        }
+    }
+
+    #[test]
+    fn config_append_across_formats() {
+        let mut config: ConfigBuilder = toml::from_str(
+            r#"
+      [sources.in]
+      type = "file"
+      include = ["/var/log/messages"]
+      "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.append(
+                serde_yaml::from_str(
+                    r#"
+            transforms:
+              foo:
+                type: json_parser
+                inputs: [in]
+            "#,
+                )
+                .unwrap()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            config.append(
+                serde_json::from_str(
+                    r#"{ "sinks": { "out": { "type": "console", "inputs": ["foo"], "encoding": "json" } } }"#,
+                )
+                .unwrap()
+            ),
+            Ok(())
+        );
+
+        assert!(config.sources.contains_key("in"));
+        assert!(config.transforms.contains_key("foo"));
+        assert!(config.sinks.contains_key("out"));
+    }
+
+    #[test]
+    fn load_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        for component in &["sources", "transforms", "sinks"] {
+            std::fs::create_dir(dir.path().join(component)).unwrap();
+        }
+        let write =
+            |path: &str, content: &str| std::fs::write(dir.path().join(path), content).unwrap();
+        write(
+            "sources/in.toml",
+            "type = \"file\"\ninclude = [\"/var/log/messages\"]\n",
+        );
+        write("transforms/parse.yaml", "type: json_parser\ninputs: [in]\n");
+        write(
+            "sinks/out.json",
+            r#"{ "type": "console", "inputs": ["parse"], "encoding": "json" }"#,
+        );
+        write("sinks/README.md", "Not a component.");
+
+        let config = load_from_paths_and_dirs(&[], &[dir.path().to_path_buf()], None).unwrap();
+
+        assert_eq!(
+            Some(PathBuf::from("/var/lib/vector")),
+            config.global.data_dir
+        );
+        assert_eq!(config.sources.keys().collect::<Vec<_>>(), vec!["in"]);
+        assert_eq!(config.transforms["parse"].inputs, vec!["in".to_string()]);
+        assert_eq!(config.sinks.keys().collect::<Vec<_>>(), vec!["out"]);
+
+        write("sinks/out.toml", "type = \"console\"\ninputs = [\"in\"]\n");
+        let errors = load_from_paths_and_dirs(&[], &[dir.path().to_path_buf()], None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("defines component \"out\""));
+
+        std::fs::remove_file(dir.path().join("sinks/out.toml")).unwrap();
+        write(
+            "sources/in.toml",
+            "type = \"file\"\ninclude = [\"${VECTOR_TEST_MISSING_DIR_INCLUDE:?}\"]\n",
+        );
+        let errors = load_from_paths_and_dirs(&[], &[dir.path().to_path_buf()], None).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![format!(
+                "Missing environment variable \"VECTOR_TEST_MISSING_DIR_INCLUDE\" in {:?}.",
+                dir.path().join("sources/in.toml")
+            )]
+        );
+    }
+
+    #[test]
+    fn resolves_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("data_dir"), "/foobar\n").unwrap();
+        std::fs::write(dir.path().join("encoding"), "hunter2").unwrap();
+        let config = |encoding: &str| {
+            format!(
+                r#"
+      data_dir = "SECRET[mounted.data_dir]"
+
+      [secret.mounted]
+      type = "file"
+      path = {:?}
+
+      [sources.in]
+      type = "file"
+      include = ["/var/log/messages"]
+
+      [sinks.out]
+      type = "console"
+      inputs = ["in"]
+      encoding = "{}"
+      "#,
+                dir.path(),
+                encoding
+            )
+        };
+
+        let loaded = load_from_str(&config("json"), Format::Toml).unwrap();
+        assert_eq!(Some(PathBuf::from("/foobar")), loaded.global.data_dir);
+
+        let errors = load_from_str(&config("SECRET[mounted.encoding]"), Format::Toml).unwrap_err();
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|error| !error.contains("hunter2")));
+
+        // Nor do the errors of building the config show the values.
+        let text = config("json").replace(
+            r#"inputs = ["in"]"#,
+            r#"inputs = ["SECRET[mounted.encoding]"]"#,
+        );
+        let errors = load_from_str(&text, Format::Toml).unwrap_err();
+        assert!(errors
+            .iter()
+            .any(|error| error.contains("SECRET[mounted.encoding]")));
+        assert!(errors.iter().all(|error| !error.contains("hunter2")));
+    }
+
+    #[test]
+    fn reports_missing_env_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vector.toml");
+        std::fs::write(
+            &path,
+            r#"
+      [sources.in]
+      type = "file"
+      include = ["${VECTOR_TEST_MISSING_INCLUDE:?the source needs files to read}"]
+
+      [sinks.out]
+      type = "console"
+      inputs = ["in"]
+      encoding = "${VECTOR_TEST_MISSING_ENCODING:?}"
+      "#,
+        )
+        .unwrap();
+
+        let errors = load_from_paths(&[path.clone()], None).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                format!(
+                    "Missing environment variable \"VECTOR_TEST_MISSING_INCLUDE\" in {:?}: the source needs files to read.",
+                    path
+                ),
+                format!(
+                    "Missing environment variable \"VECTOR_TEST_MISSING_ENCODING\" in {:?}.",
+                    path
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn config_append_collisions() {
         let mut config: ConfigBuilder = toml::from_str(
@@ -0,0 +1,350 @@
+//! Configuration fetched from an HTTP(S) endpoint, and polled for changes.
+//!
+//! Requests carry the ETag of the last response in `If-None-Match`, so that
+//! the server can answer `304 Not Modified` while the configuration hasn't
+//! changed. The last configuration that was applied is kept on disk along with
+//! its ETag, so that Vector can start while the server is unreachable.
+
+use super::{load_from_str, Config, Format};
+use crate::{
+    dns::Resolver,
+    internal_events::{RemoteConfigFetchFailed, RemoteConfigInvalid},
+    sinks::util::http::HttpClient,
+};
+use http::{
+    header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+    Request, Response, StatusCode, Uri,
+};
+use hyper::{body::to_bytes as body_to_bytes, Body};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::time;
+
+/// How long fetching the configuration may take, so that polling for changes
+/// never holds up the handling of signals for long.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Provider {
+    url: Uri,
+    auth_token: Option<String>,
+    /// Overrides the format given by the response.
+    format: Option<Format>,
+    /// Where the last known good configuration is kept.
+    cache: PathBuf,
+    client: HttpClient,
+    /// The last configuration applied.
+    last: Option<Snapshot>,
+    /// The configuration last returned by `fetch`, until it's applied.
+    pending: Option<Snapshot>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+struct Snapshot {
+    etag: Option<String>,
+    format: Format,
+    config: String,
+}
+
+impl Provider {
+    pub fn new(
+        url: Uri,
+        auth_token: Option<String>,
+        format: Option<Format>,
+        cache: PathBuf,
+    ) -> crate::Result<Self> {
+        Ok(Self {
+            url,
+            auth_token,
+            format,
+            cache,
+            client: HttpClient::new(Resolver, None)?,
+            last: None,
+            pending: None,
+        })
+    }
+
+    pub fn url(&self) -> &Uri {
+        &self.url
+    }
+
+    /// Loads the configuration to start with, from the server or, if it's
+    /// unreachable or serves an invalid configuration, from the last known
+    /// good one.
+    pub async fn load(&mut self) -> Result<Config, Vec<String>> {
+        let cached = Snapshot::read(&self.cache).unwrap_or_else(|error| {
+            warn!(
+                message = "Unable to read the last known good configuration.",
+                path = ?self.cache,
+                %error
+            );
+            None
+        });
+        self.last = cached.clone();
+
+        if let Some(config) = self.fetch().await {
+            return Ok(config);
+        }
+        match cached {
+            Some(snapshot) => {
+                info!(
+                    message = "Using the last known good configuration.",
+                    path = ?self.cache
+                );
+                snapshot.load()
+            }
+            None => Err(vec![format!(
+                "Unable to load the configuration from {}, and no last known good configuration is kept in {:?}.",
+                self.url, self.cache
+            )]),
+        }
+    }
+
+    /// Fetches the configuration, returning it if it changed and loads.
+    pub async fn fetch(&mut self) -> Option<Config> {
+        self.pending = None;
+        let fetched = time::timeout(FETCH_TIMEOUT, self.fetch_snapshot())
+            .await
+            .unwrap_or_else(|_| Err("Timed out.".into()));
+        let snapshot = match fetched {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return None,
+            Err(error) => {
+                emit!(RemoteConfigFetchFailed {
+                    url: &self.url,
+                    error
+                });
+                return None;
+            }
+        };
+        if self.last.as_ref().map(|last| &last.config) == Some(&snapshot.config) {
+            self.last = Some(snapshot);
+            return None;
+        }
+
+        match snapshot.load() {
+            Ok(config) => {
+                self.pending = Some(snapshot);
+                Some(config)
+            }
+            Err(errors) => {
+                emit!(RemoteConfigInvalid {
+                    url: &self.url,
+                    errors: &errors
+                });
+                None
+            }
+        }
+    }
+
+    /// Keeps the configuration last returned by `fetch` as the last known
+    /// good one, once it's running. Until then, polling keeps fetching it.
+    pub fn applied(&mut self) {
+        if let Some(snapshot) = self.pending.take() {
+            if let Err(error) = snapshot.write(&self.cache) {
+                warn!(
+                    message = "Unable to keep the last known good configuration.",
+                    path = ?self.cache,
+                    %error
+                );
+            }
+            self.last = Some(snapshot);
+        }
+    }
+
+    /// `None` when the server answers that the configuration hasn't changed.
+    async fn fetch_snapshot(&mut self) -> crate::Result<Option<Snapshot>> {
+        let mut request = Request::get(self.url.clone());
+        if let Some(etag) = self.last.as_ref().and_then(|last| last.etag.as_ref()) {
+            request = request.header(IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(token) = &self.auth_token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let response = self.client.send(request.body(Body::empty())?).await?;
+        match response.status() {
+            StatusCode::NOT_MODIFIED => return Ok(None),
+            status if !status.is_success() => {
+                return Err(format!("Unexpected status: {}.", status).into())
+            }
+            _ => {}
+        }
+
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(Into::into);
+        let format = self
+            .format
+            .unwrap_or_else(|| format_of(&response, &self.url));
+        let body = body_to_bytes(response.into_body()).await?;
+        let config = String::from_utf8(body.to_vec())?;
+
+        Ok(Some(Snapshot {
+            etag,
+            format,
+            config,
+        }))
+    }
+}
+
+/// The format given by the content type of the response, or else by the
+/// extension of the path of the configuration.
+fn format_of(response: &Response<Body>, url: &Uri) -> Format {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .map(str::trim);
+    match content_type {
+        Some("application/json") => Format::Json,
+        Some("application/yaml") | Some("application/x-yaml") | Some("text/yaml") => Format::Yaml,
+        Some("application/toml") => Format::Toml,
+        _ => Format::from_path(Path::new(url.path())),
+    }
+}
+
+impl Snapshot {
+    fn load(&self) -> Result<Config, Vec<String>> {
+        load_from_str(&self.config, self.format)
+    }
+
+    fn read(path: &Path) -> io::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)
+                .map(Some)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Replaces the file at `path` in one go, so that it's never left half
+    /// written.
+    fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(all(test, feature = "sources-stdin", feature = "sinks-console"))]
+mod tests {
+    use super::*;
+    use crate::test_util::next_addr;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Server,
+    };
+    use std::{
+        convert::Infallible,
+        sync::{Arc, Mutex},
+    };
+
+    const CONFIG: &str = r#"
+        [sources.in]
+        type = "stdin"
+
+        [sinks.out]
+        type = "console"
+        inputs = ["in"]
+        encoding = "json"
+    "#;
+
+    /// Serves `config` with the given ETag to requests with the right token,
+    /// recording the `If-None-Match` header of each request.
+    fn serve(
+        config: Arc<Mutex<(String, &'static str)>>,
+        seen: Arc<Mutex<Vec<Option<String>>>>,
+    ) -> Uri {
+        let addr = next_addr();
+        let make_service = make_service_fn(move |_| {
+            let config = Arc::clone(&config);
+            let seen = Arc::clone(&seen);
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let (body, etag) = config.lock().unwrap().clone();
+                    let if_none_match = request
+                        .headers()
+                        .get(IF_NONE_MATCH)
+                        .map(|etag| etag.to_str().unwrap().to_owned());
+                    seen.lock().unwrap().push(if_none_match.clone());
+
+                    let authorization = request
+                        .headers()
+                        .get(AUTHORIZATION)
+                        .and_then(|token| token.to_str().ok());
+                    let response = if authorization != Some("Bearer s3cr3t") {
+                        Response::builder()
+                            .status(StatusCode::UNAUTHORIZED)
+                            .body(Body::empty())
+                    } else if if_none_match.as_deref() == Some(etag) {
+                        Response::builder()
+                            .status(StatusCode::NOT_MODIFIED)
+                            .body(Body::empty())
+                    } else {
+                        Response::builder()
+                            .header(ETAG, etag)
+                            .body(Body::from(body))
+                    };
+                    async move { Ok::<_, Infallible>(response.unwrap()) }
+                }))
+            }
+        });
+        tokio::spawn(Server::bind(&addr).serve(make_service));
+        format!("http://{}/vector.toml", addr).parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetches_changes_and_keeps_last_known_good() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("remote_config.json");
+        let config = Arc::new(Mutex::new((CONFIG.to_owned(), "\"1\"")));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let url = serve(Arc::clone(&config), Arc::clone(&seen));
+
+        let token = Some("s3cr3t".to_owned());
+        let mut provider = Provider::new(url, token.clone(), None, cache.clone()).unwrap();
+        let loaded = provider.load().await.unwrap();
+        assert!(loaded.sources.contains_key("in"));
+        provider.applied();
+
+        // Unchanged, then changed to an invalid config, then to a valid one,
+        // which is fetched again until it's applied.
+        assert!(provider.fetch().await.is_none());
+        *config.lock().unwrap() = ("[sinks.out".to_owned(), "\"2\"");
+        assert!(provider.fetch().await.is_none());
+        *config.lock().unwrap() = (CONFIG.replace("json", "text"), "\"3\"");
+        assert!(provider.fetch().await.is_some());
+        assert!(provider.fetch().await.is_some());
+        provider.applied();
+        assert!(provider.fetch().await.is_none());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                None,
+                Some("\"1\"".to_owned()),
+                Some("\"1\"".to_owned()),
+                Some("\"1\"".to_owned()),
+                Some("\"1\"".to_owned()),
+                Some("\"3\"".to_owned())
+            ]
+        );
+
+        // Without a server, starts from the last configuration applied.
+        let mut provider =
+            Provider::new("http://127.0.0.1:1/".parse().unwrap(), token, None, cache).unwrap();
+        let loaded = provider.load().await.unwrap();
+        assert!(loaded.sources.contains_key("in"));
+    }
+}
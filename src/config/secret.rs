@@ -0,0 +1,396 @@
+//! Secrets referenced from the configuration as `SECRET[backend.key]`.
+//!
+//! Backends are declared in the `secret` section of any config file, and every
+//! secret referenced from the configuration is retrieved from them as it's
+//! loaded, before the components are read. Values are kept out of the errors
+//! reported about the configuration, from loading it to building its
+//! components.
+
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt, fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    static ref SECRET: Regex = Regex::new(r"SECRET\[([\w\-]+)\.([\w.\-]+)\]").unwrap();
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SecretBackend {
+    /// Runs a command, given `{"version": "1.0", "secrets": [<keys>]}` on its
+    /// standard input, which writes `{<key>: {"value": <value>, "error":
+    /// <error>}}` for each of them on its standard output.
+    Exec {
+        command: Vec<String>,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: u64,
+    },
+    /// Reads each secret from the file named after its key in a directory,
+    /// such as the ones Kubernetes and Docker mount secrets in.
+    File { path: PathBuf },
+}
+
+fn default_timeout_secs() -> u64 {
+    5
+}
+
+/// The `secret` section of a config file, read on its own before the rest of
+/// the file can be.
+#[derive(Deserialize, Default)]
+struct Section {
+    #[serde(default)]
+    secret: IndexMap<String, SecretBackend>,
+}
+
+/// The backends declared by `texts`, each being the content of a config file.
+/// Files that can't be read here are left for their errors to be reported
+/// when they are loaded.
+pub fn backends<'a>(
+    texts: impl IntoIterator<Item = (&'a str, super::Format)>,
+) -> IndexMap<String, SecretBackend> {
+    let mut backends = IndexMap::new();
+    for (text, format) in texts {
+        if let Ok(section) = super::format::deserialize::<Section>(text, format) {
+            for (name, backend) in section.secret {
+                backends.entry(name).or_insert(backend);
+            }
+        }
+    }
+    backends
+}
+
+/// The values of the secrets referenced by some config files.
+#[derive(Clone, Default)]
+pub struct Secrets {
+    values: HashMap<String, String>,
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.values.keys()).finish()
+    }
+}
+
+impl Secrets {
+    /// Retrieves every secret referenced from `texts`, asking each backend
+    /// once for all of its keys.
+    pub fn retrieve<'a>(
+        backends: &IndexMap<String, SecretBackend>,
+        texts: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, Vec<String>> {
+        let mut keys = IndexMap::<&str, BTreeSet<&str>>::new();
+        for text in texts {
+            for caps in SECRET.captures_iter(text) {
+                let backend = caps.get(1).unwrap().as_str();
+                let key = caps.get(2).unwrap().as_str();
+                keys.entry(backend).or_default().insert(key);
+            }
+        }
+
+        let mut secrets = Secrets::default();
+        let mut errors = Vec::new();
+        for (name, keys) in keys {
+            let keys = keys.into_iter().collect::<Vec<_>>();
+            let values = match backends.get(name) {
+                Some(backend) => backend.retrieve(&keys),
+                None => Err("no such backend is configured".to_owned()),
+            };
+            match values {
+                Ok(values) => secrets.values.extend(
+                    values
+                        .into_iter()
+                        .map(|(key, value)| (format!("{}.{}", name, key), value)),
+                ),
+                Err(error) => errors.push(format!("Secret backend {:?}: {}", name, error)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(secrets)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Adds the secrets of `other`, referenced by other config files.
+    pub fn extend(&mut self, other: Secrets) {
+        self.values.extend(other.values);
+    }
+
+    /// Replaces the references to secrets by their values.
+    pub fn interpolate(&self, input: &str) -> String {
+        SECRET
+            .replace_all(input, |caps: &Captures<'_>| {
+                let reference = format!("{}.{}", &caps[1], &caps[2]);
+                self.values
+                    .get(&reference)
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_owned())
+            })
+            .into_owned()
+    }
+
+    /// Hides the values of the secrets in a message about the configuration.
+    pub fn redact(&self, message: &str) -> String {
+        self.values
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .fold(message.to_owned(), |message, (reference, value)| {
+                message.replace(value.as_str(), &format!("SECRET[{}]", reference))
+            })
+    }
+}
+
+impl SecretBackend {
+    fn retrieve(&self, keys: &[&str]) -> Result<HashMap<String, String>, String> {
+        match self {
+            SecretBackend::Exec {
+                command,
+                timeout_secs,
+            } => exec(command, Duration::from_secs(*timeout_secs), keys),
+            SecretBackend::File { path } => keys
+                .iter()
+                .map(|&key| {
+                    if key.starts_with('.') {
+                        return Err(format!("invalid key {:?}", key));
+                    }
+                    fs::read_to_string(path.join(key))
+                        .map(|value| (key.to_owned(), value.trim_end_matches('\n').to_owned()))
+                        .map_err(|error| format!("unable to read key {:?}: {}", key, error))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ExecSecret {
+    value: Option<String>,
+    error: Option<String>,
+}
+
+fn exec(
+    command: &[String],
+    timeout: Duration,
+    keys: &[&str],
+) -> Result<HashMap<String, String>, String> {
+    let (program, args) = command.split_first().ok_or("the command is empty")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("unable to run {:?}: {}", program, error))?;
+
+    // Read the output aside, so that the command can't block on writing it.
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let output = thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+    // And write the request aside, so that a command not reading it can't
+    // block past the timeout. It may exit without reading all of it.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let request = serde_json::json!({ "version": "1.0", "secrets": keys }).to_string();
+    let written = thread::spawn(move || match stdin.write_all(request.as_bytes()) {
+        Err(error) if error.kind() != io::ErrorKind::BrokenPipe => Err(error),
+        _ => Ok(()),
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{:?} timed out after {:?}", program, timeout));
+            }
+            Err(error) => return Err(format!("unable to wait for {:?}: {}", program, error)),
+        }
+    };
+    if !status.success() {
+        return Err(format!("{:?} exited with {}", program, status));
+    }
+    written
+        .join()
+        .expect("writing the request doesn't panic")
+        .map_err(|error| format!("unable to write to {:?}: {}", program, error))?;
+    let output = output
+        .join()
+        .expect("reading the output doesn't panic")
+        .map_err(|error| format!("unable to read the output of {:?}: {}", program, error))?;
+
+    let mut response = serde_json::from_slice::<HashMap<String, ExecSecret>>(&output)
+        .map_err(|error| format!("invalid output of {:?}: {}", program, error))?;
+    keys.iter()
+        .map(|&key| match response.remove(key) {
+            Some(ExecSecret {
+                value: Some(value),
+                error: None,
+            }) => Ok((key.to_owned(), value)),
+            Some(ExecSecret {
+                error: Some(error), ..
+            }) => Err(format!("unable to retrieve key {:?}: {}", key, error)),
+            _ => Err(format!("no value for key {:?}", key)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backends(backends: Vec<(&str, SecretBackend)>) -> IndexMap<String, SecretBackend> {
+        backends
+            .into_iter()
+            .map(|(name, backend)| (name.to_owned(), backend))
+            .collect()
+    }
+
+    #[test]
+    fn retrieves_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("db_password"), "hunter2\n").unwrap();
+        let backends = backends(vec![(
+            "mounted",
+            SecretBackend::File {
+                path: dir.path().to_path_buf(),
+            },
+        )]);
+
+        let input = r#"password = "SECRET[mounted.db_password]""#;
+        let secrets = Secrets::retrieve(&backends, vec![input]).unwrap();
+        assert_eq!(secrets.interpolate(input), r#"password = "hunter2""#);
+        assert_eq!(
+            secrets.redact("invalid value \"hunter2\""),
+            "invalid value \"SECRET[mounted.db_password]\""
+        );
+
+        let errors = Secrets::retrieve(
+            &backends,
+            vec!["SECRET[mounted.missing]", "SECRET[vault.token]"],
+        )
+        .err()
+        .unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Secret backend \"mounted\": unable to read key"));
+        assert_eq!(
+            errors[1],
+            "Secret backend \"vault\": no such backend is configured"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn retrieves_from_commands() {
+        let script = r#"cat > /dev/null; echo '{"token": {"value": "s3cr3t", "error": null}, "gone": {"value": null, "error": "expired"}}'"#;
+        let backends = backends(vec![(
+            "vault",
+            SecretBackend::Exec {
+                command: vec!["sh".into(), "-c".into(), script.into()],
+                timeout_secs: 5,
+            },
+        )]);
+
+        let input = "auth = \"Bearer SECRET[vault.token]\"";
+        let secrets = Secrets::retrieve(&backends, vec![input]).unwrap();
+        assert_eq!(secrets.interpolate(input), "auth = \"Bearer s3cr3t\"");
+
+        let errors = Secrets::retrieve(&backends, vec!["SECRET[vault.gone]"])
+            .err()
+            .unwrap();
+        assert_eq!(
+            errors,
+            vec!["Secret backend \"vault\": unable to retrieve key \"gone\": expired"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn times_out_commands() {
+        let backends = backends(vec![(
+            "slow",
+            SecretBackend::Exec {
+                command: vec!["sleep".into(), "10".into()],
+                timeout_secs: 0,
+            },
+        )]);
+
+        let errors = Secrets::retrieve(&backends, vec!["SECRET[slow.token]"])
+            .err()
+            .unwrap();
+        assert!(errors[0].contains("timed out"));
+
+        // Even when the command doesn't read a request too large for the pipe.
+        let input = (0..10_000)
+            .map(|i| format!("SECRET[slow.token_{}]", i))
+            .collect::<String>();
+        let started = Instant::now();
+        let errors = Secrets::retrieve(&backends, vec![input.as_str()])
+            .err()
+            .unwrap();
+        assert!(errors[0].contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_may_leave_the_request_unread() {
+        let script = r#"echo '{"token": {"value": "s3cr3t", "error": null}}'"#;
+        let backends = backends(vec![(
+            "vault",
+            SecretBackend::Exec {
+                command: vec!["sh".into(), "-c".into(), script.into()],
+                timeout_secs: 5,
+            },
+        )]);
+
+        let input = (0..10_000)
+            .map(|i| format!("SECRET[vault.token_{}]", i))
+            .chain(std::iter::once("SECRET[vault.token]".to_owned()))
+            .collect::<String>();
+        let errors = Secrets::retrieve(&backends, vec![input.as_str()])
+            .err()
+            .unwrap();
+        // Only the missing keys are errors, not the unread request.
+        assert!(errors[0].contains("no value for key \"token_0\""));
+    }
+
+    #[test]
+    fn reads_backends_of_each_file() {
+        let toml = "[secret.mounted]\ntype = \"file\"\npath = \"/run/secrets\"\n";
+        let yaml = "secret:\n  vault:\n    type: exec\n    command: [vault-secrets]\n";
+
+        let backends = super::backends(vec![
+            (toml, super::super::Format::Toml),
+            (yaml, super::super::Format::Yaml),
+            ("[sources.in", super::super::Format::Toml),
+        ]);
+
+        assert_eq!(
+            backends["mounted"],
+            SecretBackend::File {
+                path: "/run/secrets".into()
+            }
+        );
+        assert_eq!(
+            backends["vault"],
+            SecretBackend::Exec {
+                command: vec!["vault-secrets".into()],
+                timeout_secs: 5
+            }
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! Pipeline templates, declared once in the `templates` section and
+//! instantiated by the `pipelines` section.
+//!
+//! A template is a chain of transforms, each taking the events of the one
+//! before it, whose options can reference the parameters of the template as
+//! `{{ params.<name> }}`. Each pipeline is expanded into transforms named
+//! `<pipeline>.<transform>` as the config is built: the first one takes the
+//! inputs of the pipeline, and the components taking the pipeline as an input
+//! take the last one instead.
+
+use super::{builder::ConfigBuilder, TransformOuter};
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+lazy_static! {
+    static ref PARAM: Regex = Regex::new(r"\{\{\s*params\.(\w+)\s*\}\}").unwrap();
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    /// The parameters every pipeline has to give a value to.
+    #[serde(default)]
+    pub params: Vec<String>,
+    /// The values of the parameters pipelines can leave out.
+    #[serde(default)]
+    pub defaults: IndexMap<String, Value>,
+    /// The transforms of the chain, in order.
+    pub transforms: IndexMap<String, Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Pipeline {
+    pub template: String,
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub params: IndexMap<String, Value>,
+}
+
+/// Expands the pipelines of `builder` into its transforms, returning the names
+/// of the transforms each pipeline expanded into.
+pub(super) fn expand(
+    builder: &mut ConfigBuilder,
+) -> Result<IndexMap<String, Vec<String>>, Vec<String>> {
+    let mut expansions = IndexMap::new();
+    let mut errors = Vec::new();
+
+    for (name, pipeline) in std::mem::take(&mut builder.pipelines) {
+        if builder.sources.contains_key(&name) || builder.transforms.contains_key(&name) {
+            errors.push(format!(
+                "Pipeline {:?} has the name of another component.",
+                name
+            ));
+            continue;
+        }
+
+        match instantiate(&name, &pipeline, &builder.templates) {
+            Ok(transforms) => {
+                let mut children = Vec::new();
+                for (child, transform) in transforms {
+                    if builder.transforms.contains_key(&child) {
+                        errors.push(format!("duplicate transform name found: {}", child));
+                    }
+                    builder.transforms.insert(child.clone(), transform);
+                    children.push(child);
+                }
+                expansions.insert(name, children);
+            }
+            Err(errs) => {
+                errors.extend(
+                    errs.into_iter()
+                        .map(|error| format!("Pipeline {:?}: {}.", name, error)),
+                );
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Components taking a pipeline as an input take its last transform.
+    let inputs = builder
+        .transforms
+        .values_mut()
+        .map(|transform| &mut transform.inputs)
+        .chain(builder.sinks.values_mut().map(|sink| &mut sink.inputs));
+    for inputs in inputs {
+        for input in inputs.iter_mut() {
+            if let Some(last) = expansions.get(input.as_str()).and_then(|c| c.last()) {
+                *input = last.clone();
+            }
+        }
+    }
+
+    Ok(expansions)
+}
+
+/// The transforms of a pipeline, by name, with the values of its parameters.
+fn instantiate(
+    name: &str,
+    pipeline: &Pipeline,
+    templates: &IndexMap<String, Template>,
+) -> Result<IndexMap<String, TransformOuter>, Vec<String>> {
+    let template = templates
+        .get(&pipeline.template)
+        .ok_or_else(|| vec![format!("there is no template {:?}", pipeline.template)])?;
+    let mut errors = Vec::new();
+
+    let mut params = template.defaults.clone();
+    for (param, value) in &pipeline.params {
+        if template.params.contains(param) || template.defaults.contains_key(param) {
+            params.insert(param.clone(), value.clone());
+        } else {
+            errors.push(format!(
+                "template {:?} has no parameter {:?}",
+                pipeline.template, param
+            ));
+        }
+    }
+    for param in &template.params {
+        if !params.contains_key(param) {
+            errors.push(format!("no value is given to parameter {:?}", param));
+        }
+    }
+    if template.transforms.is_empty() {
+        errors.push(format!(
+            "template {:?} has no transforms",
+            pipeline.template
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut transforms = IndexMap::new();
+    let mut inputs = pipeline.inputs.clone();
+    for (child, config) in &template.transforms {
+        let mut config = config.clone();
+        let mut undeclared = Vec::new();
+        substitute(&mut config, &params, &mut undeclared);
+        errors.extend(undeclared.into_iter().map(|param| {
+            format!(
+                "transform {:?} references parameter {:?}, which template {:?} doesn't declare",
+                child, param, pipeline.template
+            )
+        }));
+
+        match &mut config {
+            Value::Object(options) if options.contains_key("inputs") => {
+                errors.push(format!(
+                    "transform {:?} takes its inputs from the pipeline",
+                    child
+                ));
+                continue;
+            }
+            Value::Object(options) => {
+                options.insert("inputs".into(), inputs.clone().into());
+            }
+            _ => {
+                errors.push(format!("transform {:?} isn't a table", child));
+                continue;
+            }
+        }
+
+        let full_name = format!("{}.{}", name, child);
+        match serde_json::from_value::<TransformOuter>(config) {
+            Ok(transform) => {
+                transforms.insert(full_name.clone(), transform);
+            }
+            Err(error) => errors.push(format!("transform {:?}: {}", child, error)),
+        }
+        inputs = vec![full_name];
+    }
+
+    if errors.is_empty() {
+        Ok(transforms)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Replaces the references to parameters in the options of a transform,
+/// recording those to parameters that don't exist. A string made of a single
+/// reference takes the value of the parameter as it is, so that parameters
+/// can be given to options that aren't strings.
+fn substitute(value: &mut Value, params: &IndexMap<String, Value>, undeclared: &mut Vec<String>) {
+    match value {
+        Value::String(string) => {
+            let whole = PARAM
+                .captures(string)
+                .filter(|caps| caps[0].len() == string.len())
+                .map(|caps| caps[1].to_owned());
+            if let Some(param) = whole {
+                match params.get(&param) {
+                    Some(param) => *value = param.clone(),
+                    None => undeclared.push(param),
+                }
+                return;
+            }
+
+            let replaced =
+                PARAM.replace_all(string, |caps: &Captures<'_>| match params.get(&caps[1]) {
+                    Some(Value::String(param)) => param.clone(),
+                    Some(param) => param.to_string(),
+                    None => {
+                        undeclared.push(caps[1].to_owned());
+                        caps[0].to_owned()
+                    }
+                });
+            *string = replaced.into_owned();
+        }
+        Value::Array(values) => {
+            for value in values {
+                substitute(value, params, undeclared);
+            }
+        }
+        Value::Object(options) => {
+            for value in options.values_mut() {
+                substitute(value, params, undeclared);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "sources-stdin",
+    feature = "transforms-json_parser",
+    feature = "sinks-console"
+))]
+mod tests {
+    use crate::config::{load_from_str, Format};
+
+    fn config(pipelines: &str) -> String {
+        format!(
+            r#"
+            [sources.in]
+            type = "stdin"
+
+            [templates.parse]
+            params = ["field"]
+            defaults = {{ drop_invalid = true }}
+
+            [templates.parse.transforms.json]
+            type = "json_parser"
+            field = "{{{{ params.field }}}}"
+            drop_invalid = "{{{{ params.drop_invalid }}}}"
+
+            [templates.parse.transforms.payload]
+            type = "json_parser"
+            field = "{{{{ params.field }}}}_payload"
+
+            {}
+
+            [sinks.out]
+            type = "console"
+            inputs = ["db"]
+            encoding = "json"
+            "#,
+            pipelines
+        )
+    }
+
+    #[test]
+    fn expands_pipelines() {
+        let config = load_from_str(
+            &config(
+                r#"
+                [pipelines.app]
+                template = "parse"
+                inputs = ["in"]
+                params = { field = "msg" }
+
+                [pipelines.db]
+                template = "parse"
+                inputs = ["app"]
+                params = { field = "query", drop_invalid = false }
+                "#,
+            ),
+            Format::Toml,
+        )
+        .unwrap();
+
+        let mut names = config.transforms.keys().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["app.json", "app.payload", "db.json", "db.payload"]
+        );
+        assert_eq!(config.transforms["app.json"].inputs, vec!["in"]);
+        assert_eq!(config.transforms["app.payload"].inputs, vec!["app.json"]);
+        assert_eq!(config.transforms["db.json"].inputs, vec!["app.payload"]);
+        assert_eq!(config.sinks["out"].inputs, vec!["db.payload"]);
+        assert_eq!(
+            config.expansions()["db"],
+            vec!["db.json".to_owned(), "db.payload".to_owned()]
+        );
+        assert_eq!(config.get_inputs("app"), vec!["app.json"]);
+
+        let json = serde_json::to_value(&config.transforms["db.json"]).unwrap();
+        assert_eq!(json["field"], "query");
+        assert_eq!(json["drop_invalid"], false);
+        let payload = serde_json::to_value(&config.transforms["app.payload"]).unwrap();
+        assert_eq!(payload["field"], "msg_payload");
+    }
+
+    #[test]
+    fn reports_invalid_pipelines() {
+        let errors = load_from_str(
+            &config(
+                r#"
+                [pipelines.app]
+                template = "parse"
+                inputs = ["in"]
+                params = { feild = "msg" }
+
+                [pipelines.db]
+                template = "prase"
+                inputs = ["app"]
+                "#,
+            ),
+            Format::Toml,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            errors,
+            vec![
+                "Pipeline \"app\": template \"parse\" has no parameter \"feild\".",
+                "Pipeline \"app\": no value is given to parameter \"field\".",
+                "Pipeline \"db\": there is no template \"prase\".",
+            ]
+        );
+    }
+}
@@ -11,7 +11,7 @@ use indexmap::IndexMap;
 use std::{collections::HashMap, path::PathBuf};
 
 pub async fn build_unit_tests_main(path: PathBuf) -> Result<Vec<UnitTest>, Vec<String>> {
-    let config = super::loading::load_builder_from_paths(&[path])?;
+    let config = super::loading::load_builder_from_paths(&[path], None)?;
 
     // Ignore failures on calls other than the first
     crate::config::LOG_SCHEMA
@@ -21,9 +21,10 @@ pub async fn build_unit_tests_main(path: PathBuf) -> Result<Vec<UnitTest>, Vec<S
     build_unit_tests(config).await
 }
 
-async fn build_unit_tests(builder: ConfigBuilder) -> Result<Vec<UnitTest>, Vec<String>> {
+async fn build_unit_tests(mut builder: ConfigBuilder) -> Result<Vec<UnitTest>, Vec<String>> {
     let mut tests = vec![];
     let mut errors = vec![];
+    let pipelines = super::template::expand(&mut builder)?;
 
     // Don't let this escape since it's not validated
     let mut config = Config {
@@ -33,11 +34,14 @@ async fn build_unit_tests(builder: ConfigBuilder) -> Result<Vec<UnitTest>, Vec<S
         sources: builder.sources,
         sinks: builder.sinks,
         transforms: builder.transforms,
+        enrichment_tables: builder.enrichment_tables,
         tests: builder.tests,
-        expansions: Default::default(),
+        expansions: pipelines,
+        secrets: builder.secrets,
     };
 
     super::compiler::expand_macros(&mut config)?;
+    crate::enrichment_tables::load(&config.enrichment_tables)?;
 
     for test in &config.tests {
         match build_unit_test(test, &config).await {
@@ -1,5 +1,6 @@
-use super::{Config, DataType};
-use std::collections::HashMap;
+use super::{named_output, Config, DataType};
+use crate::buffers::dead_letter;
+use std::collections::{HashMap, HashSet};
 
 pub fn check_shape(config: &Config) -> Result<(), Vec<String>> {
     let mut errors = vec![];
@@ -11,7 +12,38 @@ pub fn check_shape(config: &Config) -> Result<(), Vec<String>> {
         errors.push("No sinks defined in the config.".to_owned());
     }
 
+    for output in transform_outputs(config) {
+        if config.sources.contains_key(&output)
+            || config.transforms.contains_key(&output)
+            || config.sinks.contains_key(&output)
+        {
+            errors.push(format!(
+                "Named output {:?} has the same name as another component.",
+                output
+            ));
+        }
+    }
+
+    for (name, transform) in config.transforms.iter() {
+        match transform.concurrency {
+            Some(0) => errors.push(format!(
+                "Transform {:?} must have a concurrency of at least 1.",
+                name
+            )),
+            Some(concurrency) if concurrency > 1 && !transform.inner.stateless() => {
+                errors.push(format!(
+                    "Transform {:?} of type {:?} keeps state between events and can't run with a concurrency above 1.",
+                    name,
+                    transform.inner.transform_type()
+                ))
+            }
+            _ => {}
+        }
+    }
+
     // Warnings and errors
+    let dead_letter_outputs = dead_letter_outputs(config).collect::<HashSet<_>>();
+    let transform_outputs = transform_outputs(config).collect::<HashSet<_>>();
     let sink_inputs = config
         .sinks
         .iter()
@@ -30,7 +62,11 @@ pub fn check_shape(config: &Config) -> Result<(), Vec<String>> {
         }
 
         for input in inputs {
-            if !config.sources.contains_key(&input) && !config.transforms.contains_key(&input) {
+            if !config.sources.contains_key(&input)
+                && !config.transforms.contains_key(&input)
+                && !dead_letter_outputs.contains(&input)
+                && !transform_outputs.contains(&input)
+            {
                 errors.push(format!(
                     "Input {:?} for {} {:?} doesn't exist.",
                     input, output_type, name
@@ -54,7 +90,13 @@ pub fn warnings(config: &Config) -> Option<Vec<String>> {
         .transforms
         .keys()
         .map(|name| ("transform", name.clone()));
-    for (input_type, name) in transform_names.chain(source_names) {
+    let dead_letter_names = dead_letter_outputs(config).map(|name| ("dead-letter output", name));
+    let named_output_names = transform_outputs(config).map(|name| ("named output", name));
+    for (input_type, name) in transform_names
+        .chain(source_names)
+        .chain(dead_letter_names)
+        .chain(named_output_names)
+    {
         if !config
             .transforms
             .iter()
@@ -79,6 +121,24 @@ pub fn warnings(config: &Config) -> Option<Vec<String>> {
     }
 }
 
+fn dead_letter_outputs(config: &Config) -> impl Iterator<Item = String> + '_ {
+    config
+        .sinks
+        .iter()
+        .filter(|(_, sink)| sink.dead_letter)
+        .map(|(name, _)| dead_letter::output_name(name))
+}
+
+fn transform_outputs(config: &Config) -> impl Iterator<Item = String> + '_ {
+    config.transforms.iter().flat_map(|(name, transform)| {
+        transform
+            .inner
+            .named_outputs()
+            .into_iter()
+            .map(move |output| named_output(name, &output))
+    })
+}
+
 pub fn typecheck(config: &Config) -> Result<(), Vec<String>> {
     Graph::from(config).typecheck()
 }
@@ -216,10 +276,27 @@ impl From<&Config> for Graph {
                 config.inner.output_type(),
                 config.inputs.clone(),
             );
+
+            // Named outputs carry the same type as the transform's output.
+            let ty = config.inner.output_type();
+            for output in config.inner.named_outputs() {
+                graph.add_transform(&named_output(name, &output), ty, ty, vec![name]);
+            }
         }
 
         for (name, config) in config.sinks.iter() {
             graph.add_sink(name, config.inner.input_type(), config.inputs.clone());
+
+            // Rejected events are passed through from the sink's inputs as is.
+            if config.dead_letter {
+                let ty = config.inner.input_type();
+                graph.add_transform(
+                    &dead_letter::output_name(name),
+                    ty,
+                    ty,
+                    config.inputs.clone(),
+                );
+            }
         }
 
         graph
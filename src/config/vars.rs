@@ -1,53 +1,168 @@
+use lazy_static::lazy_static;
 use regex::{Captures, Regex};
 use std::collections::HashMap;
 
-pub fn interpolate(input: &str, vars: &HashMap<String, String>) -> String {
-    let re = Regex::new(r"\$\$|\$(\w+)|\$\{(\w+)(?::-([^}]+)?)?\}").unwrap();
-    re.replace_all(input, |caps: &Captures<'_>| {
-        caps.get(1)
-            .or_else(|| caps.get(2))
-            .map(|m| m.as_str())
-            .map(|name| {
-                vars.get(name).map(|val| val.as_str()).unwrap_or_else(|| {
-                    caps.get(3).map(|m| m.as_str()).unwrap_or_else(|| {
+lazy_static! {
+    static ref VAR: Regex = Regex::new(r"\$\$|\$(\w+)|\$\{(\w+)(?::([-?+])([^}]*))?\}").unwrap();
+}
+
+/// An environment variable referenced by a config without being set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Missing {
+    pub name: String,
+    /// The message of a `${VAR:?message}` reference.
+    pub message: Option<String>,
+}
+
+/// Replaces the references to environment variables by their values, as a
+/// shell would:
+///
+/// - `$VAR` and `${VAR}` are replaced by the value of `VAR`,
+/// - `${VAR:-default}` by `default` when `VAR` isn't set,
+/// - `${VAR:+alt}` by `alt` when `VAR` is set and not empty, and by nothing
+///   otherwise,
+/// - `${VAR:?message}` is missing when `VAR` is unset or empty.
+///
+/// Variables that aren't set are replaced by nothing, or are missing when
+/// `strict`. Every missing variable is returned, once.
+pub fn interpolate(
+    input: &str,
+    vars: &HashMap<String, String>,
+    strict: bool,
+) -> Result<String, Vec<Missing>> {
+    let mut missing = Vec::new();
+    let output = VAR
+        .replace_all(input, |caps: &Captures<'_>| {
+            let name = match caps.get(1).or_else(|| caps.get(2)) {
+                Some(name) => name.as_str(),
+                None => return "$".to_owned(),
+            };
+            let value = vars.get(name).map(String::as_str);
+            let operand = caps.get(4).map(|m| m.as_str()).unwrap_or("");
+
+            let set = value.filter(|value| !value.is_empty());
+            let var = match caps.get(3).map(|m| m.as_str()) {
+                Some("-") => return value.unwrap_or(operand).to_owned(),
+                Some("+") => return set.map(|_| operand).unwrap_or("").to_owned(),
+                Some("?") => match set {
+                    Some(value) => return value.to_owned(),
+                    None => Missing {
+                        name: name.to_owned(),
+                        message: Some(operand.to_owned()).filter(|message| !message.is_empty()),
+                    },
+                },
+                _ => match value {
+                    Some(value) => return value.to_owned(),
+                    None if strict => Missing {
+                        name: name.to_owned(),
+                        message: None,
+                    },
+                    None => {
                         warn!("unknown env var in config: {:?}", name);
-                        ""
-                    })
-                })
-            })
-            .unwrap_or("$")
-            .to_string()
-    })
-    .into_owned()
+                        return String::new();
+                    }
+                },
+            };
+            if !missing.contains(&var) {
+                missing.push(var);
+            }
+            String::new()
+        })
+        .into_owned();
+
+    if missing.is_empty() {
+        Ok(output)
+    } else {
+        Err(missing)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::interpolate;
-    #[test]
-    fn interpolation() {
-        let vars = vec![
+    use super::{interpolate, Missing};
+    use std::collections::HashMap;
+
+    fn vars() -> HashMap<String, String> {
+        vec![
             ("FOO".into(), "dogs".into()),
             ("FOOBAR".into(), "cats".into()),
+            ("EMPTY".into(), "".into()),
         ]
         .into_iter()
-        .collect();
-
-        assert_eq!("dogs", interpolate("$FOO", &vars));
-        assert_eq!("dogs", interpolate("${FOO}", &vars));
-        assert_eq!("cats", interpolate("${FOOBAR}", &vars));
-        assert_eq!("xcatsy", interpolate("x${FOOBAR}y", &vars));
-        assert_eq!("x", interpolate("x$FOOBARy", &vars));
-        assert_eq!("$ x", interpolate("$ x", &vars));
-        assert_eq!("$FOO", interpolate("$$FOO", &vars));
-        assert_eq!("", interpolate("$NOT_FOO", &vars));
-        assert_eq!("-FOO", interpolate("$NOT-FOO", &vars));
-        assert_eq!("${FOO x", interpolate("${FOO x", &vars));
-        assert_eq!("${}", interpolate("${}", &vars));
-        assert_eq!("dogs", interpolate("${FOO:-cats}", &vars));
-        assert_eq!("dogcats", interpolate("${NOT:-dogcats}", &vars));
-        assert_eq!("dogs and cats", interpolate("${NOT:-dogs and cats}", &vars));
-        assert_eq!("${:-cats}", interpolate("${:-cats}", &vars));
-        assert_eq!("", interpolate("${NOT:-}", &vars));
+        .collect()
+    }
+
+    #[test]
+    fn interpolation() {
+        let vars = vars();
+        let interpolate = |input: &str| interpolate(input, &vars, false).unwrap();
+
+        assert_eq!("dogs", interpolate("$FOO"));
+        assert_eq!("dogs", interpolate("${FOO}"));
+        assert_eq!("cats", interpolate("${FOOBAR}"));
+        assert_eq!("xcatsy", interpolate("x${FOOBAR}y"));
+        assert_eq!("x", interpolate("x$FOOBARy"));
+        assert_eq!("$ x", interpolate("$ x"));
+        assert_eq!("$FOO", interpolate("$$FOO"));
+        assert_eq!("", interpolate("$NOT_FOO"));
+        assert_eq!("-FOO", interpolate("$NOT-FOO"));
+        assert_eq!("${FOO x", interpolate("${FOO x"));
+        assert_eq!("${}", interpolate("${}"));
+        assert_eq!("dogs", interpolate("${FOO:-cats}"));
+        assert_eq!("dogcats", interpolate("${NOT:-dogcats}"));
+        assert_eq!("dogs and cats", interpolate("${NOT:-dogs and cats}"));
+        assert_eq!("${:-cats}", interpolate("${:-cats}"));
+        assert_eq!("", interpolate("${NOT:-}"));
+    }
+
+    #[test]
+    fn alternative_and_required_interpolation() {
+        let vars = vars();
+
+        assert_eq!(Ok("cats".into()), interpolate("${FOO:+cats}", &vars, false));
+        assert_eq!(Ok("".into()), interpolate("${EMPTY:+cats}", &vars, false));
+        assert_eq!(Ok("".into()), interpolate("${NOT:+cats}", &vars, true));
+        assert_eq!(
+            Ok("dogs".into()),
+            interpolate("${FOO:?unused}", &vars, false)
+        );
+        assert_eq!(
+            Err(vec![
+                Missing {
+                    name: "NOT".into(),
+                    message: Some("needed for the sink".into())
+                },
+                Missing {
+                    name: "EMPTY".into(),
+                    message: None
+                }
+            ]),
+            interpolate(
+                "${NOT:?needed for the sink} ${EMPTY:?} ${NOT:?needed for the sink}",
+                &vars,
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn strict_interpolation() {
+        let vars = vars();
+
+        assert_eq!(Ok("dogs ".into()), interpolate("$FOO $EMPTY", &vars, true));
+        assert_eq!(Ok("cats".into()), interpolate("${NOT:-cats}", &vars, true));
+        assert_eq!(
+            Err(vec![
+                Missing {
+                    name: "NOT".into(),
+                    message: None
+                },
+                Missing {
+                    name: "NOT_FOO".into(),
+                    message: None
+                }
+            ]),
+            interpolate("$NOT ${NOT_FOO} $NOT", &vars, true)
+        );
     }
 }
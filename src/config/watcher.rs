@@ -21,7 +21,8 @@ const CONFIG_WATCH_DELAY: std::time::Duration = std::time::Duration::from_secs(1
 #[cfg(unix)]
 const RETRY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-/// Triggers SIGHUP when file on config_path changes.
+/// Triggers SIGHUP when file on config_path changes, or when a file is added
+/// to, changed in or removed from a config directory.
 /// Accumulates file changes until no change for given duration has occurred.
 /// Has best effort guarantee of detecting all file changes from the end of
 /// this function until the main thread stops.
@@ -112,7 +113,12 @@ fn create_watcher(
 #[cfg(unix)]
 fn add_paths(watcher: &mut RecommendedWatcher, config_paths: &[PathBuf]) -> Result<(), Error> {
     for path in config_paths {
-        watcher.watch(path, RecursiveMode::NonRecursive)?;
+        let mode = if path.is_dir() {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(path, mode)?;
     }
     Ok(())
 }
@@ -121,7 +127,7 @@ fn add_paths(watcher: &mut RecommendedWatcher, config_paths: &[PathBuf]) -> Resu
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_util::{temp_file, trace_init};
+    use crate::test_util::{temp_dir, temp_file, trace_init};
     use std::time::Duration;
     use std::{fs::File, io::Write};
     #[cfg(unix)]
@@ -151,6 +157,22 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn dir_file_added() {
+        trace_init();
+
+        let delay = Duration::from_secs(3);
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("sources")).unwrap();
+
+        let _ = spawn_thread(&[dir.clone()], delay).unwrap();
+
+        let mut file = File::create(dir.join("sources").join("in.toml")).unwrap();
+        if !test(&mut file, delay * 5).await {
+            panic!("Test timed out");
+        }
+    }
+
     #[tokio::test]
     async fn sym_file_update() {
         trace_init();
@@ -0,0 +1,507 @@
+//! Tables of reference data loaded from CSV files, for events to be enriched
+//! with.
+//!
+//! The tables of the running configuration are kept in a registry by name.
+//! The tables of a configuration being built are staged next to them, and
+//! only replace them once that configuration is running. Every column of a
+//! table is indexed, and each table checks its file for changes in the
+//! background, reading it again when it changes. A table that fails to read
+//! keeps its previous content.
+
+use crate::{
+    event::Value,
+    internal_events::{EnrichmentTableReloadFailed, EnrichmentTableReloaded},
+};
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::Read,
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock, Weak},
+    time::{Duration, SystemTime},
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EnrichmentTableConfig {
+    /// The CSV file holding the table, with the names of the columns in its
+    /// first row.
+    pub path: PathBuf,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// How often to check the file for changes, in seconds.
+    #[serde(default = "default_reload_interval_secs")]
+    pub reload_interval_secs: u64,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+fn default_reload_interval_secs() -> u64 {
+    10
+}
+
+#[derive(Debug, Snafu)]
+pub enum TableError {
+    #[snafu(display("Delimiter {:?} isn't a single byte character", delimiter))]
+    InvalidDelimiter { delimiter: char },
+    #[snafu(display("reload_interval_secs must be at least 1"))]
+    ZeroReloadInterval,
+    #[snafu(display("Unable to read {:?}: {}", path, source))]
+    ReadFile { path: PathBuf, source: csv::Error },
+}
+
+/// The content of a table at some point in time.
+#[derive(Debug)]
+pub struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// For each column, the rows holding each of its values.
+    indexes: Vec<HashMap<String, Vec<usize>>>,
+}
+
+impl Table {
+    fn read(config: &EnrichmentTableConfig) -> Result<Self, TableError> {
+        let delimiter = delimiter(config.delimiter)?;
+        let reader = fs::File::open(&config.path)
+            .map_err(csv::Error::from)
+            .context(ReadFile { path: &config.path })?;
+        Self::from_reader(reader, delimiter).context(ReadFile { path: &config.path })
+    }
+
+    pub fn from_reader(reader: impl Read, delimiter: u8) -> Result<Self, csv::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(reader);
+
+        let columns = reader
+            .headers()?
+            .iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut indexes = vec![HashMap::<_, Vec<_>>::new(); columns.len()];
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let row = record?.iter().map(String::from).collect::<Vec<_>>();
+            for (index, value) in indexes.iter_mut().zip(&row) {
+                index.entry(value.clone()).or_default().push(rows.len());
+            }
+            rows.push(row);
+        }
+
+        Ok(Self {
+            columns,
+            rows,
+            indexes,
+        })
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The first record whose columns hold all of the values of `conditions`,
+    /// given as pairs of column and value.
+    pub fn find(&self, conditions: &[(&str, &str)]) -> Result<Option<Value>, String> {
+        let conditions = conditions
+            .iter()
+            .map(|(column, value)| {
+                self.columns
+                    .iter()
+                    .position(|c| c == column)
+                    .map(|i| (i, *value))
+                    .ok_or_else(|| format!("no column named {:?}", column))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let ((first, value), rest) = conditions
+            .split_first()
+            .ok_or("at least one column is needed to find a record")?;
+
+        let record = self.indexes[*first]
+            .get(*value)
+            .into_iter()
+            .flatten()
+            .map(|&row| &self.rows[row])
+            .find(|row| rest.iter().all(|(i, value)| row[*i] == *value))
+            .map(|row| {
+                let record = self
+                    .columns
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(|value| Value::from(value.as_str())))
+                    .collect::<BTreeMap<_, _>>();
+                Value::Map(record)
+            });
+        Ok(record)
+    }
+}
+
+fn delimiter(delimiter: char) -> Result<u8, TableError> {
+    if delimiter.is_ascii() {
+        Ok(delimiter as u8)
+    } else {
+        Err(TableError::InvalidDelimiter { delimiter })
+    }
+}
+
+/// A table read from its file, along with how it was read.
+#[derive(Debug)]
+struct Content {
+    config: EnrichmentTableConfig,
+    table: Table,
+    modified: Option<SystemTime>,
+}
+
+impl Content {
+    fn read(config: &EnrichmentTableConfig) -> Result<Self, TableError> {
+        if config.reload_interval_secs == 0 {
+            return Err(TableError::ZeroReloadInterval);
+        }
+        let modified = modified(config);
+        let table = Table::read(config)?;
+        Ok(Self {
+            config: config.clone(),
+            table,
+            modified,
+        })
+    }
+}
+
+/// A table of the configuration, whose content changes along with its file.
+#[derive(Debug)]
+pub struct EnrichmentTable {
+    name: String,
+    config: RwLock<EnrichmentTableConfig>,
+    table: RwLock<Arc<Table>>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl EnrichmentTable {
+    fn load(name: &str, content: Content) -> Self {
+        Self {
+            name: name.to_string(),
+            config: RwLock::new(content.config),
+            table: RwLock::new(Arc::new(content.table)),
+            modified: Mutex::new(content.modified),
+        }
+    }
+
+    pub fn new(name: &str, table: Table) -> Self {
+        Self {
+            name: name.to_string(),
+            config: RwLock::new(EnrichmentTableConfig {
+                path: PathBuf::new(),
+                delimiter: default_delimiter(),
+                reload_interval_secs: default_reload_interval_secs(),
+            }),
+            table: RwLock::new(Arc::new(table)),
+            modified: Mutex::new(None),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The current content of the table.
+    pub fn table(&self) -> Arc<Table> {
+        Arc::clone(&self.table.read().unwrap())
+    }
+
+    fn update(&self, content: Content) {
+        *self.config.write().unwrap() = content.config;
+        *self.table.write().unwrap() = Arc::new(content.table);
+        *self.modified.lock().unwrap() = content.modified;
+    }
+
+    fn reload_interval(&self) -> Duration {
+        Duration::from_secs(self.config.read().unwrap().reload_interval_secs)
+    }
+
+    /// Reads the file again if it has changed since it was last read.
+    fn reload_if_changed(&self) {
+        let config = self.config.read().unwrap().clone();
+        let modified = modified(&config);
+        if modified.is_some() && modified == *self.modified.lock().unwrap() {
+            return;
+        }
+
+        match Table::read(&config) {
+            Ok(table) => {
+                emit!(EnrichmentTableReloaded {
+                    table: &self.name,
+                    rows: table.len(),
+                });
+                *self.table.write().unwrap() = Arc::new(table);
+                *self.modified.lock().unwrap() = modified;
+            }
+            Err(error) => emit!(EnrichmentTableReloadFailed {
+                table: &self.name,
+                error,
+            }),
+        }
+    }
+}
+
+fn modified(config: &EnrichmentTableConfig) -> Option<SystemTime> {
+    fs::metadata(&config.path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// The tables of a configuration, read but not in use yet.
+#[derive(Debug, Default)]
+struct Staged {
+    tables: HashMap<String, Arc<EnrichmentTable>>,
+    /// The new content of the tables in use whose configuration changed.
+    updates: Vec<(Arc<EnrichmentTable>, Content)>,
+    /// The tables that aren't in use yet.
+    added: Vec<Arc<EnrichmentTable>>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    tables: HashMap<String, Arc<EnrichmentTable>>,
+    staged: Option<Staged>,
+}
+
+impl Registry {
+    fn get(&self, name: &str) -> Option<Arc<EnrichmentTable>> {
+        match &self.staged {
+            Some(staged) => staged.tables.get(name).cloned(),
+            None => self.tables.get(name).cloned(),
+        }
+    }
+
+    fn stage(
+        &mut self,
+        configs: &IndexMap<String, EnrichmentTableConfig>,
+    ) -> Result<(), Vec<String>> {
+        self.staged = None;
+        let mut staged = Staged::default();
+        let mut errors = Vec::new();
+
+        for (name, config) in configs {
+            let result = match self.tables.get(name) {
+                Some(table) if *table.config.read().unwrap() == *config => Ok(Arc::clone(table)),
+                Some(table) => Content::read(config).map(|content| {
+                    staged.updates.push((Arc::clone(table), content));
+                    Arc::clone(table)
+                }),
+                None => Content::read(config).map(|content| {
+                    let table = Arc::new(EnrichmentTable::load(name, content));
+                    staged.added.push(Arc::clone(&table));
+                    table
+                }),
+            };
+            match result {
+                Ok(table) => {
+                    staged.tables.insert(name.clone(), table);
+                }
+                Err(error) => errors.push(format!("Enrichment table {:?}: {}", name, error)),
+            }
+        }
+
+        if errors.is_empty() {
+            self.staged = Some(staged);
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn commit(&mut self) {
+        if let Some(staged) = self.staged.take() {
+            for (table, content) in staged.updates {
+                table.update(content);
+            }
+            for table in &staged.added {
+                tokio::spawn(watch(Arc::downgrade(table)));
+            }
+            self.tables = staged.tables;
+        }
+    }
+}
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(Default::default);
+
+/// The table named `name` in the configuration being built, or else in the
+/// running one.
+pub fn get(name: &str) -> Option<Arc<EnrichmentTable>> {
+    REGISTRY.lock().unwrap().get(name)
+}
+
+/// Reads the tables of a configuration about to be built, for its components
+/// to look up, without changing the tables in use. If any table fails to
+/// load, nothing is staged.
+pub fn stage(configs: &IndexMap<String, EnrichmentTableConfig>) -> Result<(), Vec<String>> {
+    REGISTRY.lock().unwrap().stage(configs)
+}
+
+/// Makes the staged tables the ones to look events up in, once their
+/// configuration is running. Tables that stay in use are updated in place, so
+/// that the components already using them see the changes.
+pub fn commit() {
+    REGISTRY.lock().unwrap().commit()
+}
+
+/// Stages the tables of a configuration and commits them right away, for
+/// configurations that aren't run by a topology.
+pub fn load(configs: &IndexMap<String, EnrichmentTableConfig>) -> Result<(), Vec<String>> {
+    stage(configs)?;
+    commit();
+    Ok(())
+}
+
+/// Checks the file of a table for changes for as long as the table is in use.
+async fn watch(table: Weak<EnrichmentTable>) {
+    loop {
+        let interval = match table.upgrade() {
+            Some(table) => table.reload_interval(),
+            None => break,
+        };
+        tokio::time::delay_for(interval).await;
+        match table.upgrade() {
+            Some(table) => table.reload_if_changed(),
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    const OWNERS: &str = "hostname,team,owner\nweb-1,web,alice\nweb-2,web,bob\ndb-1,data,carol\n";
+
+    fn record(pairs: &[(&str, &str)]) -> Value {
+        Value::Map(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), Value::from(*v)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn finds_records() {
+        let table = Table::from_reader(OWNERS.as_bytes(), b',').unwrap();
+
+        assert_eq!(table.columns(), ["hostname", "team", "owner"]);
+        assert_eq!(
+            table.find(&[("hostname", "db-1")]).unwrap(),
+            Some(record(&[
+                ("hostname", "db-1"),
+                ("team", "data"),
+                ("owner", "carol")
+            ]))
+        );
+        assert_eq!(
+            table.find(&[("team", "web"), ("owner", "bob")]).unwrap(),
+            Some(record(&[
+                ("hostname", "web-2"),
+                ("team", "web"),
+                ("owner", "bob")
+            ]))
+        );
+        assert_eq!(table.find(&[("hostname", "web-3")]).unwrap(), None);
+        assert!(table.find(&[("host", "web-1")]).is_err());
+        assert!(table.find(&[]).is_err());
+    }
+
+    #[test]
+    fn reads_other_delimiters() {
+        let table = Table::from_reader("a;b\n1;2\n".as_bytes(), b';').unwrap();
+
+        assert_eq!(
+            table.find(&[("b", "2")]).unwrap(),
+            Some(record(&[("a", "1"), ("b", "2")]))
+        );
+    }
+
+    #[test]
+    fn reloads_changed_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("owners.csv");
+        fs::write(&path, OWNERS).unwrap();
+        let config = EnrichmentTableConfig {
+            path: path.clone(),
+            delimiter: ',',
+            reload_interval_secs: 1,
+        };
+
+        let table = EnrichmentTable::load("owners", Content::read(&config).unwrap());
+        assert_eq!(table.table().len(), 3);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"db-2,data,dave\n").unwrap();
+        drop(file);
+        // The modification time may not have changed within the test.
+        *table.modified.lock().unwrap() = None;
+        table.reload_if_changed();
+        assert_eq!(table.table().len(), 4);
+
+        // A broken file leaves the table as it was.
+        fs::write(&path, "hostname,team\nweb-1\n").unwrap();
+        *table.modified.lock().unwrap() = None;
+        table.reload_if_changed();
+        assert_eq!(table.table().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn changes_tables_in_use_on_commit() {
+        let dir = tempdir().unwrap();
+        let owners = dir.path().join("owners.csv");
+        fs::write(&owners, OWNERS).unwrap();
+        let teams = dir.path().join("teams.csv");
+        fs::write(&teams, "team,lead\nweb,alice\n").unwrap();
+        let configs = |path: &PathBuf, reload_interval_secs| {
+            let config = EnrichmentTableConfig {
+                path: path.clone(),
+                delimiter: ',',
+                reload_interval_secs,
+            };
+            vec![("owners".to_owned(), config)]
+                .into_iter()
+                .collect::<IndexMap<_, _>>()
+        };
+
+        let mut registry = Registry::default();
+        registry.stage(&configs(&owners, 10)).unwrap();
+        let table = registry.get("owners").unwrap();
+        registry.commit();
+        assert_eq!(table.table().len(), 3);
+
+        // The table in use only changes once its new configuration is committed.
+        registry.stage(&configs(&teams, 10)).unwrap();
+        assert!(Arc::ptr_eq(&registry.get("owners").unwrap(), &table));
+        assert_eq!(table.table().len(), 3);
+        registry.commit();
+        assert_eq!(table.table().len(), 1);
+
+        // Tables that fail to load, or would be checked for changes all the
+        // time, aren't staged.
+        assert!(registry
+            .stage(&configs(&dir.path().join("missing.csv"), 10))
+            .is_err());
+        assert!(registry.stage(&configs(&owners, 0)).is_err());
+        registry.commit();
+        assert!(Arc::ptr_eq(&registry.get("owners").unwrap(), &table));
+        assert_eq!(table.table().len(), 1);
+    }
+}
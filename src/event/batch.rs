@@ -0,0 +1,94 @@
+//! Batches of events, as passed between the components of a topology.
+//!
+//! Moving events in batches means one channel send and one wakeup for as many
+//! events as are ready at once, instead of one per event.
+
+use super::Event;
+use futures01::{stream::Fuse, Async, Poll, Stream};
+
+/// The most events put in a single batch.
+pub const MAX_BATCH_SIZE: usize = 128;
+
+/// Groups the events of `stream` that can be read without waiting into batches
+/// of at most `MAX_BATCH_SIZE` events.
+pub fn ready_batches<S>(stream: S) -> ReadyBatches<S>
+where
+    S: Stream<Item = Event, Error = ()>,
+{
+    ReadyBatches {
+        inner: stream.fuse(),
+        max_size: MAX_BATCH_SIZE,
+    }
+}
+
+/// Turns a stream of batches back into a stream of events.
+pub fn unbatch<S>(stream: S) -> impl Stream<Item = Event, Error = ()>
+where
+    S: Stream<Item = Vec<Event>, Error = ()>,
+{
+    stream.map(futures01::stream::iter_ok::<_, ()>).flatten()
+}
+
+pub struct ReadyBatches<S> {
+    inner: Fuse<S>,
+    max_size: usize,
+}
+
+impl<S: Stream<Item = Event, Error = ()>> Stream for ReadyBatches<S> {
+    type Item = Vec<Event>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let mut batch = Vec::new();
+        while batch.len() < self.max_size {
+            match self.inner.poll()? {
+                Async::Ready(Some(event)) => batch.push(event),
+                Async::Ready(None) if batch.is_empty() => return Ok(Async::Ready(None)),
+                Async::NotReady if batch.is_empty() => return Ok(Async::NotReady),
+                Async::Ready(None) | Async::NotReady => break,
+            }
+        }
+        Ok(Async::Ready(Some(batch)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures01::{stream, sync::mpsc, Future, Sink};
+
+    fn events(n: usize) -> Vec<Event> {
+        (0..n).map(|i| Event::from(i.to_string())).collect()
+    }
+
+    #[test]
+    fn batches_ready_events() {
+        let batches = ready_batches(stream::iter_ok(events(300)))
+            .collect()
+            .wait()
+            .unwrap();
+
+        let sizes = batches.iter().map(Vec::len).collect::<Vec<_>>();
+        assert_eq!(sizes, vec![128, 128, 44]);
+        assert_eq!(
+            unbatch(stream::iter_ok(batches)).collect().wait().unwrap(),
+            events(300)
+        );
+    }
+
+    #[test]
+    fn doesnt_wait_to_fill_a_batch() {
+        let (tx, rx) = mpsc::channel(10);
+        let tx = tx
+            .send_all(stream::iter_ok::<_, mpsc::SendError<Event>>(events(3)))
+            .wait()
+            .unwrap()
+            .0;
+
+        let mut batches = ready_batches(rx.map_err(|_| ())).wait();
+        assert_eq!(batches.next().unwrap().unwrap().len(), 3);
+
+        drop(tx);
+        assert!(batches.next().is_none());
+    }
+}
@@ -0,0 +1,256 @@
+//! End-to-end acknowledgement of events.
+//!
+//! A source that wants to know when its events have been delivered creates a
+//! `BatchNotifier` and attaches it to every event it emits. Each event then
+//! carries an `EventFinalizer` that is shared by all of its clones, so an event
+//! fanned out to several sinks is only finalized once every copy of it has been
+//! resolved. Once all of the finalizers of a batch are gone, the source receives
+//! the combined `BatchStatus` through its `BatchStatusReceiver`.
+
+use std::{
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+use tokio::sync::oneshot;
+
+/// The delivery status of a single event.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum EventStatus {
+    /// The event was dropped without being handed to a sink, e.g. by a filter.
+    Dropped,
+    /// The event was delivered by the sink.
+    Delivered,
+    /// The sink failed to deliver the event.
+    Errored,
+}
+
+impl EventStatus {
+    /// Combine this status with a newer one. Errors always win, and any real
+    /// outcome replaces the initial `Dropped` state.
+    pub fn update(self, status: Self) -> Self {
+        match (self, status) {
+            (EventStatus::Errored, _) | (_, EventStatus::Errored) => EventStatus::Errored,
+            (EventStatus::Dropped, status) => status,
+            (EventStatus::Delivered, _) => EventStatus::Delivered,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => EventStatus::Dropped,
+            1 => EventStatus::Delivered,
+            _ => EventStatus::Errored,
+        }
+    }
+}
+
+/// The combined delivery status of all the events of a batch.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum BatchStatus {
+    /// Every event of the batch was delivered or intentionally dropped.
+    Delivered,
+    /// At least one event of the batch failed to be delivered.
+    Errored,
+}
+
+impl BatchStatus {
+    fn update(self, status: EventStatus) -> Self {
+        match (self, status) {
+            (BatchStatus::Errored, _) | (_, EventStatus::Errored) => BatchStatus::Errored,
+            _ => BatchStatus::Delivered,
+        }
+    }
+}
+
+/// Collects the status of every event of a batch and reports it to the source
+/// once the last event has been finalized.
+#[derive(Debug)]
+pub struct BatchNotifier {
+    status: Mutex<BatchStatus>,
+    notifier: Mutex<Option<oneshot::Sender<BatchStatus>>>,
+}
+
+impl BatchNotifier {
+    pub fn new_with_receiver() -> (Arc<Self>, BatchStatusReceiver) {
+        let (tx, rx) = oneshot::channel();
+        let notifier = Self {
+            status: Mutex::new(BatchStatus::Delivered),
+            notifier: Mutex::new(Some(tx)),
+        };
+        (Arc::new(notifier), BatchStatusReceiver(rx))
+    }
+
+    /// Convenience for sources where acknowledgements are optional.
+    pub fn maybe_new_with_receiver(
+        enabled: bool,
+    ) -> (Option<Arc<Self>>, Option<BatchStatusReceiver>) {
+        if enabled {
+            let (batch, receiver) = Self::new_with_receiver();
+            (Some(batch), Some(receiver))
+        } else {
+            (None, None)
+        }
+    }
+
+    fn update_status(&self, status: EventStatus) {
+        let mut current = self.status.lock().unwrap();
+        *current = current.update(status);
+    }
+}
+
+impl Drop for BatchNotifier {
+    fn drop(&mut self) {
+        let status = *self.status.lock().unwrap();
+        if let Some(notifier) = self.notifier.lock().unwrap().take() {
+            // The source may have stopped waiting, which is fine.
+            let _ = notifier.send(status);
+        }
+    }
+}
+
+/// Resolves to the status of a batch once all of its events are finalized.
+#[derive(Debug)]
+pub struct BatchStatusReceiver(oneshot::Receiver<BatchStatus>);
+
+impl Future for BatchStatusReceiver {
+    type Output = BatchStatus;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.0).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(status)) => Poll::Ready(status),
+            Poll::Ready(Err(_)) => {
+                error!(message = "Batch notifier was dropped without a status.");
+                Poll::Ready(BatchStatus::Errored)
+            }
+        }
+    }
+}
+
+/// The finalizer of a single event, shared by all of its clones. The batch is
+/// updated with the event's final status when the last clone lets go of it.
+#[derive(Debug)]
+pub struct EventFinalizer {
+    status: AtomicU8,
+    batch: Arc<BatchNotifier>,
+}
+
+impl EventFinalizer {
+    pub fn new(batch: Arc<BatchNotifier>) -> Self {
+        Self {
+            status: AtomicU8::new(EventStatus::Dropped as u8),
+            batch,
+        }
+    }
+
+    pub fn update_status(&self, status: EventStatus) {
+        let _ = self
+            .status
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                Some(EventStatus::from_u8(current).update(status) as u8)
+            });
+    }
+}
+
+impl Drop for EventFinalizer {
+    fn drop(&mut self) {
+        let status = EventStatus::from_u8(self.status.load(Ordering::Acquire));
+        self.batch.update_status(status);
+    }
+}
+
+/// The set of finalizers attached to an event.
+#[derive(Clone, Debug, Default)]
+pub struct EventFinalizers(Vec<Arc<EventFinalizer>>);
+
+impl EventFinalizers {
+    pub fn new(finalizer: EventFinalizer) -> Self {
+        Self(vec![Arc::new(finalizer)])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn add(&mut self, finalizer: EventFinalizer) {
+        self.0.push(Arc::new(finalizer));
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    pub fn take(&mut self) -> Self {
+        mem::take(self)
+    }
+
+    pub fn update_status(&self, status: EventStatus) {
+        for finalizer in &self.0 {
+            finalizer.update_status(status);
+        }
+    }
+}
+
+impl PartialEq for EventFinalizers {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(other.0.iter())
+                .all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::FutureExt;
+
+    #[test]
+    fn defaults_to_delivered_when_dropped() {
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let finalizers = EventFinalizers::new(EventFinalizer::new(batch));
+        drop(finalizers);
+        assert_eq!(receiver.now_or_never(), Some(BatchStatus::Delivered));
+    }
+
+    #[test]
+    fn waits_for_every_clone() {
+        let (batch, mut receiver) = BatchNotifier::new_with_receiver();
+        let finalizers = EventFinalizers::new(EventFinalizer::new(batch));
+        let clone = finalizers.clone();
+
+        finalizers.update_status(EventStatus::Delivered);
+        drop(finalizers);
+        assert_eq!((&mut receiver).now_or_never(), None);
+
+        clone.update_status(EventStatus::Errored);
+        drop(clone);
+        assert_eq!(receiver.now_or_never(), Some(BatchStatus::Errored));
+    }
+
+    #[test]
+    fn errors_win_within_a_batch() {
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let first = EventFinalizers::new(EventFinalizer::new(Arc::clone(&batch)));
+        let second = EventFinalizers::new(EventFinalizer::new(batch));
+
+        first.update_status(EventStatus::Errored);
+        second.update_status(EventStatus::Delivered);
+        drop(first);
+        drop(second);
+        assert_eq!(receiver.now_or_never(), Some(BatchStatus::Errored));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::event::{lookup::Segment, util, Lookup, PathComponent, Value};
+use crate::event::{
+    lookup::Segment, util, EventFinalizer, EventFinalizers, Lookup, PathComponent, Value,
+};
 use serde::{Serialize, Serializer};
 use std::{
     collections::{btree_map::Entry, BTreeMap, HashMap},
@@ -10,6 +12,7 @@ use std::{
 #[derive(PartialEq, Debug, Clone, Default)]
 pub struct LogEvent {
     fields: BTreeMap<String, Value>,
+    finalizers: EventFinalizers,
 }
 
 impl LogEvent {
@@ -92,6 +95,26 @@ impl LogEvent {
         self.fields.is_empty()
     }
 
+    /// Rough size of the fields of the event in bytes.
+    pub fn estimated_size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|(key, value)| key.len() + value.estimated_size())
+            .sum()
+    }
+
+    pub fn add_finalizer(&mut self, finalizer: EventFinalizer) {
+        self.finalizers.add(finalizer);
+    }
+
+    pub fn add_finalizers(&mut self, finalizers: EventFinalizers) {
+        self.finalizers.merge(finalizers);
+    }
+
+    pub fn take_finalizers(&mut self) -> EventFinalizers {
+        self.finalizers.take()
+    }
+
     #[instrument(skip(self, lookup), fields(lookup = %lookup), err)]
     fn entry(&mut self, lookup: Lookup) -> crate::Result<Entry<String, Value>> {
         trace!("Seeking to entry.");
@@ -134,13 +157,16 @@ impl LogEvent {
 
 impl From<BTreeMap<String, Value>> for LogEvent {
     fn from(map: BTreeMap<String, Value>) -> Self {
-        LogEvent { fields: map }
+        LogEvent {
+            fields: map,
+            finalizers: Default::default(),
+        }
     }
 }
 
 impl Into<BTreeMap<String, Value>> for LogEvent {
     fn into(self) -> BTreeMap<String, Value> {
-        let Self { fields } = self;
+        let Self { fields, .. } = self;
         fields
     }
 }
@@ -149,6 +175,7 @@ impl From<HashMap<String, Value>> for LogEvent {
     fn from(map: HashMap<String, Value>) -> Self {
         LogEvent {
             fields: map.into_iter().collect(),
+            finalizers: Default::default(),
         }
     }
 }
@@ -181,7 +208,7 @@ impl TryInto<serde_json::Value> for LogEvent {
     type Error = crate::Error;
 
     fn try_into(self) -> Result<serde_json::Value, Self::Error> {
-        let Self { fields } = self;
+        let Self { fields, .. } = self;
         Ok(serde_json::to_value(fields)?)
     }
 }
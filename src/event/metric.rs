@@ -79,6 +79,23 @@ pub enum StatisticKind {
 }
 
 impl Metric {
+    /// Rough size of the name, tags and value of the metric in bytes.
+    pub fn estimated_size(&self) -> usize {
+        let tags = self.tags.as_ref().map_or(0, |tags| {
+            tags.iter()
+                .map(|(key, value)| key.len() + value.len())
+                .sum()
+        });
+        let value = match &self.value {
+            MetricValue::Counter { .. } | MetricValue::Gauge { .. } => 8,
+            MetricValue::Set { values } => values.iter().map(String::len).sum(),
+            MetricValue::Distribution { values, .. } => values.len() * 12,
+            MetricValue::AggregatedHistogram { buckets, .. } => buckets.len() * 12 + 12,
+            MetricValue::AggregatedSummary { quantiles, .. } => quantiles.len() * 16 + 12,
+        };
+        self.name.len() + tags + value
+    }
+
     /// Create a new Metric from this with all the data but marked as absolute.
     pub fn to_absolute(&self) -> Self {
         Self {
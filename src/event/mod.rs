@@ -2,9 +2,14 @@ use self::proto::{event_wrapper::Event as EventProto, metric::Value as MetricPro
 use crate::config::log_schema;
 use bytes::Bytes;
 use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
+pub mod batch;
 pub mod discriminant;
+pub mod finalization;
 pub mod merge;
 pub mod merge_state;
 pub mod metric;
@@ -14,6 +19,9 @@ mod log_event;
 mod lookup;
 mod value;
 
+pub use finalization::{
+    BatchNotifier, BatchStatus, BatchStatusReceiver, EventFinalizer, EventFinalizers, EventStatus,
+};
 pub use log_event::LogEvent;
 pub use lookup::Lookup;
 pub use metric::{Metric, MetricKind, MetricValue, StatisticKind};
@@ -80,6 +88,47 @@ impl Event {
             _ => panic!("Failed type coercion, {:?} is not a metric", self),
         }
     }
+
+    /// Attach a finalizer for the given batch to this event. Only log events
+    /// carry finalizers; for metrics the finalizer is released right away, so
+    /// they never hold back the batch.
+    pub fn add_batch_notifier(&mut self, batch: Arc<BatchNotifier>) {
+        let finalizer = EventFinalizer::new(batch);
+        if let Event::Log(log) = self {
+            log.add_finalizer(finalizer);
+        }
+    }
+
+    /// Attach a finalizer for the batch, if there is one.
+    pub fn with_batch_notifier_option(mut self, batch: &Option<Arc<BatchNotifier>>) -> Self {
+        if let Some(batch) = batch {
+            self.add_batch_notifier(Arc::clone(batch));
+        }
+        self
+    }
+
+    pub fn add_finalizers(&mut self, finalizers: EventFinalizers) {
+        if let Event::Log(log) = self {
+            log.add_finalizers(finalizers);
+        }
+    }
+
+    /// Remove the finalizers from this event, leaving it with none.
+    pub fn take_finalizers(&mut self) -> EventFinalizers {
+        match self {
+            Event::Log(log) => log.take_finalizers(),
+            Event::Metric(_) => EventFinalizers::default(),
+        }
+    }
+
+    /// Rough size of the data of the event in bytes, for accounting the
+    /// events held in memory.
+    pub fn estimated_size(&self) -> usize {
+        match self {
+            Event::Log(log) => log.estimated_size(),
+            Event::Metric(metric) => metric.estimated_size(),
+        }
+    }
 }
 
 fn timestamp_to_string(timestamp: &DateTime<Utc>) -> String {
@@ -203,6 +203,22 @@ impl TryInto<serde_json::Value> for Value {
 }
 
 impl Value {
+    /// Rough size of the data of the value in bytes, not counting the
+    /// overhead of its in-memory representation.
+    pub fn estimated_size(&self) -> usize {
+        match self {
+            Value::Bytes(bytes) => bytes.len(),
+            Value::Integer(_) | Value::Float(_) | Value::Timestamp(_) => 8,
+            Value::Boolean(_) => 1,
+            Value::Map(map) => map
+                .iter()
+                .map(|(key, value)| key.len() + value.estimated_size())
+                .sum(),
+            Value::Array(arr) => arr.iter().map(Value::estimated_size).sum(),
+            Value::Null => 0,
+        }
+    }
+
     // TODO: return Cow
     pub fn to_string_lossy(&self) -> String {
         match self {
@@ -1,5 +1,5 @@
 use crate::config::{
-    component::ExampleError, GlobalOptions, SinkDescription, SourceDescription,
+    self, component::ExampleError, Format, GlobalOptions, SinkDescription, SourceDescription,
     TransformDescription,
 };
 use colored::*;
@@ -46,6 +46,10 @@ pub struct Opts {
     /// is then up to you to restructure the `inputs` of each component to build
     /// the topology you need.
     expression: String,
+
+    /// The language to write the configuration in.
+    #[structopt(long, default_value = "toml", possible_values = &["toml", "yaml", "json"])]
+    format: Format,
 }
 
 #[derive(Serialize)]
@@ -66,12 +70,29 @@ pub struct TransformOuter {
 
 #[derive(Serialize, Default)]
 pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sources: Option<IndexMap<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub transforms: Option<IndexMap<String, TransformOuter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sinks: Option<IndexMap<String, SinkOuter>>,
 }
 
-fn generate_example(include_globals: bool, expression: &str) -> Result<String, Vec<String>> {
+/// A whole configuration, for the formats that can't be written one section
+/// at a time.
+#[derive(Serialize)]
+struct Document<'a> {
+    #[serde(flatten)]
+    global: Option<&'a GlobalOptions>,
+    #[serde(flatten)]
+    config: &'a Config,
+}
+
+fn generate_example(
+    include_globals: bool,
+    expression: &str,
+    format: Format,
+) -> Result<String, Vec<String>> {
     let components: Vec<Vec<_>> = expression
         .split(|c| c == '|' || c == '/')
         .map(|s| {
@@ -267,6 +288,19 @@ fn generate_example(include_globals: bool, expression: &str) -> Result<String, V
         return Err(errs);
     }
 
+    if format != Format::Toml {
+        let document = Document {
+            global: if include_globals {
+                Some(&globals)
+            } else {
+                None
+            },
+            config: &config,
+        };
+        return config::format::serialize(&document, format)
+            .map_err(|e| vec![format!("failed to marshal config: {}", e)]);
+    }
+
     let mut builder = if include_globals {
         match toml::to_string(&globals) {
             Ok(s) => s,
@@ -317,7 +351,7 @@ fn generate_example(include_globals: bool, expression: &str) -> Result<String, V
 }
 
 pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
-    match generate_example(!opts.fragment, &opts.expression) {
+    match generate_example(!opts.fragment, &opts.expression, opts.format) {
         Ok(s) => {
             println!("{}", s);
             exitcode::OK
@@ -339,7 +373,7 @@ mod tests {
 
         for name in SourceDescription::types() {
             let param = format!("{}//", name);
-            let cfg = generate_example(true, &param).unwrap();
+            let cfg = generate_example(true, &param, Format::Toml).unwrap();
             if let Err(error) = toml::from_str::<crate::config::ConfigBuilder>(&cfg) {
                 errors.push((param, error));
             }
@@ -347,7 +381,7 @@ mod tests {
 
         for name in TransformDescription::types() {
             let param = format!("/{}/", name);
-            let cfg = generate_example(true, &param).unwrap();
+            let cfg = generate_example(true, &param, Format::Toml).unwrap();
             if let Err(error) = toml::from_str::<crate::config::ConfigBuilder>(&cfg) {
                 errors.push((param, error));
             }
@@ -355,7 +389,7 @@ mod tests {
 
         for name in SinkDescription::types() {
             let param = format!("//{}", name);
-            let cfg = generate_example(true, &param).unwrap();
+            let cfg = generate_example(true, &param, Format::Toml).unwrap();
             if let Err(error) = toml::from_str::<crate::config::ConfigBuilder>(&cfg) {
                 errors.push((param, error));
             }
@@ -367,11 +401,46 @@ mod tests {
         assert!(errors.is_empty());
     }
 
+    #[test]
+    fn generate_all_in_other_formats() {
+        let mut errors = Vec::new();
+
+        let params = SourceDescription::types()
+            .into_iter()
+            .map(|name| format!("{}//", name))
+            .chain(
+                TransformDescription::types()
+                    .into_iter()
+                    .map(|name| format!("/{}/", name)),
+            )
+            .chain(
+                SinkDescription::types()
+                    .into_iter()
+                    .map(|name| format!("//{}", name)),
+            )
+            .collect::<Vec<_>>();
+        for format in &[Format::Yaml, Format::Json] {
+            for param in &params {
+                let cfg = generate_example(true, param, *format).unwrap();
+                if let Err(error) =
+                    config::format::deserialize::<crate::config::ConfigBuilder>(&cfg, *format)
+                {
+                    errors.push((param, format, error));
+                }
+            }
+        }
+
+        for (component, format, error) in &errors {
+            println!("{:?} ({:?}) : {:?}", component, format, error);
+        }
+        assert!(errors.is_empty());
+    }
+
     #[cfg(all(feature = "transforms-json_parser", feature = "sinks-console"))]
     #[test]
     fn generate_basic() {
         assert_eq!(
-            generate_example(true, "stdin/json_parser/console"),
+            generate_example(true, "stdin/json_parser/console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sources.source0]
@@ -402,7 +471,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "stdin|json_parser|console"),
+            generate_example(true, "stdin|json_parser|console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sources.source0]
@@ -433,7 +502,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "stdin//console"),
+            generate_example(true, "stdin//console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sources.source0]
@@ -458,7 +527,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "//console"),
+            generate_example(true, "//console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sinks.sink0]
@@ -479,7 +548,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "/add_fields,json_parser,remove_fields"),
+            generate_example(true, "/add_fields,json_parser,remove_fields", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [transforms.transform0]
@@ -504,7 +573,7 @@ type = "remove_fields"
         );
 
         assert_eq!(
-            generate_example(false, "/add_fields,json_parser,remove_fields"),
+            generate_example(false, "/add_fields,json_parser,remove_fields", Format::Toml),
             Ok(r#"
 [transforms.transform0]
 inputs = []
@@ -0,0 +1,395 @@
+//! The `vector graph` subcommand, rendering the topology of a configuration
+//! for Graphviz or Mermaid.
+
+use crate::config::{self, Config, DataType};
+use std::{collections::HashMap, fmt::Write, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Opts {
+    /// Read configuration from one or more files. Wildcard paths are supported.
+    /// If zero files are specified the default config path
+    /// `/etc/vector/vector.toml` will be targeted.
+    #[structopt(name = "config", short, long, env = "VECTOR_CONFIG")]
+    config_paths: Vec<PathBuf>,
+
+    /// The language to render the graph in.
+    #[structopt(long, default_value = "dot", possible_values = &["dot", "mermaid"])]
+    format: Format,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Format {
+    Dot,
+    Mermaid,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Format::Dot),
+            "mermaid" => Ok(Format::Mermaid),
+            s => Err(format!(
+                "{} is not a valid option, expected `dot` or `mermaid`",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Source,
+    Transform,
+    Sink,
+}
+
+#[derive(Debug, PartialEq)]
+struct Node {
+    name: String,
+    kind: Kind,
+    component_type: &'static str,
+    data_type: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Edge {
+    from: String,
+    to: String,
+    /// The named output of `from` the edge leaves from.
+    output: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    /// The transforms that expanded into several others, with their names.
+    expansions: Vec<(String, Vec<String>)>,
+}
+
+pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    let paths = match config::process_paths(&opts.config_paths) {
+        Some(paths) => paths,
+        None => return exitcode::CONFIG,
+    };
+    let config = match config::load_from_paths(&paths, None) {
+        Ok(config) => config,
+        Err(errors) => return crate::cli::handle_config_errors(errors),
+    };
+
+    let graph = Graph::new(&config);
+    match opts.format {
+        Format::Dot => print!("{}", graph.dot()),
+        Format::Mermaid => print!("{}", graph.mermaid()),
+    }
+
+    exitcode::OK
+}
+
+fn data_type(data_type: DataType) -> String {
+    format!("{:?}", data_type)
+}
+
+impl Graph {
+    fn new(config: &Config) -> Self {
+        let mut graph = Graph::default();
+
+        for (name, source) in &config.sources {
+            graph.nodes.push(Node {
+                name: name.clone(),
+                kind: Kind::Source,
+                component_type: source.source_type(),
+                data_type: data_type(source.output_type()),
+            });
+        }
+        for (name, transform) in &config.transforms {
+            graph.nodes.push(Node {
+                name: name.clone(),
+                kind: Kind::Transform,
+                component_type: transform.inner.transform_type(),
+                data_type: format!(
+                    "{} -> {}",
+                    data_type(transform.inner.input_type()),
+                    data_type(transform.inner.output_type())
+                ),
+            });
+            graph.add_edges(config, name, &transform.inputs);
+        }
+        for (name, sink) in &config.sinks {
+            graph.nodes.push(Node {
+                name: name.clone(),
+                kind: Kind::Sink,
+                component_type: sink.inner.sink_type(),
+                data_type: data_type(sink.inner.input_type()),
+            });
+            graph.add_edges(config, name, &sink.inputs);
+        }
+
+        graph.expansions = config
+            .expansions()
+            .iter()
+            .map(|(name, children)| (name.clone(), children.clone()))
+            .collect();
+
+        graph
+    }
+
+    fn add_edges(&mut self, config: &Config, to: &str, inputs: &[String]) {
+        for input in inputs {
+            let is_component =
+                config.sources.contains_key(input) || config.transforms.contains_key(input);
+            let (from, output) = match config::split_named_output(input) {
+                Some((name, output)) if !is_component && is_named_output(config, name, output) => {
+                    (name.to_string(), Some(output.to_string()))
+                }
+                _ => (input.clone(), None),
+            };
+            self.edges.push(Edge {
+                from,
+                to: to.to_string(),
+                output,
+            });
+        }
+    }
+
+    /// The name of the expanded transform a component is part of, if any.
+    fn parents(&self) -> HashMap<&str, &str> {
+        self.expansions
+            .iter()
+            .flat_map(|(parent, children)| {
+                children
+                    .iter()
+                    .map(move |child| (child.as_str(), parent.as_str()))
+            })
+            .collect()
+    }
+
+    fn dot(&self) -> String {
+        let parents = self.parents();
+        let mut out = String::new();
+
+        writeln!(out, "digraph {{").unwrap();
+        for node in self
+            .nodes
+            .iter()
+            .filter(|node| !parents.contains_key(&*node.name))
+        {
+            writeln!(out, "  {}", dot_node(node)).unwrap();
+        }
+        for (i, (parent, children)) in self.expansions.iter().enumerate() {
+            writeln!(out, "  subgraph cluster_{} {{", i).unwrap();
+            writeln!(out, "    label=\"{}\"", escape(parent)).unwrap();
+            for node in self
+                .nodes
+                .iter()
+                .filter(|node| children.contains(&node.name))
+            {
+                writeln!(out, "    {}", dot_node(node)).unwrap();
+            }
+            writeln!(out, "  }}").unwrap();
+        }
+        for edge in &self.edges {
+            write!(
+                out,
+                "  \"{}\" -> \"{}\"",
+                escape(&edge.from),
+                escape(&edge.to)
+            )
+            .unwrap();
+            if let Some(output) = &edge.output {
+                write!(out, " [label=\"{}\"]", escape(output)).unwrap();
+            }
+            writeln!(out).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        out
+    }
+
+    fn mermaid(&self) -> String {
+        let parents = self.parents();
+        // Mermaid identifiers can't hold every character a name can.
+        let ids = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.name.as_str(), format!("n{}", i)))
+            .collect::<HashMap<_, _>>();
+        let id = |name: &str| {
+            ids.get(name)
+                .cloned()
+                .unwrap_or_else(|| format!("\"{}\"", escape(name)))
+        };
+        let mut out = String::new();
+
+        writeln!(out, "graph LR").unwrap();
+        for node in self
+            .nodes
+            .iter()
+            .filter(|node| !parents.contains_key(&*node.name))
+        {
+            writeln!(out, "  {}", mermaid_node(&id(&node.name), node)).unwrap();
+        }
+        for (i, (parent, children)) in self.expansions.iter().enumerate() {
+            writeln!(out, "  subgraph g{} [\"{}\"]", i, escape(parent)).unwrap();
+            for node in self
+                .nodes
+                .iter()
+                .filter(|node| children.contains(&node.name))
+            {
+                writeln!(out, "    {}", mermaid_node(&id(&node.name), node)).unwrap();
+            }
+            writeln!(out, "  end").unwrap();
+        }
+        for edge in &self.edges {
+            match &edge.output {
+                Some(output) => writeln!(
+                    out,
+                    "  {} -->|{}| {}",
+                    id(&edge.from),
+                    escape(output),
+                    id(&edge.to)
+                ),
+                None => writeln!(out, "  {} --> {}", id(&edge.from), id(&edge.to)),
+            }
+            .unwrap();
+        }
+
+        out
+    }
+}
+
+fn is_named_output(config: &Config, name: &str, output: &str) -> bool {
+    let input = config::named_output(name, output);
+    config.named_output_transform(&input).is_some()
+        || config
+            .sinks
+            .get(name)
+            .map_or(false, |sink| sink.dead_letter && output == "dead_letter")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('"', "\\\"")
+}
+
+fn label(node: &Node, separator: &str) -> String {
+    format!(
+        "{}{}{}{}{}",
+        node.name, separator, node.component_type, separator, node.data_type
+    )
+}
+
+fn dot_node(node: &Node) -> String {
+    let shape = match node.kind {
+        Kind::Source => "trapezium",
+        Kind::Transform => "box",
+        Kind::Sink => "invtrapezium",
+    };
+    format!(
+        "\"{}\" [shape={} label=\"{}\"]",
+        escape(&node.name),
+        shape,
+        escape(&label(node, "\\n"))
+    )
+}
+
+fn mermaid_node(id: &str, node: &Node) -> String {
+    let label = label(node, "<br/>").replace('"', "#quot;");
+    match node.kind {
+        Kind::Source => format!("{}[/\"{}\"\\]", id, label),
+        Kind::Transform => format!("{}[\"{}\"]", id, label),
+        Kind::Sink => format!("{}[\\\"{}\"/]", id, label),
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "sources-stdin",
+    feature = "transforms-json_parser",
+    feature = "transforms-swimlanes",
+    feature = "sinks-console"
+))]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph {
+        let config = config::load_from_str(
+            r#"
+            [sources.in]
+              type = "stdin"
+
+            [transforms.parse]
+              type = "json_parser"
+              inputs = ["in"]
+              reroute_errors = true
+
+            [transforms.lanes]
+              type = "swimlanes"
+              inputs = ["parse"]
+
+              [transforms.lanes.lanes.errors]
+                type = "check_fields"
+                "level.eq" = "error"
+
+            [sinks.out]
+              type = "console"
+              inputs = ["lanes.errors", "parse.errors"]
+              encoding = "json"
+            "#,
+            config::Format::Toml,
+        )
+        .unwrap();
+        Graph::new(&config)
+    }
+
+    #[test]
+    fn annotates_nodes_and_edges() {
+        let graph = graph();
+
+        assert!(graph.nodes.contains(&Node {
+            name: "parse".into(),
+            kind: Kind::Transform,
+            component_type: "json_parser",
+            data_type: "Log -> Log".into(),
+        }));
+        assert!(graph.edges.contains(&Edge {
+            from: "parse".into(),
+            to: "out".into(),
+            output: Some("errors".into()),
+        }));
+        // A transform expanded from another is a component of its own.
+        assert!(graph.edges.contains(&Edge {
+            from: "lanes.errors".into(),
+            to: "out".into(),
+            output: None,
+        }));
+        assert_eq!(
+            graph.expansions,
+            vec![("lanes".to_string(), vec!["lanes.errors".to_string()])]
+        );
+    }
+
+    #[test]
+    fn renders_dot() {
+        let dot = graph().dot();
+
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains(r#""in" [shape=trapezium label="in\nstdin\nLog"]"#));
+        assert!(dot.contains("  subgraph cluster_0 {\n    label=\"lanes\"\n"));
+        assert!(dot.contains(r#""parse" -> "out" [label="errors"]"#));
+    }
+
+    #[test]
+    fn renders_mermaid() {
+        let mermaid = graph().mermaid();
+
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains(r#"n0[/"in<br/>stdin<br/>Log"\]"#));
+        assert!(mermaid.contains("  subgraph g0 [\"lanes\"]\n"));
+        assert!(mermaid.contains("-->|errors| n3\n"));
+    }
+}
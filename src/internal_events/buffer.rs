@@ -0,0 +1,53 @@
+use super::InternalEvent;
+use metrics::{counter, gauge};
+
+#[derive(Debug)]
+pub struct BufferUsageReported {
+    pub events: usize,
+    pub byte_size: usize,
+    pub max_events: Option<usize>,
+    pub max_byte_size: Option<usize>,
+    pub discarded: usize,
+}
+
+impl InternalEvent for BufferUsageReported {
+    fn emit_logs(&self) {
+        trace!(
+            message = "Buffer usage.",
+            events = %self.events,
+            byte_size = %self.byte_size,
+            discarded = %self.discarded,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        gauge!("buffer_events", self.events as f64);
+        gauge!("buffer_byte_size", self.byte_size as f64);
+        if let Some(max_events) = self.max_events {
+            gauge!("buffer_max_event_size", max_events as f64);
+        }
+        if let Some(max_byte_size) = self.max_byte_size {
+            gauge!("buffer_max_byte_size", max_byte_size as f64);
+        }
+        counter!("buffer_discarded_events_total", self.discarded as u64);
+    }
+}
+
+#[derive(Debug)]
+pub struct BufferEventsExpired {
+    pub count: usize,
+}
+
+impl InternalEvent for BufferEventsExpired {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Dropped expired events from buffer.",
+            count = %self.count,
+            rate_limit_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("buffer_expired_events_total", self.count as u64);
+    }
+}
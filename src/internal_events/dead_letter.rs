@@ -0,0 +1,42 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct DeadLetterEventSent<'a> {
+    pub sink: &'a str,
+    pub reason: &'a str,
+}
+
+impl InternalEvent for DeadLetterEventSent<'_> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Sent rejected event to the dead-letter output.",
+            sink = %self.sink,
+            reason = %self.reason,
+            rate_limit_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("dead_letter_events_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct DeadLetterEventDropped<'a> {
+    pub sink: &'a str,
+}
+
+impl InternalEvent for DeadLetterEventDropped<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Dead-letter output is full or closed; dropping rejected event.",
+            sink = %self.sink,
+            rate_limit_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("dead_letter_events_dropped_total", 1);
+    }
+}
@@ -0,0 +1,61 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct EnrichKeyFieldMissing<'a> {
+    pub field: &'a str,
+}
+
+impl InternalEvent for EnrichKeyFieldMissing<'_> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Key field does not exist.",
+            field = %self.field,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "field_missing");
+    }
+}
+
+#[derive(Debug)]
+pub struct EnrichRecordNotFound<'a> {
+    pub table: &'a str,
+}
+
+impl InternalEvent for EnrichRecordNotFound<'_> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "No matching record in enrichment table.",
+            table = %self.table,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("enrichment_table_misses_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct EnrichLookupFailed<'a> {
+    pub table: &'a str,
+    pub error: String,
+}
+
+impl InternalEvent for EnrichLookupFailed<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to look up record in enrichment table.",
+            table = %self.table,
+            error = %self.error,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1, "error_type" => "lookup_failed");
+    }
+}
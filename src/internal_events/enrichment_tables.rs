@@ -0,0 +1,44 @@
+use super::InternalEvent;
+use metrics::counter;
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct EnrichmentTableReloaded<'a> {
+    pub table: &'a str,
+    pub rows: usize,
+}
+
+impl InternalEvent for EnrichmentTableReloaded<'_> {
+    fn emit_logs(&self) {
+        info!(
+            message = "Reloaded enrichment table.",
+            table = %self.table,
+            rows = %self.rows,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("enrichment_table_reloads_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct EnrichmentTableReloadFailed<'a, E> {
+    pub table: &'a str,
+    pub error: E,
+}
+
+impl<E: Display> InternalEvent for EnrichmentTableReloadFailed<'_, E> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Failed to reload enrichment table; keeping its previous content.",
+            table = %self.table,
+            error = %self.error,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("enrichment_table_reload_errors_total", 1);
+    }
+}
@@ -0,0 +1,61 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct FanoutOutputDetached<'a> {
+    pub output: &'a str,
+}
+
+impl InternalEvent for FanoutOutputDetached<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Output is full; detaching it until it catches up.",
+            output = %self.output,
+            rate_limit_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("fanout_outputs_detached_total", 1, "output" => self.output.to_string());
+    }
+}
+
+#[derive(Debug)]
+pub struct FanoutOutputReattached<'a> {
+    pub output: &'a str,
+}
+
+impl InternalEvent for FanoutOutputReattached<'_> {
+    fn emit_logs(&self) {
+        info!(
+            message = "Output caught up; attaching it again.",
+            output = %self.output,
+            rate_limit_secs = 10,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct FanoutEventsDropped<'a> {
+    pub output: &'a str,
+    pub count: usize,
+}
+
+impl InternalEvent for FanoutEventsDropped<'_> {
+    fn emit_logs(&self) {
+        debug!(
+            message = "Output is full; dropping events.",
+            output = %self.output,
+            count = %self.count,
+            rate_limit_secs = 10,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "fanout_dropped_events_total",
+            self.count as u64,
+            "output" => self.output.to_string()
+        );
+    }
+}
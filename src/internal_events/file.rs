@@ -31,6 +31,29 @@ impl InternalEvent for FileEventReceived<'_> {
     }
 }
 
+#[derive(Debug)]
+pub struct FileEventNotDelivered<'a> {
+    pub file: &'a str,
+    pub offset: u64,
+}
+
+impl InternalEvent for FileEventNotDelivered<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Event was not delivered by the sinks; no longer checkpointing its file.",
+            file = %self.file,
+            offset = %self.offset,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "events_not_delivered_total", 1,
+            "file" => self.file.to_owned(),
+        );
+    }
+}
+
 #[derive(Debug)]
 pub struct FileChecksumFailed<'a> {
     pub path: &'a Path,
@@ -32,6 +32,28 @@ impl InternalEvent for KafkaOffsetUpdateFailed {
     }
 }
 
+#[derive(Debug)]
+pub struct KafkaEventNotDelivered<'a> {
+    pub topic: &'a str,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+impl InternalEvent for KafkaEventNotDelivered<'_> {
+    fn emit_logs(&self) {
+        error!(
+            message = "Event was not delivered by the sinks; no longer storing the offsets of its partition.",
+            topic = %self.topic,
+            partition = %self.partition,
+            offset = %self.offset,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_not_delivered_total", 1);
+    }
+}
+
 #[derive(Debug)]
 pub struct KafkaEventFailed {
     pub error: rdkafka::error::KafkaError,
@@ -15,17 +15,23 @@ mod aws_kinesis_firehose;
 #[cfg(feature = "sinks-aws_kinesis_streams")]
 mod aws_kinesis_streams;
 mod blackhole;
+mod buffer;
 #[cfg(feature = "transforms-coercer")]
 mod coercer;
 #[cfg(feature = "transforms-concat")]
 mod concat;
 #[cfg(feature = "sinks-console")]
 mod console;
+mod dead_letter;
 #[cfg(feature = "transforms-dedupe")]
 mod dedupe;
 #[cfg(feature = "sources-docker")]
 mod docker;
 mod elasticsearch;
+#[cfg(feature = "transforms-enrich")]
+mod enrich;
+mod enrichment_tables;
+mod fanout;
 #[cfg(feature = "sources-generator")]
 mod generator;
 #[cfg(feature = "transforms-grok_parser")]
@@ -70,6 +76,7 @@ mod rename_fields;
 mod sampler;
 #[cfg(feature = "sinks-sematext")]
 mod sematext_metrics;
+mod service;
 #[cfg(any(
     feature = "sources-socket",
     feature = "sources-syslog",
@@ -92,6 +99,8 @@ mod tag_cardinality_limit;
 mod tcp;
 #[cfg(feature = "transforms-tokenizer")]
 mod tokenizer;
+mod topology;
+mod transform;
 mod udp;
 mod unix;
 mod vector;
@@ -115,17 +124,23 @@ pub use self::aws_kinesis_firehose::*;
 #[cfg(feature = "sinks-aws_kinesis_streams")]
 pub use self::aws_kinesis_streams::*;
 pub use self::blackhole::*;
+pub use self::buffer::*;
 #[cfg(feature = "transforms-coercer")]
 pub(crate) use self::coercer::*;
 #[cfg(feature = "transforms-concat")]
 pub use self::concat::*;
 #[cfg(feature = "sinks-console")]
 pub use self::console::*;
+pub use self::dead_letter::*;
 #[cfg(feature = "transforms-dedupe")]
 pub(crate) use self::dedupe::*;
 #[cfg(feature = "sources-docker")]
 pub use self::docker::*;
 pub use self::elasticsearch::*;
+#[cfg(feature = "transforms-enrich")]
+pub use self::enrich::*;
+pub use self::enrichment_tables::*;
+pub use self::fanout::*;
 #[cfg(any(feature = "sources-file", feature = "sources-kubernetes-logs"))]
 pub use self::file::*;
 #[cfg(feature = "sources-generator")]
@@ -170,6 +185,7 @@ pub use self::rename_fields::*;
 pub use self::sampler::*;
 #[cfg(feature = "sinks-sematext")]
 pub use self::sematext_metrics::*;
+pub use self::service::*;
 #[cfg(feature = "sources-socket")]
 pub(crate) use self::socket::*;
 pub use self::split::*;
@@ -188,6 +204,8 @@ pub(crate) use self::tag_cardinality_limit::*;
 pub use self::tcp::*;
 #[cfg(feature = "transforms-tokenizer")]
 pub(crate) use self::tokenizer::*;
+pub use self::topology::*;
+pub use self::transform::*;
 pub use self::udp::*;
 pub use self::unix::*;
 pub use self::vector::*;
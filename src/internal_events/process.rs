@@ -125,3 +125,48 @@ impl InternalEvent for VectorRecoveryFailed {
 mod built_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
+
+#[derive(Debug)]
+pub struct RemoteConfigFetchFailed<'a> {
+    pub url: &'a http::Uri,
+    pub error: crate::Error,
+}
+
+impl InternalEvent for RemoteConfigFetchFailed<'_> {
+    fn emit_logs(&self) {
+        error!(
+            target: "vector",
+            message = "Failed to fetch the configuration.",
+            url = %self.url,
+            error = %self.error,
+            rate_limit_secs = 30
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("remote_config_fetch_errors_total", 1);
+    }
+}
+
+#[derive(Debug)]
+pub struct RemoteConfigInvalid<'a> {
+    pub url: &'a http::Uri,
+    pub errors: &'a [String],
+}
+
+impl InternalEvent for RemoteConfigInvalid<'_> {
+    fn emit_logs(&self) {
+        for error in self.errors {
+            error!(
+                target: "vector",
+                message = "Fetched configuration is invalid, and isn't applied.",
+                url = %self.url,
+                %error
+            );
+        }
+    }
+
+    fn emit_metrics(&self) {
+        counter!("config_load_errors_total", 1);
+    }
+}
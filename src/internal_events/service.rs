@@ -0,0 +1,19 @@
+use super::InternalEvent;
+use metrics::histogram;
+use std::time::Instant;
+
+#[derive(Debug)]
+pub struct SinkRequestCompleted {
+    pub start: Instant,
+    pub end: Instant,
+}
+
+impl InternalEvent for SinkRequestCompleted {
+    fn emit_logs(&self) {
+        trace!(message = "Request completed.", duration = ?(self.end - self.start));
+    }
+
+    fn emit_metrics(&self) {
+        histogram!("request_duration_nanoseconds", self.end - self.start);
+    }
+}
@@ -0,0 +1,27 @@
+use super::InternalEvent;
+use metrics::counter;
+
+/// Events taken in by a transform or a sink, counted by the topology so that
+/// every component is counted the same way.
+#[derive(Debug)]
+pub struct ComponentEventsReceived {
+    pub count: usize,
+}
+
+impl InternalEvent for ComponentEventsReceived {
+    fn emit_metrics(&self) {
+        counter!("events_in_total", self.count as u64);
+    }
+}
+
+/// Events sent on by a source or a transform.
+#[derive(Debug)]
+pub struct ComponentEventsSent {
+    pub count: usize,
+}
+
+impl InternalEvent for ComponentEventsSent {
+    fn emit_metrics(&self) {
+        counter!("events_out_total", self.count as u64);
+    }
+}
@@ -0,0 +1,23 @@
+use super::InternalEvent;
+use metrics::counter;
+use tokio::task::JoinError;
+
+#[derive(Debug)]
+pub struct TransformTaskFailed {
+    pub error: JoinError,
+}
+
+impl InternalEvent for TransformTaskFailed {
+    fn emit_logs(&self) {
+        error!(
+            message = "Parallel transform task failed.",
+            error = %self.error,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors_total", 1,
+            "error_type" => "task_failed",
+        );
+    }
+}
@@ -31,9 +31,11 @@ pub mod buffers;
 pub mod cli;
 pub mod conditions;
 pub mod dns;
+pub mod enrichment_tables;
 pub mod event;
 pub mod expiring_hash_map;
 pub mod generate;
+pub mod graph;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 #[macro_use]
@@ -52,6 +54,7 @@ pub mod mapping;
 pub mod metrics;
 pub(crate) mod pipeline;
 pub mod region;
+pub mod schema;
 pub mod serde;
 pub mod service;
 pub mod shutdown;
@@ -59,6 +62,8 @@ pub mod signal;
 pub mod sinks;
 pub mod sources;
 pub mod stream;
+#[cfg(feature = "api-client")]
+pub mod tap;
 pub mod template;
 pub mod test_util;
 pub mod tls;
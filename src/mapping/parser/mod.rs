@@ -541,6 +541,78 @@ pub fn parse(input: &str) -> Result<Mapping> {
     }
 }
 
+/// The top level fields of events a mapping reads before writing them, and
+/// those it writes.
+#[derive(Debug, Default, PartialEq)]
+pub struct Fields {
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+}
+
+/// Finds the fields a mapping uses without building it. Fields read through a
+/// coalescing path are allowed to be missing, so they aren't counted as read.
+pub fn fields(input: &str) -> Result<Fields> {
+    let pairs = MappingParser::parse(Rule::mapping, input)
+        .map_err(|err| format!("mapping parse error\n{}", err))?;
+    let mut fields = Fields::default();
+    for pair in pairs {
+        fields_from_pair(pair, &mut fields)?;
+    }
+    Ok(fields)
+}
+
+fn fields_from_pair(pair: Pair<Rule>, fields: &mut Fields) -> Result<()> {
+    match pair.as_rule() {
+        Rule::assignment | Rule::merge => {
+            // The value is read before the target is written.
+            let (target, other) = split_inner_rules_from_pair(pair)?;
+            for pair in other {
+                fields_from_pair(pair, fields)?;
+            }
+            if let Some(field) = first_field_from_pair(target)? {
+                if !fields.writes.contains(&field) {
+                    fields.writes.push(field);
+                }
+            }
+        }
+        Rule::dot_path => {
+            if let Some(field) = first_field_from_pair(pair)? {
+                if !fields.writes.contains(&field) && !fields.reads.contains(&field) {
+                    fields.reads.push(field);
+                }
+            }
+        }
+        // Deleted fields don't need to exist.
+        Rule::target_path => {}
+        _ => {
+            for pair in pair.into_inner() {
+                fields_from_pair(pair, fields)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn first_field_from_pair(pair: Pair<Rule>) -> Result<Option<String>> {
+    let segment = pair.into_inner().next().ok_or(TOKEN_ERR)?;
+    Ok(match segment.as_rule() {
+        Rule::path_segment => Some(
+            segment
+                .into_inner()
+                .next()
+                .ok_or(TOKEN_ERR)?
+                .as_str()
+                .to_owned(),
+        ),
+        Rule::quoted_path_segment => Some(inner_quoted_string_escaped_from_pair(
+            segment.into_inner().next().ok_or(TOKEN_ERR)?,
+        )?),
+        Rule::target_path => first_field_from_pair(segment)?,
+        Rule::path_coalesce => None,
+        _ => unexpected_parser_sytax!(segment),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -551,6 +623,42 @@ mod tests {
         TokenizeFn, TruncateFn, UpcaseFn, UuidV4Fn,
     };
 
+    #[test]
+    fn check_fields() {
+        let cases = vec![
+            (".foo = .bar", vec!["bar"], vec!["foo"]),
+            (".foo = \"x\"\n.bar = .foo", vec![], vec!["foo", "bar"]),
+            (".foo = .foo + 1", vec!["foo"], vec!["foo"]),
+            (
+                ".\"a.b\".c = upcase(.message)\ndel(.gone)",
+                vec!["message"],
+                vec!["a.b"],
+            ),
+            (
+                "if .level == \"error\" {\n.x = .(one | two)\n}",
+                vec!["level"],
+                vec!["x"],
+            ),
+            (
+                "merge(.parsed, parse_json(.message))",
+                vec!["message"],
+                vec!["parsed"],
+            ),
+        ];
+
+        for (mapping, reads, writes) in cases {
+            assert_eq!(
+                fields(mapping),
+                Ok(Fields {
+                    reads: reads.into_iter().map(Into::into).collect(),
+                    writes: writes.into_iter().map(Into::into).collect(),
+                }),
+                "{}",
+                mapping
+            );
+        }
+    }
+
     #[test]
     fn check_parser_errors() {
         let cases = vec![
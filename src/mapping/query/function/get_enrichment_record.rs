@@ -0,0 +1,160 @@
+use super::prelude::*;
+use crate::enrichment_tables::{self, EnrichmentTable};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub(in crate::mapping) struct GetEnrichmentRecordFn {
+    table: Arc<EnrichmentTable>,
+    column: Box<dyn Function>,
+    value: Box<dyn Function>,
+}
+
+impl GetEnrichmentRecordFn {
+    #[cfg(test)]
+    pub(in crate::mapping) fn new(
+        table: Arc<EnrichmentTable>,
+        column: &str,
+        value: Box<dyn Function>,
+    ) -> Self {
+        Self {
+            table,
+            column: Box::new(Literal::from(Value::from(column))),
+            value,
+        }
+    }
+}
+
+impl Function for GetEnrichmentRecordFn {
+    fn execute(&self, ctx: &Event) -> Result<QueryValue> {
+        let column = required_value!(ctx, self.column, Value::Bytes(v) => v);
+        let column = String::from_utf8_lossy(&column);
+        let value = required!(ctx, self.value, QueryValue::Value(v) => v.to_string_lossy());
+
+        self.table
+            .table()
+            .find(&[(column.as_ref(), value.as_str())])?
+            .map(QueryValue::Value)
+            .ok_or_else(|| {
+                format!(
+                    "no record in enrichment table '{}' with {} '{}'",
+                    self.table.name(),
+                    column,
+                    value
+                )
+            })
+    }
+
+    fn parameters() -> &'static [Parameter] {
+        &[
+            Parameter {
+                keyword: "table",
+                accepts: |v| matches!(v, QueryValue::Value(Value::Bytes(_))),
+                required: true,
+            },
+            Parameter {
+                keyword: "column",
+                accepts: |v| matches!(v, QueryValue::Value(Value::Bytes(_))),
+                required: true,
+            },
+            Parameter {
+                keyword: "value",
+                accepts: is_scalar_value,
+                required: true,
+            },
+        ]
+    }
+}
+
+impl TryFrom<ArgumentList> for GetEnrichmentRecordFn {
+    type Error = String;
+
+    fn try_from(mut arguments: ArgumentList) -> Result<Self> {
+        // The table is looked up once, when the mapping is parsed, so its name
+        // can't come from the event.
+        let name = arguments.required("table")?;
+        let name = match name.execute(&Event::new_empty_log()) {
+            Ok(QueryValue::Value(Value::Bytes(name))) => {
+                String::from_utf8_lossy(&name).into_owned()
+            }
+            _ => return Err("enrichment table must be named by a string literal".to_owned()),
+        };
+        let table = enrichment_tables::get(&name)
+            .ok_or_else(|| format!("unknown enrichment table '{}'", name))?;
+        let column = arguments.required("column")?;
+        let value = arguments.required("value")?;
+
+        Ok(Self {
+            table,
+            column,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment_tables::Table;
+    use crate::mapping::query::path::Path;
+
+    #[test]
+    fn get_enrichment_record() {
+        let table = Table::from_reader("hostname,owner\nweb-1,alice\n".as_bytes(), b',').unwrap();
+        let table = Arc::new(EnrichmentTable::new("owners", table));
+        let record = Value::Map(
+            vec![
+                ("hostname".to_owned(), Value::from("web-1")),
+                ("owner".to_owned(), Value::from("alice")),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let cases = vec![
+            (
+                Event::from(""),
+                Ok(record.clone()),
+                GetEnrichmentRecordFn::new(
+                    Arc::clone(&table),
+                    "hostname",
+                    Box::new(Literal::from(Value::from("web-1"))),
+                ),
+            ),
+            (
+                {
+                    let mut event = Event::from("");
+                    event.as_mut_log().insert("host", Value::from("web-1"));
+                    event
+                },
+                Ok(record),
+                GetEnrichmentRecordFn::new(
+                    Arc::clone(&table),
+                    "hostname",
+                    Box::new(Path::from(vec![vec!["host"]])),
+                ),
+            ),
+            (
+                Event::from(""),
+                Err("no record in enrichment table 'owners' with hostname 'web-2'".to_owned()),
+                GetEnrichmentRecordFn::new(
+                    Arc::clone(&table),
+                    "hostname",
+                    Box::new(Literal::from(Value::from("web-2"))),
+                ),
+            ),
+            (
+                Event::from(""),
+                Err("no column named \"host\"".to_owned()),
+                GetEnrichmentRecordFn::new(
+                    Arc::clone(&table),
+                    "host",
+                    Box::new(Literal::from(Value::from("web-1"))),
+                ),
+            ),
+        ];
+
+        for (input_event, exp, query) in cases {
+            assert_eq!(query.execute(&input_event), exp.map(QueryValue::Value));
+        }
+    }
+}
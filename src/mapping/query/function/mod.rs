@@ -159,6 +159,7 @@ build_signatures! {
     ceil => CeilFn,
     parse_syslog => ParseSyslogFn,
     split => SplitFn,
+    get_enrichment_record => GetEnrichmentRecordFn,
 }
 
 /// A parameter definition accepted by a function.
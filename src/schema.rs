@@ -0,0 +1,543 @@
+//! The `vector schema` subcommand, describing the configuration as a JSON
+//! Schema for editors and CI to check configs against.
+//!
+//! The options of the configuration itself and of buffers are described in
+//! full. Those of each component are the fields of its config, their types
+//! being inferred from its example config when it has them. Options the
+//! schema doesn't know of aren't rejected, as configs may have aliases.
+
+use crate::{
+    conditions::ConditionDescription,
+    config::{SinkDescription, SourceDescription, TransformDescription},
+};
+use serde_json::{json, Map, Value};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Opts {}
+
+pub fn cmd(_opts: &Opts) -> exitcode::ExitCode {
+    match serde_json::to_string_pretty(&schema()) {
+        Ok(schema) => {
+            println!("{}", schema);
+            exitcode::OK
+        }
+        Err(error) => {
+            eprintln!("Failed to write the schema: {}", error);
+            exitcode::SOFTWARE
+        }
+    }
+}
+
+pub fn schema() -> Value {
+    let sources = components(SourceDescription::types().into_iter().map(|name| {
+        component(
+            name,
+            SourceDescription::example(name).ok(),
+            SourceDescription::options(name).ok().flatten(),
+            json!({}),
+            &[],
+        )
+    }));
+    let transforms = components(TransformDescription::types().into_iter().map(|name| {
+        let options = json!({
+            "inputs": inputs(),
+            "concurrency": {
+                "description": "Number of instances of a stateless transform processing events in parallel.",
+                "type": "integer",
+                "minimum": 1,
+            },
+            "preserve_order": {
+                "description": "Whether the events processed in parallel keep their order.",
+                "type": "boolean",
+                "default": true,
+            },
+        });
+        component(
+            name,
+            TransformDescription::example(name).ok(),
+            TransformDescription::options(name).ok().flatten(),
+            options,
+            &["inputs"],
+        )
+    }));
+    let sinks = components(SinkDescription::types().into_iter().map(|name| {
+        let options = json!({
+            "inputs": inputs(),
+            "buffer": { "$ref": "#/definitions/buffer" },
+            "healthcheck": { "type": "boolean", "default": true },
+            "dead_letter": {
+                "description": "Exposes the events the sink rejects for good as an output named `<sink>.dead_letter`.",
+                "type": "boolean",
+                "default": false,
+            },
+            "isolation": {
+                "description": "What the components feeding the sink do when it has no room for more events.",
+                "enum": ["none", "skip", "detach"],
+                "default": "none",
+            },
+        });
+        component(
+            name,
+            SinkDescription::example(name).ok(),
+            SinkDescription::options(name).ok().flatten(),
+            options,
+            &["inputs"],
+        )
+    }));
+    let conditions = ConditionDescription::types()
+        .into_iter()
+        .map(|name| {
+            component(
+                name,
+                ConditionDescription::example(name).ok(),
+                ConditionDescription::options(name).ok().flatten(),
+                json!({}),
+                &[],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut properties = json!({
+        "data_dir": {
+            "description": "The directory components keep their state in.",
+            "type": "string",
+            "default": "/var/lib/vector/",
+        },
+        "log_schema": {
+            "description": "The names of the fields Vector gives a special meaning to in log events.",
+            "type": "object",
+            "properties": {
+                "message_key": { "type": "string", "default": "message" },
+                "timestamp_key": { "type": "string", "default": "timestamp" },
+                "host_key": { "type": "string", "default": "host" },
+                "source_type_key": { "type": "string", "default": "source_type" },
+            },
+        },
+        "sources": sources,
+        "transforms": transforms,
+        "sinks": sinks,
+        "templates": templates(),
+        "pipelines": pipelines(),
+        "enrichment_tables": enrichment_tables(),
+        "secret": secret(),
+        "tests": { "type": "array", "items": { "$ref": "#/definitions/test" } },
+    });
+    if cfg!(feature = "api") {
+        properties["api"] = json!({
+            "type": "object",
+            "properties": {
+                "enabled": { "type": "boolean", "default": false },
+                "bind": { "type": "string", "default": "127.0.0.1:8686" },
+                "playground": { "type": "boolean", "default": true },
+                "auth": {
+                    "description": "The credentials requests have to carry to be served, which also allow them mutations.",
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "properties": {
+                                "strategy": { "const": "basic" },
+                                "user": { "type": "string" },
+                                "password": { "type": "string" },
+                            },
+                            "required": ["strategy", "user", "password"],
+                            "additionalProperties": false,
+                        },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "strategy": { "const": "bearer" },
+                                "token": { "type": "string" },
+                            },
+                            "required": ["strategy", "token"],
+                            "additionalProperties": false,
+                        },
+                    ],
+                },
+                "tls": tls(),
+            },
+        });
+    }
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Vector configuration",
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+        "definitions": {
+            "buffer": buffer(),
+            "condition": { "oneOf": conditions },
+            "test": test(),
+        },
+    })
+}
+
+fn templates() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": {
+            "type": "object",
+            "properties": {
+                "params": {
+                    "description": "The parameters every pipeline has to give a value to.",
+                    "type": "array",
+                    "items": { "type": "string" },
+                },
+                "defaults": {
+                    "description": "The values of the parameters pipelines can leave out.",
+                    "type": "object",
+                },
+                "transforms": {
+                    "description": "The transforms of the chain, in order, referencing parameters as `{{ params.<name> }}`.",
+                    "type": "object",
+                    "additionalProperties": { "type": "object", "required": ["type"] },
+                    "minProperties": 1,
+                },
+            },
+            "required": ["transforms"],
+            "additionalProperties": false,
+        },
+    })
+}
+
+fn pipelines() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": {
+            "type": "object",
+            "properties": {
+                "template": { "type": "string" },
+                "inputs": inputs(),
+                "params": { "type": "object" },
+            },
+            "required": ["template", "inputs"],
+            "additionalProperties": false,
+        },
+    })
+}
+
+fn enrichment_tables() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": {
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "delimiter": { "type": "string", "minLength": 1, "maxLength": 1, "default": "," },
+                "reload_interval_secs": { "type": "integer", "minimum": 0, "default": 10 },
+            },
+            "required": ["path"],
+            "additionalProperties": false,
+        },
+    })
+}
+
+fn secret() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": {
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": { "const": "exec" },
+                        "command": {
+                            "type": "array",
+                            "items": { "type": "string" },
+                            "minItems": 1,
+                        },
+                        "timeout_secs": { "type": "integer", "minimum": 0, "default": 5 },
+                    },
+                    "required": ["type", "command"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": { "const": "file" },
+                        "path": { "type": "string" },
+                    },
+                    "required": ["type", "path"],
+                    "additionalProperties": false,
+                },
+            ],
+        },
+    })
+}
+
+fn inputs() -> Value {
+    json!({
+        "description": "The names of the components, or of their named outputs, to take events from.",
+        "type": "array",
+        "items": { "type": "string" },
+    })
+}
+
+/// The schema of a section holding components by name.
+fn components(schemas: impl Iterator<Item = Value>) -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": { "oneOf": schemas.collect::<Vec<_>>() },
+    })
+}
+
+/// The schema of a component of type `name`, made of the `fields` of its
+/// config, typed after its example, along with the `options` every component
+/// of its kind has. Configs that don't declare their fields get the options of
+/// their example.
+fn component(
+    name: &str,
+    example: Option<toml::Value>,
+    fields: Option<&[&str]>,
+    options: Value,
+    required: &[&str],
+) -> Value {
+    let mut properties = Map::new();
+    properties.insert("type".into(), json!({ "const": name }));
+    for field in fields.unwrap_or_default() {
+        properties.insert((*field).to_owned(), json!({}));
+    }
+    if let Some(toml::Value::Table(example)) = example {
+        for (option, value) in example.iter().filter(|(option, _)| *option != "type") {
+            properties.insert(option.clone(), infer(value));
+        }
+    }
+    if let Value::Object(options) = options {
+        properties.extend(options);
+    }
+
+    let required = std::iter::once("type")
+        .chain(required.iter().copied())
+        .collect::<Vec<_>>();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// The schema of an option, inferred from an example of its value.
+fn infer(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::String(value) => json!({ "type": "string", "examples": [value] }),
+        toml::Value::Integer(value) => json!({ "type": "integer", "examples": [value] }),
+        toml::Value::Float(value) => json!({ "type": "number", "examples": [value] }),
+        toml::Value::Boolean(_) => json!({ "type": "boolean" }),
+        toml::Value::Datetime(value) => {
+            json!({ "type": "string", "examples": [value.to_string()] })
+        }
+        toml::Value::Array(values) => match values.first() {
+            Some(value) => json!({ "type": "array", "items": infer(value) }),
+            None => json!({ "type": "array" }),
+        },
+        toml::Value::Table(table) => {
+            let properties = table
+                .iter()
+                .map(|(option, value)| (option.clone(), infer(value)))
+                .collect::<Map<_, _>>();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+fn tls() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "enabled": { "type": "boolean" },
+            "verify_certificate": { "type": "boolean" },
+            "verify_hostname": { "type": "boolean" },
+            "ca_file": { "type": "string" },
+            "crt_file": { "type": "string" },
+            "key_file": { "type": "string" },
+            "key_pass": { "type": "string" },
+        },
+    })
+}
+
+fn buffer() -> Value {
+    let when_full = json!({
+        "description": "What to do with events once the buffer is full.",
+        "enum": ["block", "drop_newest", "overflow"],
+        "default": "block",
+    });
+    let max_age = json!({
+        "description": "Age in seconds past which events are dropped instead of being read.",
+        "type": "integer",
+        "minimum": 0,
+    });
+
+    json!({
+        "oneOf": [
+            {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "memory" },
+                    "max_events": { "type": "integer", "minimum": 1, "default": 500 },
+                    "when_full": when_full,
+                    "max_size": {
+                        "description": "Size of the disk buffer events spill over to when `when_full` is `overflow`.",
+                        "type": "integer",
+                        "minimum": 1,
+                    },
+                    "max_age": max_age,
+                },
+                "required": ["type"],
+            },
+            {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "disk" },
+                    "max_size": { "type": "integer", "minimum": 1 },
+                    "when_full": when_full,
+                    "max_age": max_age,
+                },
+                "required": ["type", "max_size"],
+            },
+        ],
+    })
+}
+
+fn test() -> Value {
+    let input = json!({
+        "type": "object",
+        "properties": {
+            "insert_at": { "type": "string" },
+            "type": { "enum": ["raw", "log", "metric"], "default": "raw" },
+            "value": { "type": "string" },
+            "log_fields": {
+                "type": "object",
+                "additionalProperties": { "type": ["string", "integer", "number", "boolean"] },
+            },
+            "metric": { "type": "object" },
+        },
+        "required": ["insert_at"],
+        "additionalProperties": false,
+    });
+
+    json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "input": input,
+            "inputs": { "type": "array", "items": input },
+            "outputs": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "extract_from": { "type": "string" },
+                        "conditions": {
+                            "type": "array",
+                            "items": {
+                                "anyOf": [
+                                    { "$ref": "#/definitions/condition" },
+                                    { "type": "object" },
+                                    { "type": "string" },
+                                ],
+                            },
+                        },
+                    },
+                    "required": ["extract_from"],
+                    "additionalProperties": false,
+                },
+            },
+            "no_outputs_from": { "type": "array", "items": { "type": "string" } },
+        },
+        "required": ["name"],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_every_component() {
+        let schema = schema();
+        let types = |section: &str| {
+            schema["properties"][section]["additionalProperties"]["oneOf"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|component| {
+                    component["properties"]["type"]["const"]
+                        .as_str()
+                        .unwrap()
+                        .to_owned()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(types("sources"), SourceDescription::types());
+        assert_eq!(types("transforms"), TransformDescription::types());
+        assert_eq!(types("sinks"), SinkDescription::types());
+        assert_eq!(
+            schema["definitions"]["condition"]["oneOf"]
+                .as_array()
+                .unwrap()
+                .len(),
+            ConditionDescription::types().len()
+        );
+    }
+
+    #[cfg(feature = "sources-file")]
+    #[test]
+    fn describes_options_missing_from_examples() {
+        let schema = schema();
+        let file = schema["properties"]["sources"]["additionalProperties"]["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|component| component["properties"]["type"]["const"] == "file")
+            .unwrap();
+
+        assert!(file["properties"]["remove_after"].is_object());
+        assert!(file["properties"]["acknowledgements"].is_object());
+    }
+
+    #[test]
+    fn infers_options_from_examples() {
+        let example = toml::from_str(
+            r#"
+            type = "ignored"
+            include = ["/var/log/*.log"]
+            max_line_bytes = 102400
+            [multiline]
+            mode = "halt_before"
+            "#,
+        )
+        .unwrap();
+
+        let schema = component(
+            "file",
+            Some(example),
+            Some(&["include", "exclude", "max_line_bytes", "multiline"]),
+            json!({ "inputs": inputs() }),
+            &["inputs"],
+        );
+
+        assert_eq!(schema["properties"]["type"], json!({ "const": "file" }));
+        assert_eq!(
+            schema["properties"]["include"],
+            json!({
+                "type": "array",
+                "items": { "type": "string", "examples": ["/var/log/*.log"] },
+            })
+        );
+        assert_eq!(
+            schema["properties"]["max_line_bytes"]["type"],
+            json!("integer")
+        );
+        // Fields missing from the example are described all the same.
+        assert_eq!(schema["properties"]["exclude"], json!({}));
+        assert_eq!(
+            schema["properties"]["multiline"]["properties"]["mode"]["type"],
+            json!("string")
+        );
+        assert_eq!(schema["properties"]["inputs"]["type"], json!("array"));
+        assert_eq!(schema["required"], json!(["type", "inputs"]));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    buffers::Acker,
+    buffers::{dead_letter::Rejection, Acker},
     config::{DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
     event::Event,
     internal_events::ConsoleFieldNotFound,
@@ -134,8 +134,8 @@ struct WriterSink {
 impl StreamSink for WriterSink {
     async fn run(&mut self, mut input: BoxStream<'_, Event>) -> Result<(), ()> {
         while let Some(event) = input.next().await {
-            self.acker.ack(1);
             if let Some(mut buf) = encode_event(event, &self.encoding) {
+                self.acker.ack(1);
                 buf.push('\n');
                 if let Err(error) = self.output.write_all(buf.as_bytes()).await {
                     // Error when writing to stdout/stderr is likely irrecoverable,
@@ -143,6 +143,9 @@ impl StreamSink for WriterSink {
                     error!("Error writing to output: {}. Stopping sink.", error);
                     return Err(());
                 }
+            } else {
+                self.acker
+                    .reject(1, Rejection::new("Failed to encode the event.", 1));
             }
         }
         Ok(())
@@ -7,7 +7,7 @@ use crate::{
     sinks::util::{
         encoding::{EncodingConfigWithDefault, EncodingConfiguration},
         http::{BatchedHttpSink, HttpClient, HttpSink},
-        retries::{RetryAction, RetryLogic},
+        retries::{self, RetryAction, RetryLogic},
         rusoto, BatchConfig, BatchSettings, Buffer, Compression, TowerRequestConfig,
     },
     template::{Template, TemplateError},
@@ -320,6 +320,7 @@ impl RetryLogic for ElasticSearchRetryLogic {
                 let body = String::from_utf8_lossy(response.body());
 
                 if body.contains("\"errors\":true") {
+                    retries::reject_items(get_rejected_items(&body));
                     RetryAction::DontRetry(get_error_reason(&body))
                 } else {
                     RetryAction::Successful
@@ -343,6 +344,26 @@ fn get_error_reason(body: &str) -> String {
     }
 }
 
+/// The positions of the failed items of a bulk response, which match the
+/// positions of the events in the request, with the reason for each.
+fn get_rejected_items(body: &str) -> Vec<(usize, String)> {
+    match serde_json::from_str::<ESResultResponse>(&body) {
+        Err(_) => Vec::new(),
+        Ok(resp) => resp
+            .items
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                item.index.error.map(|error| {
+                    let reason =
+                        format!("error type: {}, reason: {}", error.err_type, error.reason);
+                    (index, reason)
+                })
+            })
+            .collect(),
+    }
+}
+
 impl ElasticSearchCommon {
     pub fn parse_config(config: &ElasticSearchConfig) -> crate::Result<Self> {
         let authorization = match &config.auth {
@@ -557,6 +578,18 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn finds_rejected_items() {
+        let json = r#"{"took":3,"errors":true,"items":[{"index":{"status":201}},{"index":{"status":400,"error":{"type":"mapper_parsing_exception","reason":"failed to parse"}}},{"index":{"status":201}}]}"#;
+        assert_eq!(
+            get_rejected_items(json),
+            vec![(
+                1,
+                "error type: mapper_parsing_exception, reason: failed to parse".to_string()
+            )]
+        );
+    }
+
     #[test]
     fn allows_using_excepted_fields() {
         let config = ElasticSearchConfig {
@@ -1,6 +1,6 @@
 use crate::expiring_hash_map::ExpiringHashMap;
 use crate::{
-    buffers::Acker,
+    buffers::{dead_letter::Rejection, Acker},
     config::{log_schema, DataType, GenerateConfig, SinkConfig, SinkContext, SinkDescription},
     event::Event,
     sinks::util::{
@@ -203,8 +203,10 @@ impl FileSink {
                 event = input.next() => {
                     match event {
                         Some(event) => {
-                            self.process_event(event).await;
-                            self.acker.ack(1);
+                            match self.process_event(event).await {
+                                Ok(()) => self.acker.ack(1),
+                                Err(reason) => self.acker.reject(1, Rejection::new(reason, 1)),
+                            }
                         },
                         None => {
                             // If we got `None` - terminate the processing.
@@ -248,7 +250,8 @@ impl FileSink {
         Ok(())
     }
 
-    async fn process_event(&mut self, event: Event) {
+    /// Writes the event to its file, or returns why it was dropped.
+    async fn process_event(&mut self, event: Event) -> Result<(), String> {
         let path = match self.partition_event(&event) {
             Some(path) => path,
             None => {
@@ -256,7 +259,7 @@ impl FileSink {
                 // file.
                 // This is already logged at `partition_event`, so
                 // here we just skip the event.
-                return;
+                return Err("Keys of the path template do not exist on the event.".into());
             }
         };
 
@@ -275,7 +278,7 @@ impl FileSink {
                     // Maybe other events will work though! Just log
                     // the error and skip this event.
                     error!(message = "Unable to open the file.", ?path, %error);
-                    return;
+                    return Err(format!("Unable to open the file: {}", error));
                 }
             };
 
@@ -288,7 +291,10 @@ impl FileSink {
         trace!(message = "Writing an event to file.", ?path);
         if let Err(error) = write_event_to_file(file, event, &self.encoding).await {
             error!(message = "Failed to write file.", ?path, %error);
+            return Err(format!("Failed to write file: {}", error));
         }
+
+        Ok(())
     }
 }
 
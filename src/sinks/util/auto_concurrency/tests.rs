@@ -414,6 +414,10 @@ async fn run_test(params: TestParams) -> TestResults {
         matches!(metrics.get("auto_concurrency_averaged_rtt").unwrap().value,
                  MetricValue::Distribution { .. })
     );
+    assert!(
+        matches!(metrics.get("request_duration_nanoseconds").unwrap().value,
+                 MetricValue::Distribution { .. })
+    );
     if params.in_flight_limit == InFlightLimit::Auto {
         assert!(
             matches!(metrics.get("auto_concurrency_limit").unwrap().value,
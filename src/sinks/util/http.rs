@@ -3,7 +3,7 @@ use super::{
     sink, Batch, TowerBatchedSink, TowerRequestSettings,
 };
 use crate::{
-    buffers::Acker,
+    buffers::{dead_letter::Rejection, Acker},
     dns::Resolver,
     event::Event,
     tls::{tls_connector_builder, MaybeTlsSettings},
@@ -150,9 +150,15 @@ where
         }
         assert!(self.slot.is_none(), "poll_complete did not clear slot");
 
-        if let Some(item) = self.sink.encode_event(item) {
-            self.slot = Some(item);
-            self.poll_complete()?;
+        match self.sink.encode_event(item) {
+            Some(item) => {
+                self.slot = Some(item);
+                self.poll_complete()?;
+            }
+            // The event still has to be acknowledged, in its place.
+            None => self
+                .inner
+                .skip(Err(Rejection::new("Event couldn't be encoded.", 0))),
         }
 
         Ok(AsyncSink::Ready)
@@ -372,7 +378,7 @@ impl RetryLogic for HttpRetryLogic {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(deny_unknown_fields, rename_all = "snake_case", tag = "strategy")]
 pub enum Auth {
     Basic { user: String, password: String },
@@ -34,7 +34,7 @@ pub use service::{
     InFlightLimit, ServiceBuilderExt, TowerBatchedSink, TowerRequestConfig, TowerRequestLayer,
     TowerRequestSettings,
 };
-pub use sink::{BatchSink, PartitionBatchSink, StreamSink, StreamSinkOld};
+pub use sink::{BatchSink, Flush, PartitionBatchSink, StreamSink, StreamSinkOld};
 pub use uri::UriSerde;
 
 #[derive(Debug, Snafu)]
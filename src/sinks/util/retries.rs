@@ -1,6 +1,7 @@
 use crate::Error;
 use futures::FutureExt;
 use std::{
+    cell::RefCell,
     cmp,
     future::Future,
     pin::Pin,
@@ -19,6 +20,46 @@ pub enum RetryAction {
     Successful,
 }
 
+tokio::task_local! {
+    static REQUEST: RefCell<RequestState>;
+}
+
+/// What the retry policy learned about a request while sending it.
+#[derive(Debug, Default)]
+pub struct RequestState {
+    /// Number of times the request was sent.
+    pub attempts: usize,
+    /// Why the request was given up on, if the response itself looks fine.
+    pub rejection: Option<String>,
+    /// Items of the request that were rejected individually, by their position
+    /// in the request, with the reason.
+    pub rejected_items: Vec<(usize, String)>,
+}
+
+/// Runs `future`, which sends a request through a `FixedRetryPolicy`, and
+/// collects what the policy recorded about it along the way.
+pub async fn track_request<F: Future>(future: F) -> (F::Output, RequestState) {
+    REQUEST
+        .scope(RefCell::new(RequestState::default()), async move {
+            let output = future.await;
+            let mut state = REQUEST.with(|state| state.replace(RequestState::default()));
+            state.attempts = state.attempts.max(1);
+            (output, state)
+        })
+        .await
+}
+
+fn record(f: impl FnOnce(&mut RequestState)) {
+    // Requests sent outside of `track_request` simply aren't tracked.
+    let _ = REQUEST.try_with(|state| f(&mut state.borrow_mut()));
+}
+
+/// Lets a `RetryLogic` report the items of a response that were rejected,
+/// when the request as a whole went through.
+pub fn reject_items(items: Vec<(usize, String)>) {
+    record(|state| state.rejected_items = items);
+}
+
 pub trait RetryLogic: Clone + Send + Sync + 'static {
     type Error: std::error::Error + Send + Sync + 'static;
     type Response;
@@ -94,10 +135,15 @@ where
     type Future = RetryPolicyFuture<L>;
 
     fn retry(&self, _: &Req, result: Result<&Res, &Error>) -> Option<Self::Future> {
+        record(|state| state.attempts += 1);
+
         match result {
             Ok(response) => {
                 if self.remaining_attempts == 0 {
                     error!("Retries exhausted; dropping the request.");
+                    if !self.logic.should_retry_response(response).is_successful() {
+                        record(|state| state.rejection = Some("Retries exhausted.".into()));
+                    }
                     return None;
                 }
 
@@ -109,6 +155,7 @@ where
 
                     RetryAction::DontRetry(reason) => {
                         error!(message = "Not retriable; dropping the request.", %reason);
+                        record(|state| state.rejection = Some(reason));
                         None
                     }
 
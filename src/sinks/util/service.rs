@@ -4,8 +4,8 @@ use super::auto_concurrency::{
 use super::retries::{FixedRetryPolicy, RetryLogic};
 use super::sink::Response;
 use super::{Batch, BatchSink};
-use crate::buffers::Acker;
-use futures::TryFutureExt;
+use crate::{buffers::Acker, internal_events::SinkRequestCompleted};
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
 use serde::{
     de::{self, Unexpected, Visitor},
     Deserialize, Deserializer, Serialize,
@@ -13,7 +13,7 @@ use serde::{
 use std::fmt;
 use std::sync::Arc;
 use std::task::Poll;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tower::{
     layer::{util::Stack, Layer},
     limit::RateLimit,
@@ -23,7 +23,8 @@ use tower::{
     Service, ServiceBuilder,
 };
 
-pub type Svc<S, L> = RateLimit<Retry<FixedRetryPolicy<L>, AutoConcurrencyLimit<Timeout<S>, L>>>;
+pub type Svc<S, L> =
+    RateLimit<Retry<FixedRetryPolicy<L>, AutoConcurrencyLimit<Timeout<Timed<S>>, L>>>;
 pub type TowerBatchedSink<S, B, L, Request> = BatchSink<Svc<S, L>, B, Request>;
 
 pub trait ServiceBuilderExt<L> {
@@ -278,6 +279,7 @@ impl TowerRequestSettings {
                 retry_logic,
             ))
             .timeout(self.timeout)
+            .layer(TimedLayer)
             .service(service)
     }
 }
@@ -311,6 +313,7 @@ where
             )
             .retry(policy)
             .timeout(self.settings.timeout)
+            .layer(TimedLayer)
             .service(inner);
 
         BoxService::new(l)
@@ -372,6 +375,53 @@ impl<S: Clone, R1, R2> Clone for Map<S, R1, R2> {
     }
 }
 
+// === timed ===
+
+/// Records the duration of each request sent by the inner service.
+#[derive(Clone, Copy, Debug)]
+pub struct TimedLayer;
+
+impl<S> Layer<S> for TimedLayer {
+    type Service = Timed<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Timed { inner }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Timed<S> {
+    inner: S,
+}
+
+impl<S, Request> Service<Request> for Timed<S>
+where
+    S: Service<Request>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let start = Instant::now();
+        let response = self.inner.call(req);
+        async move {
+            let result = response.await;
+            emit!(SinkRequestCompleted {
+                start,
+                end: Instant::now(),
+            });
+            result
+        }
+        .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,8 +35,12 @@
 use super::{
     batch::{Batch, PushResult, StatefulBatch},
     buffer::partition::Partition,
+    retries::{self, RequestState},
+};
+use crate::{
+    buffers::{dead_letter::Rejection, Acker},
+    Event,
 };
-use crate::{buffers::Acker, Event};
 use async_trait::async_trait;
 use futures::{
     compat::{Compat, Future01CompatExt},
@@ -44,8 +48,8 @@ use futures::{
     FutureExt, TryFutureExt,
 };
 use futures01::{
-    future::Either, stream::FuturesUnordered, sync::oneshot, try_ready, Async, AsyncSink, Future,
-    Poll, Sink, StartSend, Stream,
+    future::Either, stream::FuturesUnordered, sync::oneshot, task::AtomicTask, try_ready, Async,
+    AsyncSink, Future, Poll, Sink, StartSend, Stream,
 };
 use std::{
     collections::{HashMap, VecDeque},
@@ -53,11 +57,55 @@ use std::{
     fmt,
     hash::Hash,
     marker::PhantomData,
+    mem,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 use tokio::time::{delay_for, Duration};
 use tower::Service;
 use tracing_futures::Instrument;
 
+// === Flush ===
+
+tokio::task_local! {
+    static FLUSH: Flush;
+}
+
+/// Makes the batch sinks of a sink send the batches they hold right away,
+/// rather than once they are full or their linger timeout elapses.
+#[derive(Clone, Debug, Default)]
+pub struct Flush {
+    requested: Arc<AtomicBool>,
+    task: Arc<AtomicTask>,
+}
+
+impl Flush {
+    /// Builds a sink with `future`, so that the batch sinks built along the
+    /// way are flushed by this.
+    pub async fn scope<F: std::future::Future>(self, future: F) -> F::Output {
+        FLUSH.scope(self, future).await
+    }
+
+    pub fn trigger(&self) {
+        self.requested.store(true, Ordering::Release);
+        self.task.notify();
+    }
+
+    fn current() -> Option<Self> {
+        FLUSH.try_with(Clone::clone).ok()
+    }
+
+    /// Whether a flush was requested since the last call, waking the current
+    /// task up on the next one.
+    fn requested(&self) -> bool {
+        self.task.register();
+        self.requested.swap(false, Ordering::AcqRel)
+    }
+}
+
 // === StreamSinkOld ===
 
 const STREAM_SINK_MAX: usize = 10_000;
@@ -159,8 +207,10 @@ pub trait StreamSink {
 pub struct BatchSink<S, B, Request> {
     service: ServiceSink<S, Request>,
     batch: StatefulBatch<B>,
+    positions: Positions,
     timeout: Duration,
     linger: Option<SafeLinger>,
+    flush: Option<Flush>,
     closing: bool,
     service_was_not_ready: bool,
     _pd: PhantomData<Request>,
@@ -180,16 +230,28 @@ where
         Self {
             service,
             batch: batch.into(),
+            positions: Positions::default(),
             timeout,
             linger: None,
+            flush: Flush::current(),
             closing: false,
             service_was_not_ready: false,
             _pd: PhantomData,
         }
     }
 
+    /// Accounts for an event the sink read but didn't add to a batch, e.g. because it couldn't
+    /// be encoded, so that the events after it are still acknowledged as themselves.
+    pub fn skip(&mut self, result: Result<(), Rejection>) {
+        self.service.skip(result);
+    }
+
     fn should_send(&mut self) -> bool {
-        self.closing || self.batch.was_full() || self.linger_elapsed()
+        self.closing || self.batch.was_full() || self.flush_requested() || self.linger_elapsed()
+    }
+
+    fn flush_requested(&self) -> bool {
+        self.flush.as_ref().map_or(false, Flush::requested)
     }
 
     fn linger_elapsed(&mut self) -> bool {
@@ -244,9 +306,11 @@ where
         }
 
         match self.batch.push(item) {
-            PushResult::Ok(false) => Ok(AsyncSink::Ready),
-            PushResult::Ok(true) => {
-                self.poll_complete()?;
+            PushResult::Ok(full) => {
+                self.positions.push(self.service.next_position());
+                if full {
+                    self.poll_complete()?;
+                }
                 Ok(AsyncSink::Ready)
             }
             PushResult::Overflow(item) => self.start_send(item),
@@ -257,6 +321,8 @@ where
         loop {
             if self.batch.is_empty() {
                 trace!("No batches; driving service to completion.");
+                // There's nothing to flush.
+                self.flush_requested();
                 return self.service.poll_complete();
             } else {
                 // We have data to send, so check if we should send it and either attempt the send
@@ -276,8 +342,9 @@ where
 
                     let batch_size = batch.num_items();
                     let request = batch.finish();
+                    let positions = mem::take(&mut self.positions);
 
-                    let fut = self.service.call(request, batch_size).compat();
+                    let fut = self.service.call(request, batch_size, positions).compat();
                     tokio::spawn(fut);
 
                     // Remove the now-sent batch's linger timeout
@@ -375,11 +442,15 @@ pub struct PartitionBatchSink<B, S, K, Request> {
     batch: StatefulBatch<B>,
     service: ServiceSink<S, Request>,
     partitions: HashMap<K, StatefulBatch<B>>,
+    /// The positions of the events of each partition's batch, since batches of different
+    /// partitions interleave the events the sink reads.
+    positions: HashMap<K, Positions>,
     timeout: Duration,
     closing: bool,
-    sending: VecDeque<B>,
+    sending: VecDeque<(B, Positions)>,
     lingers: FuturesUnordered<LingerDelay<K>>,
     linger_handles: HashMap<K, oneshot::Sender<K>>,
+    flush: Option<Flush>,
 }
 
 enum LingerState<K> {
@@ -404,11 +475,13 @@ where
             batch: batch.into(),
             service,
             partitions: HashMap::new(),
+            positions: HashMap::new(),
             timeout,
             closing: false,
             sending: VecDeque::new(),
             lingers: FuturesUnordered::new(),
             linger_handles: HashMap::new(),
+            flush: Flush::current(),
         }
     }
 
@@ -437,14 +510,30 @@ where
         self.lingers.push(Box::new(fut));
     }
 
-    fn poll_send(&mut self, batch: B) -> Poll<(), crate::Error> {
+    /// Records that the next event the sink read went into the batch of `partition`.
+    fn push_position(&mut self, partition: &K) {
+        let position = self.service.next_position();
+        self.positions
+            .entry(partition.clone())
+            .or_default()
+            .push(position);
+    }
+
+    /// Takes the batch of `partition` out, along with the positions of its events.
+    fn remove_partition(&mut self, partition: &K) -> Option<(StatefulBatch<B>, Positions)> {
+        let batch = self.partitions.remove(partition)?;
+        let positions = self.positions.remove(partition).unwrap_or_default();
+        Some((batch, positions))
+    }
+
+    fn poll_send(&mut self, batch: B, positions: Positions) -> Poll<(), crate::Error> {
         if let Async::NotReady = self.service.poll_ready()? {
-            self.sending.push_front(batch);
+            self.sending.push_front((batch, positions));
         } else {
             let batch_size = batch.num_items();
             let batch = batch.finish();
 
-            let fut = self.service.call(batch, batch_size).compat();
+            let fut = self.service.call(batch, batch_size, positions).compat();
             tokio::spawn(fut);
         }
 
@@ -468,6 +557,7 @@ where
                 } else {
                     match batch.push(item) {
                         PushResult::Ok(full) => {
+                            self.push_position(partition);
                             if full {
                                 if let Err(error) = self.poll_complete() {
                                     return FullBatchResult::Result(Err(error));
@@ -536,6 +626,7 @@ where
                     trace!("Adding event to batch.");
                     match batch.push(item) {
                         PushResult::Ok(full) => {
+                            self.push_position(&partition);
                             if full {
                                 self.poll_complete()?;
                             }
@@ -563,7 +654,8 @@ where
             PushResult::Ok(full) => {
                 self.set_linger(partition.clone());
 
-                self.partitions.insert(partition, batch);
+                self.partitions.insert(partition.clone(), batch);
+                self.push_position(&partition);
 
                 if full {
                     self.poll_complete()?;
@@ -577,13 +669,13 @@ where
     fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
         self.service.poll_complete()?;
 
-        while let Some(batch) = self.sending.pop_front() {
-            if self.poll_send(batch)? == Async::Ready(()) {
+        while let Some((batch, positions)) = self.sending.pop_front() {
+            if self.poll_send(batch, positions)? == Async::Ready(()) {
                 break;
             }
         }
 
-        let closing = self.closing;
+        let flushing = self.closing || self.flush.as_ref().map_or(false, Flush::requested);
 
         let mut partitions = Vec::new();
 
@@ -593,7 +685,7 @@ where
                 trace!("Batch linger expired.");
                 self.linger_handles.remove(&partition);
 
-                if let Some(batch) = self.partitions.remove(&partition) {
+                if let Some(batch) = self.remove_partition(&partition) {
                     partitions.push(batch);
                 }
             }
@@ -602,13 +694,13 @@ where
         let ready = self
             .partitions
             .iter()
-            .filter(|(_, b)| closing || b.was_full())
+            .filter(|(_, b)| flushing || b.was_full())
             .map(|(p, _)| p.clone())
             .collect::<Vec<_>>();
 
         let mut ready_batches = Vec::new();
         for partition in ready {
-            if let Some(batch) = self.partitions.remove(&partition) {
+            if let Some(batch) = self.remove_partition(&partition) {
                 if let Some(linger_cancel) = self.linger_handles.remove(&partition) {
                     // XXX: had to remove the expect here, a cancellation should
                     // always be a best effort.
@@ -625,8 +717,8 @@ where
             );
         }
 
-        for batch in ready_batches.into_iter().chain(partitions) {
-            self.poll_send(batch.into_inner())?;
+        for (batch, positions) in ready_batches.into_iter().chain(partitions) {
+            self.poll_send(batch.into_inner(), positions)?;
         }
 
         // If we still have an inflight partition then
@@ -663,13 +755,65 @@ where
 
 // === ServiceSink ===
 
+/// How the events of a request fared, as runs of consecutive events.
+type RequestOutcome = Vec<(usize, Result<(), Rejection>)>;
+
+/// The positions of the events of a batch among the events the sink read, as runs of
+/// consecutive positions. Acknowledgements are positional, so every event read has to be
+/// acknowledged at its own position, whichever batch it ended up in.
+#[derive(Debug, Default)]
+struct Positions(Vec<Range<usize>>);
+
+impl Positions {
+    fn push(&mut self, position: usize) {
+        match self.0.last_mut() {
+            Some(run) if run.end == position => run.end += 1,
+            _ => self.0.push(position..position + 1),
+        }
+    }
+
+    /// Spreads the outcome of a request over the positions of its events. Events the batch
+    /// left out, e.g. merged metrics, share the fate of the last events of the request.
+    fn outcome(self, outcome: RequestOutcome) -> Vec<(Range<usize>, Result<(), Rejection>)> {
+        let last = outcome.last().map_or(Ok(()), |(_, result)| result.clone());
+        let mut results = outcome.into_iter().filter(|(len, _)| *len > 0);
+        let mut current = results.next();
+
+        let mut runs = Vec::new();
+        for mut run in self.0 {
+            while !run.is_empty() {
+                let take = match &mut current {
+                    Some((len, result)) => {
+                        let take = (*len).min(run.len());
+                        runs.push((run.start..run.start + take, result.clone()));
+                        *len -= take;
+                        take
+                    }
+                    None => {
+                        runs.push((run.clone(), last.clone()));
+                        run.len()
+                    }
+                };
+                run.start += take;
+                if matches!(current, Some((0, _))) {
+                    current = results.next();
+                }
+            }
+        }
+        runs
+    }
+}
+
 struct ServiceSink<S, Request> {
     service: S,
-    in_flight: FuturesUnordered<oneshot::Receiver<(usize, usize)>>,
+    in_flight: FuturesUnordered<oneshot::Receiver<Vec<(Range<usize>, Result<(), Rejection>)>>>,
     acker: Acker,
-    seq_head: usize,
-    seq_tail: usize,
-    pending_acks: HashMap<usize, usize>,
+    /// The position of the next event the sink reads.
+    next_position: usize,
+    /// The position of the first event that isn't acknowledged yet.
+    acked_position: usize,
+    /// Outcomes of runs of events, by position, waiting for the events before them.
+    pending_acks: HashMap<usize, (usize, Result<(), Rejection>)>,
     next_request_id: usize,
     _pd: PhantomData<Request>,
 }
@@ -686,8 +830,8 @@ where
             service,
             in_flight: FuturesUnordered::new(),
             acker,
-            seq_head: 0,
-            seq_tail: 0,
+            next_position: 0,
+            acked_position: 0,
             pending_acks: HashMap::new(),
             next_request_id: 0,
             _pd: PhantomData,
@@ -699,14 +843,26 @@ where
         task_compat::poll_03_to_01(p).map_err(Into::into)
     }
 
+    /// Assigns the next event the sink read its position.
+    fn next_position(&mut self) -> usize {
+        let position = self.next_position;
+        self.next_position += 1;
+        position
+    }
+
+    /// Acknowledges an event the sink read without sending it, once the events before it are.
+    fn skip(&mut self, result: Result<(), Rejection>) {
+        let position = self.next_position();
+        self.pending_acks.insert(position, (1, result));
+        self.ack_in_order();
+    }
+
     fn call(
         &mut self,
         req: Request,
         batch_size: usize,
+        positions: Positions,
     ) -> Box<dyn Future<Item = (), Error = ()> + Send + 'static> {
-        let seqno = self.seq_head;
-        self.seq_head += 1;
-
         let (tx, rx) = oneshot::channel();
 
         self.in_flight.push(rx);
@@ -718,28 +874,16 @@ where
             message = "submitting service request.",
             in_flight_requests = self.in_flight.len()
         );
-        let response = Compat::new(Box::pin(self.service.call(req)))
-            .map_err(Into::into)
-            .then(move |result| {
-                match result {
-                    Ok(response) if response.is_successful() => {
-                        trace!(message = "Response successful.", ?response);
-                    }
-                    Ok(response) => {
-                        error!(message = "Response wasn't successful.", ?response);
-                    }
-                    Err(error) => {
-                        error!(
-                            message = "Request failed.",
-                            %error,
-                        );
-                    }
-                }
+        let response = retries::track_request(self.service.call(req)).map(Ok);
+        let response = Compat::new(Box::pin(response))
+            .then(move |result: Result<_, ()>| {
+                let (result, state) = result.unwrap();
+                let outcome = request_outcome(batch_size, result.map_err(Into::into), state);
 
                 // If the rx end is dropped we still completed
                 // the request so this is a weird case that we can
                 // ignore for now.
-                let _ = tx.send((seqno, batch_size));
+                let _ = tx.send(positions.outcome(outcome));
 
                 Ok::<_, ()>(())
             })
@@ -753,21 +897,30 @@ where
             match self.in_flight.poll() {
                 Ok(Async::NotReady) => return Ok(Async::NotReady),
                 Ok(Async::Ready(None)) => return Ok(Async::Ready(())),
-                Ok(Async::Ready(Some((seqno, batch_size)))) => {
-                    self.pending_acks.insert(seqno, batch_size);
-
-                    let mut num_to_ack = 0;
-                    while let Some(ack_size) = self.pending_acks.remove(&self.seq_tail) {
-                        num_to_ack += ack_size;
-                        self.seq_tail += 1
+                Ok(Async::Ready(Some(outcome))) => {
+                    for (run, result) in outcome {
+                        self.pending_acks.insert(run.start, (run.len(), result));
                     }
-                    trace!(message = "acking events.", acking_num = num_to_ack);
-                    self.acker.ack(num_to_ack);
+                    self.ack_in_order();
                 }
                 Err(_) => panic!("ServiceSink service sender dropped"),
             }
         }
     }
+
+    /// Acknowledges the events whose outcome is known, up to the first one whose isn't.
+    fn ack_in_order(&mut self) {
+        let mut num_to_ack = 0;
+        while let Some((ack_size, result)) = self.pending_acks.remove(&self.acked_position) {
+            match result {
+                Ok(()) => self.acker.ack(ack_size),
+                Err(rejection) => self.acker.reject(ack_size, rejection),
+            }
+            num_to_ack += ack_size;
+            self.acked_position += ack_size;
+        }
+        trace!(message = "acking events.", acking_num = num_to_ack);
+    }
 }
 
 impl<S, Request> fmt::Debug for ServiceSink<S, Request>
@@ -778,13 +931,68 @@ where
         f.debug_struct("ServiceSink")
             .field("service", &self.service)
             .field("acker", &self.acker)
-            .field("seq_head", &self.seq_head)
-            .field("seq_tail", &self.seq_tail)
+            .field("next_position", &self.next_position)
+            .field("acked_position", &self.acked_position)
             .field("pending_acks", &self.pending_acks)
             .finish()
     }
 }
 
+fn request_outcome<R: Response>(
+    batch_size: usize,
+    result: Result<R, crate::Error>,
+    state: RequestState,
+) -> RequestOutcome {
+    let rejection = |reason: String| Rejection::new(reason, state.attempts);
+
+    match result {
+        Ok(response) if !state.rejected_items.is_empty() => {
+            error!(
+                message = "Some events were rejected.",
+                rejected = state.rejected_items.len(),
+                ?response
+            );
+
+            let mut items = state.rejected_items.clone();
+            items.sort_by_key(|(index, _)| *index);
+            items.dedup_by_key(|(index, _)| *index);
+
+            let mut outcome = Vec::new();
+            let mut next = 0;
+            for (index, reason) in items.into_iter().filter(|(index, _)| *index < batch_size) {
+                if index > next {
+                    outcome.push((index - next, Ok(())));
+                }
+                outcome.push((1, Err(rejection(reason))));
+                next = index + 1;
+            }
+            if next < batch_size {
+                outcome.push((batch_size - next, Ok(())));
+            }
+            outcome
+        }
+        Ok(response) if response.is_successful() && state.rejection.is_none() => {
+            trace!(message = "Response successful.", ?response);
+            vec![(batch_size, Ok(()))]
+        }
+        Ok(response) => {
+            error!(message = "Response wasn't successful.", ?response);
+            let reason = state
+                .rejection
+                .clone()
+                .unwrap_or_else(|| format!("Response wasn't successful: {:?}", response));
+            vec![(batch_size, Err(rejection(reason)))]
+        }
+        Err(error) => {
+            error!(
+                message = "Request failed.",
+                %error,
+            );
+            vec![(batch_size, Err(rejection(error.to_string())))]
+        }
+    }
+}
+
 // === Response ===
 
 pub trait Response: fmt::Debug {
@@ -800,7 +1008,8 @@ impl<'a> Response for &'a str {}
 mod tests {
     use super::*;
     use crate::{
-        buffers::Acker,
+        buffers::{dead_letter::track_dead_letters, track_finalizers, Acker},
+        event::{BatchNotifier, BatchStatus},
         sinks::util::{buffer::partition::Partition, BatchSettings, EncodedLength, VecBuffer},
     };
     use bytes::Bytes;
@@ -1138,6 +1347,128 @@ mod tests {
         .await;
     }
 
+    #[tokio::test]
+    async fn batch_sink_dead_letters_skipped_events() {
+        run_as_future01(async {
+            let (tx, rx) = futures01::sync::mpsc::channel(10);
+            let input = futures01::stream::iter_ok(vec![
+                Event::from("one"),
+                Event::from("two"),
+                Event::from("three"),
+            ]);
+            let (input, acker) = track_dead_letters(Box::new(input), Acker::Null, "out", tx);
+            assert_eq!(input.collect().wait().unwrap().len(), 3);
+
+            let svc = tower::service_fn(|_req: Vec<usize>| future::ok::<_, Infallible>(()));
+            let batch = BatchSettings::default().bytes(9999).events(10);
+            let mut sink = BatchSink::new(svc, VecBuffer::new(batch.size), TIMEOUT, acker);
+
+            // The second event couldn't be encoded, so it never reaches the batch.
+            assert!(sink.start_send(0).unwrap().is_ready());
+            sink.skip(Err(Rejection::new("Event couldn't be encoded.", 0)));
+            assert!(sink.start_send(2).unwrap().is_ready());
+            drop(sink.flush().compat().await.unwrap());
+
+            let dead = rx.collect().compat().await.unwrap();
+            assert_eq!(dead.len(), 1);
+            let log = dead[0].as_log();
+            assert_eq!(log["message"], "two".into());
+            assert_eq!(
+                log["dead_letter.reason"],
+                "Event couldn't be encoded.".into()
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn partition_batch_sink_acks_events_at_their_positions() {
+        run_as_future01(async {
+            let mut receivers = Vec::new();
+            let events = (0..3)
+                .map(|_| {
+                    let (batch, receiver) = BatchNotifier::new_with_receiver();
+                    receivers.push(receiver);
+                    let mut event = Event::new_empty_log();
+                    event.add_batch_notifier(batch);
+                    event
+                })
+                .collect::<Vec<_>>();
+            let (input, acker) = track_finalizers(Box::new(futures01::stream::iter_ok(events)));
+            assert_eq!(input.collect().wait().unwrap().len(), 3);
+
+            let svc = tower::service_fn(|req: Vec<Partitions>| {
+                if req[0] == Partitions::B {
+                    future::err("bad")
+                } else {
+                    future::ok("good")
+                }
+            });
+            let batch = BatchSettings::default().bytes(9999).events(10);
+            let mut sink = PartitionBatchSink::new(svc, VecBuffer::new(batch.size), TIMEOUT, acker);
+
+            // The batch of A holds the first and last events, which are delivered, while the
+            // one in between is in the failed batch of B.
+            assert!(sink.start_send(Partitions::A).unwrap().is_ready());
+            assert!(sink.start_send(Partitions::B).unwrap().is_ready());
+            assert!(sink.start_send(Partitions::A).unwrap().is_ready());
+            drop(sink.flush().compat().await.unwrap());
+
+            assert_eq!(
+                future::join_all(receivers).await,
+                vec![
+                    BatchStatus::Delivered,
+                    BatchStatus::Errored,
+                    BatchStatus::Delivered
+                ]
+            );
+        })
+        .await;
+    }
+
+    #[test]
+    fn positions_spread_request_outcomes() {
+        let mut positions = Positions::default();
+        for position in &[0, 1, 3, 4, 6] {
+            positions.push(*position);
+        }
+        assert_eq!(positions.0, vec![0..2, 3..5, 6..7]);
+
+        let rejection = Rejection::new("bad", 1);
+        // The batch holds 4 events only, e.g. after merging two of them.
+        let outcome = vec![(1, Ok(())), (2, Err(rejection.clone())), (1, Ok(()))];
+        assert_eq!(
+            positions.outcome(outcome),
+            vec![
+                (0..1, Ok(())),
+                (1..2, Err(rejection.clone())),
+                (3..4, Err(rejection)),
+                (4..5, Ok(())),
+                (6..7, Ok(())),
+            ]
+        );
+    }
+
+    #[test]
+    fn request_outcome_splits_rejected_items() {
+        let state = RequestState {
+            attempts: 2,
+            rejection: Some("some items failed".into()),
+            rejected_items: vec![(3, "bad".into()), (1, "worse".into())],
+        };
+
+        assert_eq!(
+            request_outcome(5, Ok(()), state),
+            vec![
+                (1, Ok(())),
+                (1, Err(Rejection::new("worse", 2))),
+                (1, Ok(())),
+                (1, Err(Rejection::new("bad", 2))),
+                (1, Ok(())),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn service_sink_doesnt_propagate_error() {
         run_as_future01(async {
@@ -1155,10 +1486,19 @@ mod tests {
                 }
             });
             let mut sink = ServiceSink::new(svc, acker);
+            let positions = |sink: &mut ServiceSink<_, _>, num| {
+                let mut positions = Positions::default();
+                for _ in 0..num {
+                    positions.push(sink.next_position());
+                }
+                positions
+            };
 
             // send some initial requests
-            let mut fut1 = sink.call(1, 1);
-            let mut fut2 = sink.call(2, 2);
+            let p = positions(&mut sink, 1);
+            let mut fut1 = sink.call(1, 1, p);
+            let p = positions(&mut sink, 2);
+            let mut fut2 = sink.call(2, 2, p);
 
             assert_eq!(ack_counter.load(Relaxed), 0);
 
@@ -1169,8 +1509,10 @@ mod tests {
             assert_eq!(ack_counter.load(Relaxed), 3);
 
             // send one request that will error and one normal
-            let mut fut3 = sink.call(3, 3); // i will error
-            let mut fut4 = sink.call(4, 4);
+            let p = positions(&mut sink, 3);
+            let mut fut3 = sink.call(3, 3, p); // i will error
+            let p = positions(&mut sink, 4);
+            let mut fut4 = sink.call(4, 4, p);
 
             // make sure they all "worked"
             assert!(fut3.poll().unwrap().is_ready());
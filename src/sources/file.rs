@@ -1,8 +1,8 @@
 use super::util::MultilineConfig;
 use crate::{
     config::{log_schema, DataType, GlobalOptions, SourceConfig, SourceDescription},
-    event::Event,
-    internal_events::{FileEventReceived, FileSourceInternalEventsEmitter},
+    event::{BatchNotifier, BatchStatus, Event},
+    internal_events::{FileEventNotDelivered, FileEventReceived, FileSourceInternalEventsEmitter},
     line_agg::{self, LineAgg},
     shutdown::ShutdownSignal,
     trace::{current_span, Instrument},
@@ -11,7 +11,7 @@ use crate::{
 use bytes::Bytes;
 use file_source::{
     paths_provider::glob::{Glob, MatchOptions},
-    FileServer, Fingerprinter,
+    FileFingerprint, FileServer, Fingerprinter, Line,
 };
 use futures::{
     compat::{Compat, Compat01As03, Compat01As03Sink, Future01CompatExt},
@@ -22,8 +22,10 @@ use futures01::{future, Future, Sink, Stream};
 use regex::bytes::Regex;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
+use std::collections::HashSet;
 use std::convert::TryInto;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::task::spawn_blocking;
 
@@ -78,6 +80,7 @@ pub struct FileConfig {
     pub max_read_bytes: usize,
     pub oldest_first: bool,
     pub remove_after: Option<u64>,
+    pub acknowledgements: bool,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
@@ -134,6 +137,31 @@ impl Default for FileConfig {
             max_read_bytes: 2048,
             oldest_first: false,
             remove_after: None,
+            acknowledgements: false,
+        }
+    }
+}
+
+/// How many lines may wait for their acknowledgement at the same time.
+const MAX_PENDING_ACKNOWLEDGEMENTS: usize = 1000;
+
+/// The files whose checkpoints can still move as acknowledgements come back. Once a line of a
+/// file isn't delivered, the checkpoint of that file stays before it, so that the line is read
+/// again when the source restarts.
+#[derive(Debug, Default)]
+struct Acknowledgements {
+    errored: HashSet<FileFingerprint>,
+}
+
+impl Acknowledgements {
+    /// Records the status of a line, returning whether its file can be checkpointed past it.
+    fn acknowledge(&mut self, file_id: FileFingerprint, status: BatchStatus) -> bool {
+        match status {
+            BatchStatus::Delivered => !self.errored.contains(&file_id),
+            BatchStatus::Errored => {
+                self.errored.insert(file_id);
+                false
+            }
         }
     }
 }
@@ -183,6 +211,10 @@ impl SourceConfig for FileConfig {
     fn source_type(&self) -> &'static str {
         "file"
     }
+
+    fn acknowledgements(&self) -> bool {
+        self.acknowledgements
+    }
 }
 
 pub fn file_source(
@@ -210,8 +242,16 @@ pub fn file_source(
         fingerprinter: config.fingerprint.clone().into(),
         oldest_first: config.oldest_first,
         remove_after: config.remove_after.map(Duration::from_secs),
+        // With acknowledgements the checkpoints only move once the lines are
+        // delivered, further down the stream.
+        acknowledged_checkpoints: if config.acknowledgements {
+            Some(Arc::default())
+        } else {
+            None
+        },
         emitter: FileSourceInternalEventsEmitter,
     };
+    let checkpoints = file_server.acknowledged_checkpoints.clone();
 
     let file_key = config.file_key.clone();
     let host_key = config
@@ -237,13 +277,21 @@ pub fn file_source(
                 futures::future::ready(val.ok())
             });
             let logic = line_agg::Logic::new(config);
+            // An aggregated line keeps the position of its first line, so that its file is
+            // never checkpointed past lines that aren't delivered yet.
+            let rx = rx.map(|line: Line| (line.filename, line.text, (line.file_id, line.offset)));
             Box::new(Compat::new(
-                LineAgg::new(rx.map(|(line, src)| (src, line, ())), logic)
-                    .map(|(src, line, _context)| (line, src))
+                LineAgg::new(rx, logic)
+                    .map(|(filename, text, (file_id, offset))| Line {
+                        text,
+                        filename,
+                        file_id,
+                        offset,
+                    })
                     .map(Ok),
             ))
         };
-        let messages: Box<dyn Stream<Item = (Bytes, String), Error = ()> + Send> =
+        let messages: Box<dyn Stream<Item = Line, Error = ()> + Send> =
             if let Some(ref multiline_config) = multiline_config {
                 wrap_with_line_agg(
                     rx,
@@ -265,16 +313,50 @@ pub fn file_source(
         // logs in the queue.
         let span = current_span();
         let span2 = span.clone();
+        let acknowledged = checkpoints.is_some();
+        let mut acknowledgements = Acknowledgements::default();
         tokio::spawn(
-            messages
-                .map(move |(msg, file): (Bytes, String)| {
-                    let _enter = span2.enter();
-                    create_event(msg, file, &host_key, &hostname, &file_key)
-                })
-                .forward(out.sink_map_err(|e| error!(%e)))
-                .map(|_| ())
-                .compat()
-                .instrument(span),
+            StreamExt::filter_map(Compat01As03::new(messages), |line| {
+                futures::future::ready(line.ok())
+            })
+            .then(move |line: Line| {
+                let out = out.clone();
+                let _enter = span2.enter();
+                let (batch, receiver) = BatchNotifier::maybe_new_with_receiver(acknowledged);
+                let pending = receiver
+                    .map(|receiver| (line.filename.clone(), line.file_id, line.offset, receiver));
+                let event = create_event(line.text, line.filename, &host_key, &hostname, &file_key)
+                    .with_batch_notifier_option(&batch);
+                async move {
+                    out.send(event)
+                        .compat()
+                        .await
+                        .map_err(|error| error!(message = "Error sending to sink.", %error))?;
+                    Ok::<_, ()>(pending)
+                }
+            })
+            .take_while(|result| futures::future::ready(result.is_ok()))
+            .filter_map(|result| futures::future::ready(result.ok().flatten()))
+            // Wait for the acknowledgements in the order the lines were read, and stop
+            // checkpointing a file once one of its lines isn't delivered.
+            .map(|(file, file_id, offset, receiver)| async move {
+                (file, file_id, offset, receiver.await)
+            })
+            .buffered(MAX_PENDING_ACKNOWLEDGEMENTS)
+            .for_each(move |(file, file_id, offset, status)| {
+                if acknowledgements.acknowledge(file_id, status) {
+                    if let Some(checkpoints) = &checkpoints {
+                        checkpoints.update(file_id, offset);
+                    }
+                } else if status == BatchStatus::Errored {
+                    emit!(FileEventNotDelivered {
+                        file: &file,
+                        offset,
+                    });
+                }
+                futures::future::ready(())
+            })
+            .instrument(span),
         );
 
         let span = info_span!("file_server");
@@ -325,7 +407,10 @@ fn create_event(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{config::Config, shutdown::ShutdownSignal, sources::file};
+    use crate::{
+        config::Config, event::EventStatus, shutdown::ShutdownSignal, sources::file,
+        test_util::collect_n,
+    };
     use futures01::Stream;
     use pretty_assertions::assert_eq;
     use std::{
@@ -904,6 +989,64 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn file_acknowledgements_hold_checkpoints() {
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![dir.path().join("*")],
+            acknowledgements: true,
+            ..test_default_file_config(&dir)
+        };
+
+        let path = dir.path().join("file");
+        let mut file = File::create(&path).unwrap();
+        writeln!(&mut file, "first line").unwrap();
+        writeln!(&mut file, "second line").unwrap();
+        writeln!(&mut file, "third line").unwrap();
+        sleep_500_millis().await;
+
+        // The second line isn't delivered, so the file isn't checkpointed past it.
+        {
+            let (trigger_shutdown, shutdown, _) = ShutdownSignal::new_wired();
+
+            let (tx, rx) = Pipeline::new_test();
+            let source = file::file_source(&config, config.data_dir.clone().unwrap(), shutdown, tx);
+            let source = tokio::spawn(source.compat());
+
+            let mut events = collect_n(rx, 3).await.unwrap();
+            for (event, status) in events.iter_mut().zip(&[
+                EventStatus::Delivered,
+                EventStatus::Errored,
+                EventStatus::Delivered,
+            ]) {
+                event.take_finalizers().update_status(*status);
+            }
+            drop(events);
+            sleep_500_millis().await;
+
+            drop(trigger_shutdown);
+            wait_with_timeout(source).await.unwrap();
+        }
+        // Restart server, read the file again from the undelivered line.
+        {
+            let (trigger_shutdown, shutdown, _) = ShutdownSignal::new_wired();
+
+            let (tx, rx) = Pipeline::new_test();
+            let source = file::file_source(&config, config.data_dir.clone().unwrap(), shutdown, tx);
+            tokio::spawn(source.compat());
+
+            sleep_500_millis().await;
+            drop(trigger_shutdown);
+
+            let received = wait_with_timeout(rx.collect().compat()).await;
+            let lines = received
+                .into_iter()
+                .map(|event| event.as_log()[log_schema().message_key()].to_string_lossy())
+                .collect::<Vec<_>>();
+            assert_eq!(lines, vec!["second line", "third line"]);
+        }
+    }
+
     #[tokio::test]
     async fn file_start_position_server_restart_with_file_rotation() {
         let dir = tempdir().unwrap();
@@ -27,6 +27,8 @@ pub struct SimpleHttpConfig {
     headers: Vec<String>,
     tls: Option<TlsConfig>,
     auth: Option<HttpSourceAuthConfig>,
+    #[serde(default)]
+    acknowledgements: bool,
 }
 
 inventory::submit! {
@@ -41,6 +43,7 @@ impl GenerateConfig for SimpleHttpConfig {
             headers: Vec::new(),
             tls: None,
             auth: None,
+            acknowledgements: false,
         })
         .unwrap()
     }
@@ -91,7 +94,15 @@ impl SourceConfig for SimpleHttpConfig {
             encoding: self.encoding,
             headers: self.headers.clone(),
         };
-        source.run(self.address, "", &self.tls, &self.auth, out, shutdown)
+        source.run(
+            self.address,
+            "",
+            &self.tls,
+            &self.auth,
+            self.acknowledgements,
+            out,
+            shutdown,
+        )
     }
 
     fn output_type(&self) -> DataType {
@@ -101,6 +112,10 @@ impl SourceConfig for SimpleHttpConfig {
     fn source_type(&self) -> &'static str {
         "http"
     }
+
+    fn acknowledgements(&self) -> bool {
+        self.acknowledgements
+    }
 }
 
 fn add_headers(
@@ -223,7 +238,7 @@ mod tests {
     use crate::shutdown::ShutdownSignal;
     use crate::{
         config::{log_schema, GlobalOptions, SourceConfig},
-        event::{Event, Value},
+        event::{Event, EventStatus, Value},
         test_util::{collect_n, next_addr, trace_init, wait_for_tcp},
         Pipeline,
     };
@@ -242,6 +257,14 @@ mod tests {
     async fn source(
         encoding: Encoding,
         headers: Vec<String>,
+    ) -> (mpsc::Receiver<Event>, SocketAddr) {
+        source_with(encoding, headers, false).await
+    }
+
+    async fn source_with(
+        encoding: Encoding,
+        headers: Vec<String>,
+        acknowledgements: bool,
     ) -> (mpsc::Receiver<Event>, SocketAddr) {
         let (sender, recv) = Pipeline::new_test();
         let address = next_addr();
@@ -252,6 +275,7 @@ mod tests {
                 headers,
                 tls: None,
                 auth: None,
+                acknowledgements,
             }
             .build(
                 "default",
@@ -319,6 +343,40 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn http_acknowledgements_delivered() {
+        trace_init();
+
+        let (rx, addr) = source_with(Encoding::default(), vec![], true).await;
+
+        let response = tokio::spawn(send(addr, "test body"));
+
+        let mut events = collect_n(rx, 1).await.unwrap();
+        events[0]
+            .take_finalizers()
+            .update_status(EventStatus::Delivered);
+        drop(events);
+
+        assert_eq!(200, response.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn http_acknowledgements_errored() {
+        trace_init();
+
+        let (rx, addr) = source_with(Encoding::default(), vec![], true).await;
+
+        let response = tokio::spawn(send(addr, "test body"));
+
+        let mut events = collect_n(rx, 1).await.unwrap();
+        events[0]
+            .take_finalizers()
+            .update_status(EventStatus::Errored);
+        drop(events);
+
+        assert_eq!(503, response.await.unwrap());
+    }
+
     #[tokio::test]
     async fn http_multiline_text2() {
         trace_init();
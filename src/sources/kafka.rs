@@ -1,7 +1,9 @@
 use crate::{
     config::{log_schema, DataType, GlobalOptions, SourceConfig, SourceDescription},
-    event::{Event, Value},
-    internal_events::{KafkaEventFailed, KafkaEventReceived, KafkaOffsetUpdateFailed},
+    event::{BatchNotifier, BatchStatus, Event, Value},
+    internal_events::{
+        KafkaEventFailed, KafkaEventNotDelivered, KafkaEventReceived, KafkaOffsetUpdateFailed,
+    },
     kafka::KafkaAuthConfig,
     shutdown::ShutdownSignal,
     Pipeline,
@@ -10,7 +12,7 @@ use bytes::Bytes;
 use chrono::{TimeZone, Utc};
 use futures::{
     compat::{Compat, Future01CompatExt},
-    FutureExt, StreamExt,
+    future, FutureExt, StreamExt,
 };
 use futures01::Sink;
 use rdkafka::{
@@ -20,7 +22,10 @@ use rdkafka::{
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -50,6 +55,8 @@ pub struct KafkaSourceConfig {
     librdkafka_options: Option<HashMap<String, String>>,
     #[serde(flatten)]
     auth: KafkaAuthConfig,
+    #[serde(default)]
+    acknowledgements: bool,
 }
 
 fn default_session_timeout_ms() -> u64 {
@@ -72,6 +79,31 @@ fn default_auto_offset_reset() -> String {
     "largest".into() // default in librdkafka
 }
 
+/// How many messages may wait for their acknowledgement at the same time.
+const MAX_PENDING_ACKNOWLEDGEMENTS: usize = 1000;
+
+/// The partitions whose offsets can still be stored as acknowledgements come back. Once a
+/// message of a partition isn't delivered, no later offset of that partition is stored, so that
+/// the message is read again when the consumer restarts.
+#[derive(Debug, Default)]
+struct Acknowledgements {
+    errored: HashSet<(String, i32)>,
+}
+
+impl Acknowledgements {
+    /// Records the status of a message, returning whether its offset can be stored.
+    fn acknowledge(&mut self, topic: &str, partition: i32, status: BatchStatus) -> bool {
+        let key = (topic.to_owned(), partition);
+        match status {
+            BatchStatus::Delivered => !self.errored.contains(&key),
+            BatchStatus::Errored => {
+                self.errored.insert(key);
+                false
+            }
+        }
+    }
+}
+
 inventory::submit! {
     SourceDescription::new::<KafkaSourceConfig>("kafka")
 }
@@ -98,6 +130,10 @@ impl SourceConfig for KafkaSourceConfig {
     fn source_type(&self) -> &'static str {
         "kafka"
     }
+
+    fn acknowledgements(&self) -> bool {
+        self.acknowledgements
+    }
 }
 
 fn kafka_source(
@@ -106,15 +142,19 @@ fn kafka_source(
     out: Pipeline,
 ) -> crate::Result<super::Source> {
     let key_field = config.key_field.clone();
+    let acknowledgements = config.acknowledgements;
     let consumer = Arc::new(create_consumer(config)?);
 
     let fut = async move {
+        let offsets_consumer = Arc::clone(&consumer);
+        let mut acknowledgements = Acknowledgements::default();
         Arc::clone(&consumer)
             .start()
             .take_until(shutdown.clone())
             .then(move |message| {
                 let key_field = key_field.clone();
                 let consumer = Arc::clone(&consumer);
+                let out = out.clone();
 
                 async move {
                     match message {
@@ -162,30 +202,54 @@ fn kafka_source(
                                 }
                             }
 
-                            consumer.store_offset(&msg).map_err(|error| {
-                                emit!(KafkaOffsetUpdateFailed { error });
-                            })?;
+                            // With acknowledgements the offset is only stored once
+                            // the event has been delivered, further down the stream.
+                            let receiver = if acknowledgements {
+                                let (batch, receiver) = BatchNotifier::new_with_receiver();
+                                event.add_batch_notifier(batch);
+                                Some(receiver)
+                            } else {
+                                consumer.store_offset(&msg).map_err(|error| {
+                                    emit!(KafkaOffsetUpdateFailed { error });
+                                })?;
+                                None
+                            };
+
+                            if let Err(e) = out.send(event).compat().await {
+                                error!(message = "Error sending to sink", error = ?e);
+                                return Err(());
+                            }
 
-                            Ok(event)
+                            Ok((msg, receiver))
                         }
                     }
                 }
             })
-            // Try `forward` after removing old futures.
-            // Error: implementation of `futures_core::stream::Stream` is not general enough
-            // .forward(
-            //     out.sink_compat()
-            //         .sink_map_err(|e| error!(message = "Error sending to sink", error = ?e)),
-            // )
-            .for_each(|item| {
-                let out = out.clone();
-                async move {
-                    if let Ok(item) = item {
-                        if let Err(e) = out.send(item).compat().await {
-                            error!(message = "Error sending to sink", error = ?e);
+            // Wait for the acknowledgements in the order the messages were read, and stop
+            // storing the offsets of a partition once one of its messages isn't delivered,
+            // so that offsets never move past an undelivered message.
+            .map(|item| async move {
+                match item {
+                    Ok((msg, Some(receiver))) => Some((msg, receiver.await)),
+                    _ => None,
+                }
+            })
+            .buffered(MAX_PENDING_ACKNOWLEDGEMENTS)
+            .for_each(move |item| {
+                if let Some((msg, status)) = item {
+                    if acknowledgements.acknowledge(msg.topic(), msg.partition(), status) {
+                        if let Err(error) = offsets_consumer.store_offset(&msg) {
+                            emit!(KafkaOffsetUpdateFailed { error });
                         }
+                    } else if status == BatchStatus::Errored {
+                        emit!(KafkaEventNotDelivered {
+                            topic: msg.topic(),
+                            partition: msg.partition(),
+                            offset: msg.offset(),
+                        });
                     }
                 }
+                future::ready(())
             })
             .await;
         Ok(())
@@ -229,8 +293,8 @@ fn create_consumer(config: &KafkaSourceConfig) -> crate::Result<StreamConsumer>
 
 #[cfg(test)]
 mod test {
-    use super::{kafka_source, KafkaSourceConfig};
-    use crate::{shutdown::ShutdownSignal, Pipeline};
+    use super::{kafka_source, Acknowledgements, KafkaSourceConfig};
+    use crate::{event::BatchStatus, shutdown::ShutdownSignal, Pipeline};
 
     #[test]
     fn generate_config() {
@@ -266,6 +330,19 @@ mod test {
         };
         assert!(kafka_source(&config, ShutdownSignal::noop(), Pipeline::new_test().0).is_err());
     }
+
+    #[test]
+    fn offsets_stop_at_undelivered_messages() {
+        let mut acknowledgements = Acknowledgements::default();
+        assert!(acknowledgements.acknowledge("logs", 0, BatchStatus::Delivered));
+        assert!(!acknowledgements.acknowledge("logs", 0, BatchStatus::Errored));
+        // A later message of the same partition is delivered, but storing its offset
+        // would commit past the undelivered one.
+        assert!(!acknowledgements.acknowledge("logs", 0, BatchStatus::Delivered));
+        // Other partitions move on.
+        assert!(acknowledgements.acknowledge("logs", 1, BatchStatus::Delivered));
+        assert!(acknowledgements.acknowledge("metrics", 0, BatchStatus::Delivered));
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
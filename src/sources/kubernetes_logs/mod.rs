@@ -20,7 +20,7 @@ use crate::{
     Pipeline,
 };
 use bytes::Bytes;
-use file_source::{FileServer, FileServerShutdown, Fingerprinter};
+use file_source::{FileServer, FileServerShutdown, Fingerprinter, Line};
 use futures::{future::FutureExt, sink::Sink, stream::StreamExt};
 use k8s_openapi::api::core::v1::Pod;
 use serde::{Deserialize, Serialize};
@@ -263,23 +263,24 @@ impl Source {
             oldest_first: false,
             // We do not remove the log files, `kubelet` is responsible for it.
             remove_after: None,
+            // Checkpoints move as the lines are read.
+            acknowledged_checkpoints: None,
             // The standard emitter.
             emitter: FileSourceInternalEventsEmitter,
         };
 
-        let (file_source_tx, file_source_rx) =
-            futures::channel::mpsc::channel::<(Bytes, String)>(100);
+        let (file_source_tx, file_source_rx) = futures::channel::mpsc::channel::<Line>(100);
 
         let mut parser = parser::build();
         let mut partial_events_merger = partial_events_merger::build(auto_partial_merge);
 
-        let events = file_source_rx.map(move |(bytes, file)| {
+        let events = file_source_rx.map(move |line| {
             emit!(KubernetesLogsEventReceived {
-                file: &file,
-                byte_size: bytes.len(),
+                file: &line.filename,
+                byte_size: line.text.len(),
             });
-            let mut event = create_event(bytes, &file);
-            if annotator.annotate(&mut event, &file).is_none() {
+            let mut event = create_event(line.text, &line.filename);
+            if annotator.annotate(&mut event, &line.filename).is_none() {
                 emit!(KubernetesLogsEventAnnotationFailed { event: &event });
             }
             event
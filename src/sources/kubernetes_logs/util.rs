@@ -1,6 +1,5 @@
-use bytes::Bytes;
 use file_source::{
-    paths_provider::PathsProvider, FileServer, FileServerShutdown, FileSourceInternalEvents,
+    paths_provider::PathsProvider, FileServer, FileServerShutdown, FileSourceInternalEvents, Line,
 };
 use futures::future::{select, Either};
 use futures::{pin_mut, Sink};
@@ -19,8 +18,8 @@ pub async fn run_file_server<PP, E, C, S>(
 where
     PP: PathsProvider + Send + 'static,
     E: FileSourceInternalEvents,
-    C: Sink<(Bytes, String)> + Unpin + Send + 'static,
-    <C as Sink<(Bytes, String)>>::Error: Error + Send,
+    C: Sink<Line> + Unpin + Send + 'static,
+    <C as Sink<Line>>::Error: Error + Send,
     S: Future + Unpin + Send + 'static,
 {
     let span = info_span!("file_server");
@@ -62,7 +62,15 @@ impl SourceConfig for LogplexConfig {
         out: Pipeline,
     ) -> crate::Result<super::Source> {
         let source = LogplexSource::default();
-        source.run(self.address, "events", &self.tls, &self.auth, out, shutdown)
+        source.run(
+            self.address,
+            "events",
+            &self.tls,
+            &self.auth,
+            false,
+            out,
+            shutdown,
+        )
     }
 
     fn output_type(&self) -> DataType {
@@ -1,6 +1,6 @@
 use crate::{
     config::{log_schema, DataType, GlobalOptions, SourceConfig, SourceDescription},
-    event::{Event, LogEvent, Value},
+    event::{BatchNotifier, BatchStatus, BatchStatusReceiver, Event, LogEvent, Value},
     internal_events::{
         SplunkHECEventReceived, SplunkHECRequestBodyInvalid, SplunkHECRequestError,
         SplunkHECRequestReceived,
@@ -41,6 +41,8 @@ pub struct SplunkConfig {
     /// Splunk HEC token
     token: Option<String>,
     tls: Option<TlsConfig>,
+    /// Only respond to requests once their events have been delivered
+    acknowledgements: bool,
 }
 
 inventory::submit! {
@@ -65,6 +67,7 @@ impl Default for SplunkConfig {
             address: default_socket_address(),
             token: None,
             tls: None,
+            acknowledgements: false,
         }
     }
 }
@@ -135,11 +138,16 @@ impl SourceConfig for SplunkConfig {
     fn source_type(&self) -> &'static str {
         "splunk_hec"
     }
+
+    fn acknowledgements(&self) -> bool {
+        self.acknowledgements
+    }
 }
 
 /// Shared data for responding to requests.
 struct SplunkSource {
     credentials: Option<Bytes>,
+    acknowledgements: bool,
 }
 
 impl SplunkSource {
@@ -149,10 +157,12 @@ impl SplunkSource {
                 .token
                 .as_ref()
                 .map(|token| format!("Splunk {}", token).into()),
+            acknowledgements: config.acknowledgements,
         }
     }
 
     fn event_service(&self, out: Pipeline) -> BoxedFilter<(Response,)> {
+        let acknowledgements = self.acknowledgements;
         warp::post()
             .and(path!("event").or(path!("event" / "1.0")))
             .and(self.authorization())
@@ -169,20 +179,25 @@ impl SplunkSource {
                       body: Bytes| {
                     let out = out.clone();
                     async move {
+                        let (batch, receiver) =
+                            BatchNotifier::maybe_new_with_receiver(acknowledgements);
                         // Construct event parser
                         if gzip {
                             EventStream::new(GzDecoder::new(body.reader()), channel, host)
+                                .map(move |event| event.with_batch_notifier_option(&batch))
                                 .forward(out.clone().sink_map_err(|_| ApiError::ServerShutdown))
                                 .map(|_| ())
                                 .compat()
-                                .await
+                                .await?;
                         } else {
                             EventStream::new(body.reader(), channel, host)
+                                .map(move |event| event.with_batch_notifier_option(&batch))
                                 .forward(out.clone().sink_map_err(|_| ApiError::ServerShutdown))
                                 .map(|_| ())
                                 .compat()
-                                .await
+                                .await?;
                         }
+                        wait_for_delivery(receiver).await
                     }
                 },
             )
@@ -191,6 +206,7 @@ impl SplunkSource {
     }
 
     fn raw_service(&self, out: Pipeline) -> BoxedFilter<(Response,)> {
+        let acknowledgements = self.acknowledgements;
         warp::post()
             .and(path!("raw" / "1.0").or(path!("raw")))
             .and(self.authorization())
@@ -212,12 +228,16 @@ impl SplunkSource {
                 move |_, _, channel: String, host: Option<String>, gzip: bool, body: Bytes| {
                     let out = out.clone();
                     async move {
+                        let (batch, receiver) =
+                            BatchNotifier::maybe_new_with_receiver(acknowledgements);
                         // Construct event parser
                         futures01::stream::once(raw_event(body, gzip, channel, host))
+                            .map(move |event| event.with_batch_notifier_option(&batch))
                             .forward(out.clone().sink_map_err(|_| ApiError::ServerShutdown))
                             .map(|_| ())
                             .compat()
-                            .await
+                            .await?;
+                        wait_for_delivery(receiver).await
                     }
                 },
             )
@@ -616,6 +636,18 @@ fn raw_event(
     Ok(event)
 }
 
+/// Waits for the sinks to be done with the events of a request, when the
+/// source was asked to acknowledge them.
+async fn wait_for_delivery(receiver: Option<BatchStatusReceiver>) -> Result<(), Rejection> {
+    match receiver {
+        None => Ok(()),
+        Some(receiver) => match receiver.await {
+            BatchStatus::Delivered => Ok(()),
+            BatchStatus::Errored => Err(ApiError::DeliveryFailed.into()),
+        },
+    }
+}
+
 #[derive(Clone, Copy, Debug, Snafu)]
 pub(crate) enum ApiError {
     MissingAuthorization,
@@ -628,6 +660,7 @@ pub(crate) enum ApiError {
     EmptyEventField { event: usize },
     MissingEventField { event: usize },
     BadRequest,
+    DeliveryFailed,
 }
 
 impl From<ApiError> for Rejection {
@@ -705,6 +738,10 @@ async fn finish_err(rejection: Rejection) -> Result<(Response,), Rejection> {
                 event_error("Event field is required", 12, event)
             }
             ApiError::BadRequest => empty_response(StatusCode::BAD_REQUEST),
+            ApiError::DeliveryFailed => response_json(
+                StatusCode::SERVICE_UNAVAILABLE,
+                splunk_response::SERVER_ERROR.as_ref(),
+            ),
         },))
     } else {
         Err(rejection)
@@ -784,6 +821,7 @@ mod tests {
                 address,
                 token,
                 tls: None,
+                acknowledgements: false,
             }
             .build(
                 "default",
@@ -1,5 +1,5 @@
 use crate::{
-    event::Event,
+    event::{BatchNotifier, BatchStatus, Event},
     internal_events::{HTTPBadRequest, HTTPEventsReceived},
     shutdown::ShutdownSignal,
     tls::{MaybeTlsSettings, TlsConfig},
@@ -115,6 +115,7 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
         path: &'static str,
         tls: &Option<TlsConfig>,
         auth: &Option<HttpSourceAuthConfig>,
+        acknowledgements: bool,
         out: Pipeline,
         shutdown: ShutdownSignal,
     ) -> crate::Result<crate::sources::Source> {
@@ -148,6 +149,15 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
                                 events_count: events.len(),
                                 byte_size: body_size,
                             });
+
+                            let (batch, receiver) =
+                                BatchNotifier::maybe_new_with_receiver(acknowledgements);
+                            let events = events
+                                .into_iter()
+                                .map(|event| event.with_batch_notifier_option(&batch))
+                                .collect::<Vec<_>>();
+                            drop(batch);
+
                             out.send_all(futures01::stream::iter_ok(events))
                                 .compat()
                                 .map_err(move |e: futures01::sync::mpsc::SendError<Event>| {
@@ -157,8 +167,21 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
                                     error!("Tried to send the following event: {:?}", e);
                                     warp::reject::custom(RejectShuttingDown)
                                 })
-                                .map_ok(|_| warp::reply())
-                                .await
+                                .await?;
+
+                            // Only answer once the sinks are done with the events.
+                            match receiver {
+                                None => Ok(warp::reply()),
+                                Some(receiver) => match receiver.await {
+                                    BatchStatus::Delivered => Ok(warp::reply()),
+                                    BatchStatus::Errored => {
+                                        Err(warp::reject::custom(ErrorMessage::new(
+                                            StatusCode::SERVICE_UNAVAILABLE,
+                                            "Events could not be delivered".to_owned(),
+                                        )))
+                                    }
+                                },
+                            }
                         }
                         Err(err) => {
                             emit!(HTTPBadRequest {
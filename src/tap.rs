@@ -0,0 +1,102 @@
+//! The `vector tap` subcommand, printing sampled copies of the events leaving
+//! components of a running Vector instance, as JSON lines.
+
+use crate::{
+    config,
+    top::{AuthOpts, TlsOpts},
+};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use structopt::StructOpt;
+use url::Url;
+use vector_api_client::{connect_subscription_client_with_tls, gql::TapSubscriptionExt};
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Opts {
+    /// Glob patterns of the names of the components to tap
+    #[structopt(default_value = "*")]
+    patterns: Vec<String>,
+
+    /// URL of the GraphQL endpoint of the API
+    #[structopt(short, long)]
+    url: Option<Url>,
+
+    /// How often events are sent back (in milliseconds)
+    #[structopt(default_value = "500", short = "i", long)]
+    interval: u32,
+
+    /// Most events sent back every interval, the others being dropped
+    #[structopt(default_value = "100", short = "l", long)]
+    limit: u32,
+
+    #[structopt(flatten)]
+    auth: AuthOpts,
+
+    #[structopt(flatten)]
+    tls: TlsOpts,
+}
+
+pub async fn cmd(opts: &Opts) -> exitcode::ExitCode {
+    let mut url = opts.url.clone().unwrap_or_else(|| {
+        let addr = config::api::default_bind().unwrap();
+        Url::parse(&*format!("http://{}/graphql", addr)).unwrap()
+    });
+    // Subscriptions are served over WebSockets, at the same path.
+    let scheme = match url.scheme() {
+        "https" | "wss" => "wss",
+        _ => "ws",
+    };
+    if url.set_scheme(scheme).is_err() {
+        eprintln!("Invalid API URL: {}", url);
+        return exitcode::USAGE;
+    }
+
+    let auth = opts.auth.auth();
+    let client = connect_subscription_client_with_tls(&url, auth.as_ref(), &opts.tls.tls()).await;
+    let client = match client {
+        Ok(client) => client,
+        Err(_) => {
+            eprintln!("Vector API server not reachable");
+            return exitcode::UNAVAILABLE;
+        }
+    };
+    let subscription = match client
+        .tap_subscription(
+            opts.patterns.clone(),
+            opts.interval as i64,
+            opts.limit as i64,
+        )
+        .await
+    {
+        Ok(subscription) => subscription,
+        Err(error) => {
+            eprintln!("Couldn't tap the components: {}", error);
+            return exitcode::UNAVAILABLE;
+        }
+    };
+
+    let mut events = subscription.stream();
+    while let Some(response) = events.next().await {
+        let response = match response {
+            Some(response) => response,
+            None => continue,
+        };
+        if let Some(errors) = response.errors {
+            for error in errors {
+                eprintln!("{}", error.message);
+            }
+            return exitcode::UNAVAILABLE;
+        }
+        if let Some(data) = response.data {
+            let event = serde_json::from_str::<Value>(&data.tap.json)
+                .unwrap_or_else(|_| Value::String(data.tap.json));
+            println!(
+                "{}",
+                json!({ "component_name": data.tap.component_name, "event": event })
+            );
+        }
+    }
+
+    exitcode::OK
+}
@@ -229,7 +229,7 @@ pub async fn collect_n<T>(rx: mpsc::Receiver<T>, n: usize) -> Result<Vec<T>, ()>
 
 pub async fn collect_ready<S>(rx: S) -> Result<Vec<S::Item>, ()>
 where
-    S: Stream01<Item = Event, Error = ()>,
+    S: Stream01<Error = ()>,
 {
     let mut rx = rx.compat();
 
@@ -38,7 +38,12 @@ impl TlsSettings {
 impl MaybeTlsSettings {
     pub(crate) async fn bind(&self, addr: &SocketAddr) -> crate::tls::Result<MaybeTlsListener> {
         let listener = TcpListener::bind(addr).await.context(TcpBind)?;
+        self.listen(listener)
+    }
 
+    /// Accepts the connections of a `listener` that's already bound, for servers that have to
+    /// be listening by the time they're started.
+    pub(crate) fn listen(&self, listener: TcpListener) -> crate::tls::Result<MaybeTlsListener> {
         let acceptor = match self {
             Self::Tls(tls) => Some(tls.acceptor()?),
             Self::Raw(()) => None,
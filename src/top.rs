@@ -1,10 +1,14 @@
 use crate::config;
 use prettytable::{format, Table};
+use std::path::PathBuf;
 use structopt::StructOpt;
 use url::Url;
 use vector_api_client::{
-    gql::{HealthQueryExt, TopologyQueryExt},
-    Client,
+    gql::{
+        topology_query::{TopologyQueryTopologyOn, TopologyQueryTopologyOnSinkBuffer},
+        HealthQueryExt, TopologyQueryExt,
+    },
+    Auth, Client, Tls,
 };
 
 trait StatsWriter {
@@ -69,6 +73,76 @@ pub struct Opts {
 
     #[structopt(short, long)]
     human: bool,
+
+    #[structopt(flatten)]
+    auth: AuthOpts,
+
+    #[structopt(flatten)]
+    tls: TlsOpts,
+}
+
+/// Credentials for an API that requires auth
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct AuthOpts {
+    /// Token for the bearer auth of the API
+    #[structopt(
+        long,
+        env = "VECTOR_API_TOKEN",
+        hide_env_values = true,
+        conflicts_with = "user"
+    )]
+    token: Option<String>,
+
+    /// User for the basic auth of the API
+    #[structopt(long, env = "VECTOR_API_USER", requires = "password")]
+    user: Option<String>,
+
+    /// Password for the basic auth of the API
+    #[structopt(
+        long,
+        env = "VECTOR_API_PASSWORD",
+        hide_env_values = true,
+        requires = "user"
+    )]
+    password: Option<String>,
+}
+
+impl AuthOpts {
+    pub fn auth(&self) -> Option<Auth> {
+        match (&self.token, &self.user, &self.password) {
+            (Some(token), _, _) => Some(Auth::Bearer {
+                token: token.clone(),
+            }),
+            (None, Some(user), Some(password)) => Some(Auth::Basic {
+                user: user.clone(),
+                password: password.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// TLS settings for an API served with a certificate that the system doesn't trust
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct TlsOpts {
+    /// CA certificate, in PEM format, to verify the certificate of the API with
+    #[structopt(long, parse(from_os_str))]
+    ca_file: Option<PathBuf>,
+
+    /// Don't verify the certificate of the API
+    #[structopt(long)]
+    insecure: bool,
+}
+
+impl TlsOpts {
+    pub fn tls(&self) -> Tls {
+        Tls {
+            ca_file: self.ca_file.clone(),
+            insecure: self.insecure,
+        }
+    }
 }
 
 async fn print_topology(client: &Client, mut formatter: Box<dyn StatsWriter>) -> Result<(), ()> {
@@ -76,16 +150,22 @@ async fn print_topology(client: &Client, mut formatter: Box<dyn StatsWriter>) ->
 
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
-    table.set_titles(row!("NAME", "TYPE", r->"EVENTS"));
+    table.set_titles(row!("NAME", "TYPE", r->"EVENTS", r->"ERRORS", r->"BUFFERED"));
 
     for data in res.data.unwrap().topology {
+        let buffered = match &data.on {
+            TopologyQueryTopologyOn::Sink(sink) => buffered(&sink.buffer, formatter.as_mut()),
+            _ => String::new(),
+        };
         table.add_row(row!(
             data.name,
             data.on.to_string(),
             r->formatter.kb(data
                 .events_processed_total
                 .map(|ep| ep.events_processed_total)
-                .unwrap_or(0.00))));
+                .unwrap_or(0.00)),
+            r->formatter.kb(data.errors_total.errors_total),
+            r->buffered));
     }
 
     table.printstd();
@@ -93,13 +173,39 @@ async fn print_topology(client: &Client, mut formatter: Box<dyn StatsWriter>) ->
     Ok(())
 }
 
+/// How full a sink's buffer is, along with a percentage of its maximum size if
+/// it has one.
+fn buffered(buffer: &TopologyQueryTopologyOnSinkBuffer, formatter: &mut dyn StatsWriter) -> String {
+    let events = match buffer.events {
+        Some(events) => events,
+        None => return "-".to_string(),
+    };
+    let full = match (
+        buffer.max_event_size,
+        buffer.max_byte_size,
+        buffer.byte_size,
+    ) {
+        (Some(max), _, _) if max > 0.0 => Some(events / max),
+        (_, Some(max), Some(bytes)) if max > 0.0 => Some(bytes / max),
+        _ => None,
+    };
+    match full {
+        Some(full) => format!("{} ({:.0}%)", formatter.kb(events), full * 100.0),
+        None => formatter.kb(events),
+    }
+}
+
 pub async fn cmd(opts: &Opts) -> exitcode::ExitCode {
     let url = opts.url.clone().unwrap_or_else(|| {
         let addr = config::api::default_bind().unwrap();
         Url::parse(&*format!("http://{}/graphql", addr)).unwrap()
     });
 
-    let client = Client::new(url);
+    let client = Client::new(url).with_tls(opts.tls.tls());
+    let client = match opts.auth.auth() {
+        Some(auth) => client.with_auth(auth),
+        None => client,
+    };
 
     // Check that the GraphQL server is reachable
     match client.health_query().await {
@@ -1,31 +1,39 @@
 use super::{
-    fanout::{self, Fanout},
+    fanout::{self, Fanout, OutputRouter},
     task::Task,
     ConfigDiff,
 };
 use crate::{
-    buffers,
-    config::{DataType, SinkContext, TransformContext},
+    buffers::{self, dead_letter},
+    config::{self, DataType, SinkContext, TransformContext},
     dns::Resolver,
-    event::Event,
+    enrichment_tables,
+    event::{batch::ready_batches, Event},
+    internal_events::{ComponentEventsReceived, ComponentEventsSent},
     shutdown::SourceShutdownCoordinator,
-    Pipeline,
+    sinks::util::Flush,
+    transforms, Pipeline,
 };
 use futures::{
     compat::{Future01CompatExt, Stream01CompatExt},
     future, FutureExt, StreamExt, TryFutureExt,
 };
 use futures01::{sync::mpsc, Future, Stream};
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 use tokio::time::{timeout, Duration};
 
 pub struct Pieces {
-    pub inputs: HashMap<String, (buffers::BufferInputCloner, Vec<String>)>,
+    pub inputs: HashMap<String, (fanout::Input, Vec<String>)>,
     pub outputs: HashMap<String, fanout::ControlChannel>,
     pub tasks: HashMap<String, Task>,
     pub source_tasks: HashMap<String, Task>,
     pub healthchecks: HashMap<String, Task>,
     pub shutdown_coordinator: SourceShutdownCoordinator,
+    /// Flushes the batches of each sink.
+    pub flushes: HashMap<String, Flush>,
 }
 
 /// Builds only the new pieces, and doesn't check their topology.
@@ -39,9 +47,16 @@ pub async fn build_pieces(
     let mut source_tasks = HashMap::new();
     let mut healthchecks = HashMap::new();
     let mut shutdown_coordinator = SourceShutdownCoordinator::default();
+    let mut flushes = HashMap::new();
 
     let mut errors = vec![];
 
+    // Transforms look up their enrichment tables as they are built. The tables
+    // only replace those in use once the topology commits them.
+    if let Err(table_errors) = enrichment_tables::stage(&config.enrichment_tables) {
+        errors.extend(table_errors);
+    }
+
     // TODO: remove the unimplemented
     let resolver = Resolver;
 
@@ -70,7 +85,15 @@ pub async fn build_pieces(
         };
 
         let (output, control) = Fanout::new();
-        let pump = rx.forward(output).map(|_| ()).compat();
+        let pump = ready_batches(rx)
+            .inspect(|events| {
+                emit!(ComponentEventsSent {
+                    count: events.len()
+                })
+            })
+            .forward(output)
+            .map(|_| ())
+            .compat();
         let pump = Task::new(name, typetag, pump);
 
         // The force_shutdown_tripwire is a Future that when it resolves means that this source
@@ -105,24 +128,83 @@ pub async fn build_pieces(
         let cx = TransformContext { resolver };
 
         let input_type = transform.inner.input_type();
-        let transform = match transform.inner.build(cx).await {
-            Err(error) => {
-                errors.push(format!("Transform \"{}\": {}", name, error));
-                continue;
+        let named_outputs = transform.inner.named_outputs();
+        let concurrency = transform.concurrency();
+
+        // Stateless transforms can run as several instances.
+        let mut instances = Vec::with_capacity(concurrency);
+        while instances.len() < concurrency {
+            match transform.inner.build(cx.clone()).await {
+                Err(error) => {
+                    errors.push(format!("Transform \"{}\": {}", name, error));
+                    break;
+                }
+                Ok(transform) => instances.push(transform),
             }
-            Ok(transform) => transform,
-        };
+        }
+        if instances.len() < concurrency {
+            continue;
+        }
 
         let (input_tx, input_rx) = futures01::sync::mpsc::channel(100);
-        let input_tx = buffers::BufferInputCloner::Memory(input_tx, buffers::WhenFull::Block);
+        let input_tx = fanout::Input::new(
+            name,
+            buffers::BufferInputCloner::Batches(input_tx),
+            config::Isolation::None,
+        );
 
         let (output, control) = Fanout::new();
+        let input_rx: Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send> =
+            Box::new(filter_batch_type(input_rx, input_type).inspect(|events| {
+                emit!(ComponentEventsReceived {
+                    count: events.len()
+                })
+            }));
 
-        let transform = transform
-            .transform_stream(filter_event_type(input_rx, input_type))
-            .forward(output)
-            .map(|_| debug!("Finished"))
-            .compat();
+        // Every named output gets a fanout of its own.
+        let mut named = HashMap::new();
+        for output_name in named_outputs {
+            let (output, control) = Fanout::new();
+            outputs.insert(config::named_output(name, &output_name), control);
+            named.insert(output_name, output);
+        }
+
+        let transform = if concurrency == 1 && named.is_empty() {
+            instances
+                .pop()
+                .unwrap()
+                .transform_batches(input_rx)
+                .inspect(|events| {
+                    emit!(ComponentEventsSent {
+                        count: events.len()
+                    })
+                })
+                .forward(output)
+                .map(|_| debug!("Finished"))
+                .compat()
+                .boxed()
+        } else {
+            let events = if concurrency == 1 {
+                transforms::transform_stream_with_outputs(instances.pop().unwrap(), input_rx)
+            } else {
+                transforms::parallel::transform_stream(
+                    instances,
+                    input_rx,
+                    transform.preserve_order,
+                )
+            };
+
+            events
+                .inspect(|(_, events)| {
+                    emit!(ComponentEventsSent {
+                        count: events.len()
+                    })
+                })
+                .forward(OutputRouter::new(output, named))
+                .map(|_| debug!("Finished"))
+                .compat()
+                .boxed()
+        };
         let task = Task::new(name, typetag, transform);
 
         inputs.insert(name.clone(), (input_tx, trans_inputs.clone()));
@@ -150,10 +232,39 @@ pub async fn build_pieces(
             }
             Ok(buffer) => buffer,
         };
+        let usage = Arc::clone(tx.usage().expect("Sinks always have a buffer."));
+
+        // Disk buffers finalize events as soon as they are written, so only
+        // memory buffers need to hand the finalizers over to the sink.
+        let rx = filter_event_type(rx, input_type);
+        let (rx, acker) = match acker {
+            buffers::Acker::Null if upstream_acknowledgements(config, sink_inputs) => {
+                buffers::track_finalizers(rx)
+            }
+            acker => (rx, acker),
+        };
+
+        // The rejected events are pumped into their own fanout, which lives as
+        // long as the sink does.
+        let (rx, acker, dead_letter_pump) = if sink.dead_letter {
+            let (dead_letter_tx, dead_letter_rx) = mpsc::channel(1000);
+            let (output, control) = Fanout::new();
+            let pump = ready_batches(dead_letter_rx)
+                .forward(output)
+                .map(|_| ())
+                .compat();
+            outputs.insert(dead_letter::output_name(name), control);
+
+            let (rx, acker) = dead_letter::track_dead_letters(rx, acker, name, dead_letter_tx);
+            (rx, acker, Some(pump))
+        } else {
+            (rx, acker, None)
+        };
 
         let cx = SinkContext { resolver, acker };
 
-        let (sink, healthcheck) = match sink.inner.build(cx).await {
+        let flush = Flush::default();
+        let (sink, healthcheck) = match flush.clone().scope(sink.inner.build(cx)).await {
             Err(error) => {
                 errors.push(format!("Sink \"{}\": {}", name, error));
                 continue;
@@ -163,12 +274,23 @@ pub async fn build_pieces(
 
         let sink = sink
             .run(
-                filter_event_type(rx, input_type)
-                    .compat()
+                rx.compat()
                     .take_while(|e| future::ready(e.is_ok()))
-                    .map(|x| x.unwrap()),
+                    .map(|x| x.unwrap())
+                    .inspect(|_| emit!(ComponentEventsReceived { count: 1 })),
             )
             .inspect(|_| debug!("Finished"));
+        let sink = match dead_letter_pump {
+            Some(pump) => future::join(sink, pump).map(|(result, _)| result).boxed(),
+            None => sink.boxed(),
+        };
+        // The buffer usage is reported from the sink's task so that the
+        // metrics are tagged with the sink, and for as long as it runs.
+        let sink =
+            future::select(sink, buffers::report_usage(usage).boxed()).map(|either| match either {
+                future::Either::Left((result, _)) => result,
+                future::Either::Right(((), _)) => unreachable!("Reporting never ends."),
+            });
         let task = Task::new(name, typetag, sink);
 
         let healthcheck_task = async move {
@@ -197,9 +319,11 @@ pub async fn build_pieces(
         };
         let healthcheck_task = Task::new(name, typetag, healthcheck_task);
 
+        let tx = fanout::Input::new(name, tx, sink.isolation);
         inputs.insert(name.clone(), (tx, sink_inputs.clone()));
         healthchecks.insert(name.clone(), healthcheck_task);
         tasks.insert(name.clone(), task);
+        flushes.insert(name.clone(), flush);
     }
 
     if errors.is_empty() {
@@ -210,14 +334,40 @@ pub async fn build_pieces(
             source_tasks,
             healthchecks,
             shutdown_coordinator,
+            flushes,
         };
 
         Ok(pieces)
     } else {
-        Err(errors)
+        Err(errors.iter().map(|error| config.redact(error)).collect())
     }
 }
 
+/// Whether any source feeding into these inputs, directly or through
+/// transforms, waits for its events to be acknowledged.
+fn upstream_acknowledgements(config: &super::Config, inputs: &[String]) -> bool {
+    let mut pending = inputs.to_vec();
+    let mut seen = HashSet::new();
+
+    while let Some(name) = pending.pop() {
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+
+        if let Some(source) = config.sources.get(&name) {
+            if source.acknowledgements() {
+                return true;
+            }
+        } else if let Some(transform) = config.transforms.get(&name) {
+            pending.extend(transform.inputs.iter().cloned());
+        } else if let Some(transform) = config.named_output_transform(&name) {
+            pending.push(transform.to_string());
+        }
+    }
+
+    false
+}
+
 fn filter_event_type<S>(
     stream: S,
     data_type: DataType,
@@ -237,3 +387,25 @@ where
         })),
     }
 }
+
+fn filter_batch_type<S>(
+    stream: S,
+    data_type: DataType,
+) -> Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send>
+where
+    S: Stream<Item = Vec<Event>, Error = ()> + Send + 'static,
+{
+    let keep: fn(&Event) -> bool = match data_type {
+        DataType::Any => return Box::new(stream),
+        DataType::Log => |event| matches!(event, Event::Log(_)),
+        DataType::Metric => |event| matches!(event, Event::Metric(_)),
+    };
+    Box::new(
+        stream
+            .map(move |mut events| {
+                events.retain(keep);
+                events
+            })
+            .filter(|events| !events.is_empty()),
+    )
+}
@@ -1,37 +1,166 @@
-use crate::Event;
+use crate::{
+    buffers::BufferInputCloner,
+    config::Isolation,
+    event::EventStatus,
+    internal_events::{FanoutEventsDropped, FanoutOutputDetached, FanoutOutputReattached},
+    Event,
+};
 use futures::compat::Future01CompatExt;
 use futures01::{future, sync::mpsc, Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use std::collections::HashMap;
 
-type RouterSink = Box<dyn Sink<SinkItem = Event, SinkError = ()> + 'static + Send>;
+type RouterSink<T> = Box<dyn Sink<SinkItem = T, SinkError = ()> + 'static + Send>;
 
-pub struct Fanout {
-    sinks: Vec<(String, RouterSink)>,
+/// The input of a component, handed to the fanouts of the components feeding
+/// it.
+pub struct Input {
+    name: String,
+    tx: BufferInputCloner,
+    isolation: Isolation,
+}
+
+impl Input {
+    pub fn new(name: &str, tx: BufferInputCloner, isolation: Isolation) -> Self {
+        Self {
+            name: name.to_string(),
+            tx,
+            isolation,
+        }
+    }
+
+    /// A sink for one of the fanouts feeding the component, which only waits
+    /// for the component when it isn't isolated.
+    pub fn get(&self) -> RouterSink<Vec<Event>> {
+        let tx = self.tx.get_batched();
+        match self.isolation {
+            Isolation::None => tx,
+            isolation => Box::new(Isolated::new(&self.name, isolation, tx)),
+        }
+    }
+}
+
+/// Keeps a full output from holding up the fanout, by dropping the batches it
+/// has no room for. When detaching, the first of them is held back and sent
+/// again until the output accepts it, which is when it's attached again.
+pub struct Isolated {
+    name: String,
+    isolation: Isolation,
+    inner: RouterSink<Vec<Event>>,
+    held: Option<Vec<Event>>,
+}
+
+impl Isolated {
+    pub fn new(name: &str, isolation: Isolation, inner: RouterSink<Vec<Event>>) -> Self {
+        Self {
+            name: name.to_string(),
+            isolation,
+            inner,
+            held: None,
+        }
+    }
+
+    /// Tries to send the held batch again, returning whether the output is
+    /// attached.
+    fn try_reattach(&mut self) -> Result<bool, ()> {
+        let events = match self.held.take() {
+            Some(events) => events,
+            None => return Ok(true),
+        };
+        match self.inner.start_send(events)? {
+            AsyncSink::Ready => {
+                emit!(FanoutOutputReattached { output: &self.name });
+                Ok(true)
+            }
+            AsyncSink::NotReady(events) => {
+                self.held = Some(events);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Drops a batch the output had no room for, its events never being
+    /// delivered by the output.
+    fn drop_events(&self, mut events: Vec<Event>) {
+        for event in &mut events {
+            event.take_finalizers().update_status(EventStatus::Errored);
+        }
+        emit!(FanoutEventsDropped {
+            output: &self.name,
+            count: events.len(),
+        });
+    }
+}
+
+impl Sink for Isolated {
+    type SinkItem = Vec<Event>;
+    type SinkError = ();
+
+    fn start_send(&mut self, events: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if !self.try_reattach()? {
+            self.drop_events(events);
+            return Ok(AsyncSink::Ready);
+        }
+
+        if let AsyncSink::NotReady(events) = self.inner.start_send(events)? {
+            match self.isolation {
+                Isolation::Detach => {
+                    emit!(FanoutOutputDetached { output: &self.name });
+                    self.held = Some(events);
+                }
+                _ => self.drop_events(events),
+            }
+        }
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.try_reattach()?;
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        if !self.try_reattach()? {
+            return Ok(Async::NotReady);
+        }
+        self.inner.close()
+    }
+}
+
+/// Sends each item to every one of its sinks. The topology passes batches of
+/// events around.
+pub struct Fanout<T = Vec<Event>> {
+    sinks: Vec<(String, RouterSink<T>)>,
     i: usize,
-    control_channel: mpsc::UnboundedReceiver<ControlMessage>,
+    control_channel: mpsc::UnboundedReceiver<ControlMessage<T>>,
+    paused: bool,
 }
 
-pub enum ControlMessage {
-    Add(String, RouterSink),
+pub enum ControlMessage<T = Vec<Event>> {
+    Add(String, RouterSink<T>),
     Remove(String),
-    Replace(String, RouterSink),
+    Replace(String, RouterSink<T>),
+    /// Holds back the items sent to the fanout until it's resumed.
+    Pause,
+    Resume,
 }
 
-pub type ControlChannel = mpsc::UnboundedSender<ControlMessage>;
+pub type ControlChannel<T = Vec<Event>> = mpsc::UnboundedSender<ControlMessage<T>>;
 
-impl Fanout {
-    pub fn new() -> (Self, ControlChannel) {
+impl<T: Clone + Send + 'static> Fanout<T> {
+    pub fn new() -> (Self, ControlChannel<T>) {
         let (control_tx, control_rx) = mpsc::unbounded();
 
         let fanout = Self {
             sinks: vec![],
             i: 0,
             control_channel: control_rx,
+            paused: false,
         };
 
         (fanout, control_tx)
     }
 
-    pub fn add(&mut self, name: String, sink: RouterSink) {
+    pub fn add(&mut self, name: String, sink: RouterSink<T>) {
         assert!(
             !self.sinks.iter().any(|(n, _)| n == &name),
             "Duplicate output name in fanout"
@@ -53,7 +182,7 @@ impl Fanout {
         }
     }
 
-    fn replace(&mut self, name: String, sink: RouterSink) {
+    fn replace(&mut self, name: String, sink: RouterSink<T>) {
         if let Some((_, existing)) = self.sinks.iter_mut().find(|(n, _)| n == &name) {
             *existing = sink
         } else {
@@ -67,6 +196,8 @@ impl Fanout {
                 ControlMessage::Add(name, sink) => self.add(name, sink),
                 ControlMessage::Remove(name) => self.remove(&name),
                 ControlMessage::Replace(name, sink) => self.replace(name, sink),
+                ControlMessage::Pause => self.paused = true,
+                ControlMessage::Resume => self.paused = false,
             }
         }
     }
@@ -109,13 +240,18 @@ impl Fanout {
     }
 }
 
-impl Sink for Fanout {
-    type SinkItem = Event;
+impl<T: Clone + Send + 'static> Sink for Fanout<T> {
+    type SinkItem = T;
     type SinkError = ();
 
     fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
         self.process_control_messages();
 
+        // The control channel wakes the task up once the fanout is resumed.
+        if self.paused && self.i == 0 {
+            return Ok(AsyncSink::NotReady(item));
+        }
+
         if self.sinks.is_empty() {
             return Ok(AsyncSink::Ready);
         }
@@ -150,10 +286,79 @@ impl Sink for Fanout {
     }
 }
 
+/// Sends the batches of a transform with named outputs to the `Fanout` of the
+/// output each of them is meant for, `None` being the default output.
+pub struct OutputRouter {
+    primary: Fanout,
+    named: HashMap<String, Fanout>,
+}
+
+impl OutputRouter {
+    pub fn new(primary: Fanout, named: HashMap<String, Fanout>) -> Self {
+        Self { primary, named }
+    }
+
+    fn poll_all(&mut self, close: bool) -> Poll<(), ()> {
+        let mut poll_result = Async::Ready(());
+        for fanout in std::iter::once(&mut self.primary).chain(self.named.values_mut()) {
+            let result = if close {
+                fanout.close()?
+            } else {
+                fanout.poll_complete()?
+            };
+            if result.is_not_ready() {
+                poll_result = Async::NotReady;
+            }
+        }
+        Ok(poll_result)
+    }
+}
+
+impl Sink for OutputRouter {
+    type SinkItem = (Option<String>, Vec<Event>);
+    type SinkError = ();
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        let (output, events) = item;
+        let fanout = match &output {
+            None => &mut self.primary,
+            Some(name) => match self.named.get_mut(name) {
+                Some(fanout) => fanout,
+                None => {
+                    warn!(
+                        message = "Dropping events sent to an undeclared output.",
+                        output = %name,
+                        rate_limit_secs = 30
+                    );
+                    return Ok(AsyncSink::Ready);
+                }
+            },
+        };
+
+        match fanout.start_send(events)? {
+            AsyncSink::Ready => Ok(AsyncSink::Ready),
+            AsyncSink::NotReady(events) => Ok(AsyncSink::NotReady((output, events))),
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.poll_all(false)
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        self.poll_all(true)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ControlMessage, Fanout};
-    use crate::{test_util::collect_ready, Event};
+    use super::{ControlMessage, Fanout, Isolated, OutputRouter};
+    use crate::{
+        config::Isolation,
+        event::{BatchNotifier, BatchStatus},
+        test_util::collect_ready,
+        Event,
+    };
     use futures::compat::Future01CompatExt;
     use futures01::{stream, sync::mpsc, Future, Sink, Stream};
     use tokio::time::{delay_for, Duration};
@@ -413,6 +618,29 @@ mod tests {
         let _fanout = fanout.send(rec2).compat().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn fanout_pause() {
+        let (tx_a, mut rx_a) = mpsc::unbounded();
+        let tx_a = Box::new(tx_a.sink_map_err(|_| unreachable!()));
+
+        let (mut fanout, control) = Fanout::new();
+        fanout.add("a".to_string(), tx_a);
+        control.unbounded_send(ControlMessage::Pause).unwrap();
+
+        let rec1 = Event::from("line 1".to_string());
+        let rec2 = Event::from("line 2".to_string());
+        let recs = vec![rec1, rec2];
+        let send = fanout.send_all(stream::iter_ok(recs.clone()));
+        tokio::spawn(send.map(|_| ()).compat());
+
+        delay_for(Duration::from_millis(50)).await;
+        assert!(collect_ready(rx_a.by_ref()).await.unwrap().is_empty());
+
+        control.unbounded_send(ControlMessage::Resume).unwrap();
+        delay_for(Duration::from_millis(50)).await;
+        assert_eq!(collect_ready(rx_a).await.unwrap(), recs);
+    }
+
     #[tokio::test]
     async fn fanout_replace() {
         let (tx_a1, rx_a1) = mpsc::unbounded();
@@ -448,4 +676,125 @@ mod tests {
         );
         assert_eq!(collect_ready(rx_a2).await.unwrap(), vec![rec3]);
     }
+
+    #[tokio::test]
+    async fn output_router_sends_to_named_outputs() {
+        let (tx_primary, rx_primary) = mpsc::unbounded();
+        let (tx_errors, rx_errors) = mpsc::unbounded();
+
+        let mut primary = Fanout::new().0;
+        primary.add(
+            "primary".to_string(),
+            Box::new(tx_primary.sink_map_err(|_| unreachable!())),
+        );
+        let mut errors = Fanout::new().0;
+        errors.add(
+            "errors".to_string(),
+            Box::new(tx_errors.sink_map_err(|_| unreachable!())),
+        );
+        let router = OutputRouter::new(
+            primary,
+            vec![("errors".to_string(), errors)].into_iter().collect(),
+        );
+
+        let rec1 = Event::from("line 1".to_string());
+        let rec2 = Event::from("line 2".to_string());
+        let rec3 = Event::from("line 3".to_string());
+
+        let _router = router
+            .send_all(stream::iter_ok(vec![
+                (None, vec![rec1.clone()]),
+                (Some("errors".to_string()), vec![rec2.clone()]),
+                (Some("unknown".to_string()), vec![rec3]),
+            ]))
+            .compat()
+            .await
+            .unwrap();
+
+        assert_eq!(collect_ready(rx_primary).await.unwrap(), vec![vec![rec1]]);
+        assert_eq!(collect_ready(rx_errors).await.unwrap(), vec![vec![rec2]]);
+    }
+
+    fn isolated_fanout(
+        isolation: Isolation,
+    ) -> (
+        Fanout,
+        mpsc::UnboundedReceiver<Vec<Event>>,
+        mpsc::Receiver<Vec<Event>>,
+    ) {
+        let (tx_a, rx_a) = mpsc::unbounded();
+        let tx_a = Box::new(tx_a.sink_map_err(|_| unreachable!()));
+        // Only has room for a single batch.
+        let (tx_b, rx_b) = mpsc::channel(0);
+        let tx_b = Box::new(tx_b.sink_map_err(|_| unreachable!()));
+
+        let mut fanout = Fanout::new().0;
+        fanout.add("a".to_string(), tx_a);
+        fanout.add(
+            "b".to_string(),
+            Box::new(Isolated::new("b", isolation, tx_b)),
+        );
+
+        (fanout, rx_a, rx_b)
+    }
+
+    fn batches() -> Vec<Vec<Event>> {
+        (1..=3)
+            .map(|i| vec![Event::from(format!("line {}", i))])
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn isolated_output_skips_batches_when_full() {
+        let (fanout, rx_a, rx_b) = isolated_fanout(Isolation::Skip);
+
+        // Nothing reads from b, yet sending doesn't wait for it.
+        let _fanout = fanout
+            .send_all(stream::iter_ok(batches()))
+            .compat()
+            .await
+            .unwrap();
+
+        assert_eq!(collect_ready(rx_a).await.unwrap(), batches());
+        assert_eq!(collect_ready(rx_b).await.unwrap(), batches()[..1].to_vec());
+    }
+
+    #[tokio::test]
+    async fn skipped_batches_are_not_delivered() {
+        let (fanout, rx_a, rx_b) = isolated_fanout(Isolation::Skip);
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let mut batches = batches();
+        for event in &mut batches[2] {
+            event.add_batch_notifier(batch.clone());
+        }
+        drop(batch);
+
+        let _fanout = fanout
+            .send_all(stream::iter_ok(batches))
+            .compat()
+            .await
+            .unwrap();
+        // a got the batch, but b skipped it.
+        drop(collect_ready(rx_a).await.unwrap());
+        drop(collect_ready(rx_b).await.unwrap());
+
+        assert_eq!(receiver.await, BatchStatus::Errored);
+    }
+
+    #[tokio::test]
+    async fn detached_output_catches_up() {
+        let (fanout, rx_a, rx_b) = isolated_fanout(Isolation::Detach);
+
+        let send = fanout.send_all(stream::iter_ok(batches()));
+        tokio::spawn(send.map(|_| ()).compat());
+
+        delay_for(Duration::from_millis(50)).await;
+        // a got everything while b was detached.
+        assert_eq!(collect_ready(rx_a).await.unwrap(), batches());
+
+        // The batch b had no room for when it was detached is sent once it
+        // catches up, while the one after it was dropped.
+        let expected = vec![batches()[0].clone(), batches()[1].clone()];
+        assert_eq!(rx_b.collect().compat().await.unwrap(), expected);
+    }
 }
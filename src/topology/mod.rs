@@ -8,13 +8,16 @@
 
 pub mod builder;
 mod fanout;
+pub mod tap;
 mod task;
 
 use crate::{
-    buffers,
-    config::{Config, ConfigDiff},
+    buffers::dead_letter,
+    config::{self, Config, ConfigDiff},
+    enrichment_tables,
     shutdown::SourceShutdownCoordinator,
-    topology::{builder::Pieces, task::Task},
+    sinks::util::Flush,
+    topology::{builder::Pieces, tap::Tap, task::Task},
 };
 use futures::{compat::Future01CompatExt, future, FutureExt, StreamExt, TryFutureExt};
 use futures01::{sync::mpsc, Future};
@@ -22,7 +25,10 @@ use std::{
     collections::{HashMap, HashSet},
     panic::AssertUnwindSafe,
 };
-use tokio::time::{delay_until, interval, Duration, Instant};
+use tokio::{
+    sync::oneshot,
+    time::{delay_until, interval, Duration, Instant},
+};
 use tracing_futures::Instrument;
 
 // TODO: Result is only for compat, remove when not needed
@@ -30,15 +36,35 @@ type TaskHandle = tokio::task::JoinHandle<Result<(), ()>>;
 
 #[allow(dead_code)]
 pub struct RunningTopology {
-    inputs: HashMap<String, buffers::BufferInputCloner>,
+    inputs: HashMap<String, fanout::Input>,
     outputs: HashMap<String, fanout::ControlChannel>,
     source_tasks: HashMap<String, TaskHandle>,
     tasks: HashMap<String, TaskHandle>,
     shutdown_coordinator: SourceShutdownCoordinator,
     config: Config,
     abort_tx: mpsc::UnboundedSender<()>,
+    flushes: HashMap<String, Flush>,
+    paused: HashSet<String>,
+    taps: HashMap<usize, Tap>,
+}
+
+/// A change asked of the running topology from outside of it, like through the
+/// API.
+#[derive(Debug, Clone)]
+pub enum Control {
+    /// Reloads the configuration, which is up to the application loading it.
+    Reload,
+    PauseSource(String),
+    ResumeSource(String),
+    FlushSink(String),
+    Tap(Tap),
+    /// Removes the tap with the given id.
+    Untap(usize),
 }
 
+/// A `Control`, along with where to send whether it went through.
+pub type ControlRequest = (Control, oneshot::Sender<Result<(), String>>);
+
 pub async fn start_validated(
     config: Config,
     diff: ConfigDiff,
@@ -55,6 +81,9 @@ pub async fn start_validated(
         source_tasks: HashMap::new(),
         tasks: HashMap::new(),
         abort_tx,
+        flushes: HashMap::new(),
+        paused: HashSet::new(),
+        taps: HashMap::new(),
     };
 
     if !running_topology
@@ -65,6 +94,7 @@ pub async fn start_validated(
     }
     running_topology.connect_diff(&diff, &mut pieces);
     running_topology.spawn_diff(&diff, pieces);
+    enrichment_tables::commit();
 
     Some((running_topology, abort_rx))
 }
@@ -235,6 +265,7 @@ impl RunningTopology {
             {
                 self.connect_diff(&diff, &mut new_pieces);
                 self.spawn_diff(&diff, new_pieces);
+                enrichment_tables::commit();
                 self.config = new_config;
                 // We have successfully changed to new config.
                 return Ok(true);
@@ -251,6 +282,7 @@ impl RunningTopology {
             {
                 self.connect_diff(&diff, &mut new_pieces);
                 self.spawn_diff(&diff, new_pieces);
+                enrichment_tables::commit();
                 // We have successfully returned to old config.
                 return Ok(false);
             }
@@ -290,6 +322,67 @@ impl RunningTopology {
         }
     }
 
+    /// Holds back the events of a source, which waits with them until it's
+    /// resumed. Sources stay paused when a reload rebuilds them.
+    pub fn pause_source(&mut self, name: &str) -> Result<(), String> {
+        self.control_source(name, fanout::ControlMessage::Pause)?;
+        self.paused.insert(name.to_string());
+        Ok(())
+    }
+
+    pub fn resume_source(&mut self, name: &str) -> Result<(), String> {
+        self.control_source(name, fanout::ControlMessage::Resume)?;
+        self.paused.remove(name);
+        Ok(())
+    }
+
+    /// Makes a sink send the batches it holds right away.
+    pub fn flush_sink(&self, name: &str) -> Result<(), String> {
+        match self.flushes.get(name) {
+            Some(flush) => {
+                flush.trigger();
+                Ok(())
+            }
+            None => Err(format!("There is no sink {:?}.", name)),
+        }
+    }
+
+    /// Sends copies of the events leaving the outputs matched by `tap` to it,
+    /// until it's removed.
+    pub fn tap(&mut self, tap: Tap) {
+        for (name, output) in &self.outputs {
+            if tap.matches(name) {
+                // This can only fail if the component is shutting down.
+                let _ = output.unbounded_send(fanout::ControlMessage::Add(
+                    tap.name(),
+                    Box::new(tap.sink(name)),
+                ));
+            }
+        }
+        self.taps.insert(tap.id(), tap);
+    }
+
+    pub fn untap(&mut self, id: usize) {
+        if let Some(tap) = self.taps.remove(&id) {
+            for (name, output) in &self.outputs {
+                if tap.matches(name) {
+                    let _ = output.unbounded_send(fanout::ControlMessage::Remove(tap.name()));
+                }
+            }
+        }
+    }
+
+    fn control_source(&self, name: &str, message: fanout::ControlMessage) -> Result<(), String> {
+        match self.outputs.get(name) {
+            Some(output) if self.config.sources.contains_key(name) => {
+                // This can only fail if the source is shutting down.
+                let _ = output.unbounded_send(message);
+                Ok(())
+            }
+            _ => Err(format!("There is no source {:?}.", name)),
+        }
+    }
+
     /// Shutdowns removed and replaced pieces of topology.
     async fn shutdown_diff(&mut self, diff: &ConfigDiff) {
         // Sources
@@ -315,6 +408,7 @@ impl RunningTopology {
             drop(previous); // detach and forget
 
             self.remove_outputs(name);
+            self.paused.remove(name);
             source_shutdown_complete_futures
                 .push(self.shutdown_coordinator.shutdown_source(name, deadline));
         }
@@ -360,6 +454,11 @@ impl RunningTopology {
 
             self.remove_inputs(&name);
             self.remove_outputs(&name);
+            self.remove_named_outputs(&name);
+        }
+        for name in &diff.transforms.to_change {
+            // The new version of the transform may not have the same outputs.
+            self.remove_named_outputs(&name);
         }
 
         // Sinks
@@ -368,8 +467,13 @@ impl RunningTopology {
 
             let previous = self.tasks.remove(name).unwrap();
             drop(previous); // detach and forget
+            self.flushes.remove(name);
 
             self.remove_inputs(&name);
+            self.remove_outputs(&dead_letter::output_name(name));
+        }
+        for name in &diff.sinks.to_change {
+            self.remove_outputs(&dead_letter::output_name(name));
         }
     }
 
@@ -378,6 +482,17 @@ impl RunningTopology {
         // Sources
         for name in diff.sources.changed_and_added() {
             self.setup_outputs(&name, new_pieces);
+            if self.paused.contains(name) {
+                let _ = self.outputs[name].unbounded_send(fanout::ControlMessage::Pause);
+            }
+        }
+
+        // Dead-letter outputs of sinks
+        for name in diff.sinks.changed_and_added() {
+            let output = dead_letter::output_name(name);
+            if new_pieces.outputs.contains_key(&output) {
+                self.setup_outputs(&output, new_pieces);
+            }
         }
 
         // Transforms
@@ -386,6 +501,11 @@ impl RunningTopology {
         for name in diff.transforms.changed_and_added() {
             self.setup_outputs(&name, new_pieces);
         }
+        // All that's left are the named outputs of those transforms.
+        let named_outputs = new_pieces.outputs.keys().cloned().collect::<Vec<_>>();
+        for output in named_outputs {
+            self.setup_outputs(&output, new_pieces);
+        }
 
         for name in &diff.transforms.to_change {
             self.replace_inputs(&name, new_pieces);
@@ -454,6 +574,10 @@ impl RunningTopology {
         if let Some(previous) = self.tasks.insert(name.to_string(), spawned) {
             drop(previous); // detach and forget
         }
+
+        if let Some(flush) = new_pieces.flushes.remove(name) {
+            self.flushes.insert(name.to_string(), flush);
+        }
     }
 
     fn spawn_transform(&mut self, name: &str, new_pieces: &mut builder::Pieces) {
@@ -499,6 +623,14 @@ impl RunningTopology {
         self.outputs.remove(name);
     }
 
+    fn remove_named_outputs(&mut self, name: &str) {
+        if let Some(transform) = self.config.transforms.get(name) {
+            for output in transform.inner.named_outputs() {
+                self.remove_outputs(&config::named_output(name, &output));
+            }
+        }
+    }
+
     fn remove_inputs(&mut self, name: &str) {
         self.inputs.remove(name);
 
@@ -544,6 +676,15 @@ impl RunningTopology {
             }
         }
 
+        for tap in self.taps.values().filter(|tap| tap.matches(name)) {
+            output
+                .unbounded_send(fanout::ControlMessage::Add(
+                    tap.name(),
+                    Box::new(tap.sink(name)),
+                ))
+                .expect("Components shouldn't be spawned before connecting them together.");
+        }
+
         self.outputs.insert(name.to_string(), output);
     }
 
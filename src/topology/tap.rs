@@ -0,0 +1,144 @@
+//! Copies of the events leaving the outputs of the topology, for watching
+//! them live through the API.
+//!
+//! A tap is added to the fanout of every output whose name matches one of its
+//! patterns, and to those of the outputs a reload rebuilds. It never holds up
+//! the fanout: events it has no room for are dropped. Nor does it hold up the
+//! acknowledgement of the events, as its copies don't carry their finalizers.
+
+use crate::Event;
+use futures01::{Async, AsyncSink, Poll, Sink, StartSend};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// An event, along with the name of the output it left.
+#[derive(Debug, Clone)]
+pub struct Tapped {
+    pub output: String,
+    pub event: Event,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tap {
+    id: usize,
+    patterns: Vec<glob::Pattern>,
+    tx: mpsc::Sender<Tapped>,
+}
+
+impl Tap {
+    pub fn new(patterns: &[String], tx: mpsc::Sender<Tapped>) -> Result<Self, String> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern)
+                    .map_err(|error| format!("Invalid pattern {:?}: {}.", pattern, error))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            patterns,
+            tx,
+        })
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub(super) fn matches(&self, output: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(output))
+    }
+
+    /// The name of the tap among the outputs of a fanout.
+    pub(super) fn name(&self) -> String {
+        format!("_tap_{}", self.id)
+    }
+
+    pub(super) fn sink(&self, output: &str) -> TapSink {
+        TapSink {
+            output: output.to_string(),
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+pub struct TapSink {
+    output: String,
+    tx: mpsc::Sender<Tapped>,
+}
+
+impl Sink for TapSink {
+    type SinkItem = Vec<Event>;
+    type SinkError = ();
+
+    fn start_send(&mut self, events: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        for mut event in events {
+            // The copy isn't delivered anywhere, so the batch needn't wait for it.
+            let _ = event.take_finalizers();
+            let tapped = Tapped {
+                output: self.output.clone(),
+                event,
+            };
+            // Full or closed, either way the rest of the batch would be dropped too.
+            if self.tx.try_send(tapped).is_err() {
+                break;
+            }
+        }
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        Ok(Async::Ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{BatchNotifier, BatchStatus};
+    use futures::FutureExt;
+
+    #[test]
+    fn drops_events_without_room() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let tap = Tap::new(&["in*".to_owned(), "parse.*".to_owned()], tx).unwrap();
+        assert!(tap.matches("in1"));
+        assert!(tap.matches("parse.json"));
+        assert!(!tap.matches("out"));
+
+        let mut sink = tap.sink("in1");
+        let events = (0..5).map(|i| Event::from(i.to_string())).collect();
+        assert!(sink.start_send(events).unwrap().is_ready());
+
+        let mut tapped = Vec::new();
+        while let Ok(Tapped { output, event }) = rx.try_recv() {
+            assert_eq!(output, "in1");
+            tapped.push(event);
+        }
+        assert_eq!(tapped, vec![Event::from("0"), Event::from("1")]);
+    }
+
+    #[test]
+    fn tapped_events_dont_hold_up_their_batch() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let tap = Tap::new(&["in".to_owned()], tx).unwrap();
+        let (batch, receiver) = BatchNotifier::new_with_receiver();
+        let mut event = Event::from("tapped");
+        event.add_batch_notifier(batch);
+
+        let mut sink = tap.sink("in");
+        assert!(sink.start_send(vec![event]).unwrap().is_ready());
+
+        // The batch resolves while the copy is yet to be read.
+        assert_eq!(receiver.now_or_never(), Some(BatchStatus::Delivered));
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let (tx, _rx) = mpsc::channel(1);
+        assert!(Tap::new(&["in[".to_owned()], tx).is_err());
+    }
+}
@@ -80,6 +80,10 @@ impl TransformConfig for AddFieldsConfig {
     fn transform_type(&self) -> &'static str {
         "add_fields"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 impl AddFields {
@@ -53,6 +53,10 @@ impl TransformConfig for AddTagsConfig {
     fn transform_type(&self) -> &'static str {
         "add_tags"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 impl AddTags {
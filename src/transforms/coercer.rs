@@ -45,6 +45,10 @@ impl TransformConfig for CoercerConfig {
     fn transform_type(&self) -> &'static str {
         "coercer"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct Coercer {
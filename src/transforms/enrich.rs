@@ -0,0 +1,192 @@
+use super::Transform;
+use crate::{
+    config::{DataType, GenerateConfig, TransformConfig, TransformContext, TransformDescription},
+    enrichment_tables::{self, EnrichmentTable},
+    event::Event,
+    internal_events::{EnrichKeyFieldMissing, EnrichLookupFailed, EnrichRecordNotFound},
+};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EnrichConfig {
+    pub table: String,
+    /// The columns a record must match, each with the field of the event
+    /// holding the value to match.
+    pub keys: IndexMap<String, String>,
+    /// Defaults to the name of the table.
+    pub target: Option<String>,
+}
+
+inventory::submit! {
+    TransformDescription::new::<EnrichConfig>("enrich")
+}
+
+impl GenerateConfig for EnrichConfig {
+    fn generate_config() -> toml::Value {
+        toml::from_str(
+            r#"table = "owners"
+            keys.hostname = "host""#,
+        )
+        .unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+#[typetag::serde(name = "enrich")]
+impl TransformConfig for EnrichConfig {
+    async fn build(&self, _cx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        let table = enrichment_tables::get(&self.table)
+            .ok_or_else(|| format!("Unknown enrichment table {:?}.", self.table))?;
+        if self.keys.is_empty() {
+            return Err("At least one key is needed to find records.".into());
+        }
+        for column in self.keys.keys() {
+            if !table.table().columns().contains(column) {
+                return Err(format!(
+                    "Enrichment table {:?} has no column {:?}.",
+                    self.table, column
+                )
+                .into());
+            }
+        }
+
+        let target = self.target.clone().unwrap_or_else(|| self.table.clone());
+        Ok(Box::new(Enrich::new(table, self.keys.clone(), target)))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "enrich"
+    }
+
+    fn stateless(&self) -> bool {
+        true
+    }
+}
+
+pub struct Enrich {
+    table: Arc<EnrichmentTable>,
+    keys: IndexMap<String, String>,
+    target: String,
+}
+
+impl Enrich {
+    pub fn new(
+        table: Arc<EnrichmentTable>,
+        keys: IndexMap<String, String>,
+        target: String,
+    ) -> Self {
+        Self {
+            table,
+            keys,
+            target,
+        }
+    }
+}
+
+impl Transform for Enrich {
+    fn transform(&mut self, mut event: Event) -> Option<Event> {
+        let log = event.as_mut_log();
+
+        let mut values = Vec::with_capacity(self.keys.len());
+        for (column, field) in &self.keys {
+            match log.get(field) {
+                Some(value) => values.push((column.as_str(), value.to_string_lossy())),
+                None => {
+                    emit!(EnrichKeyFieldMissing { field });
+                    return Some(event);
+                }
+            }
+        }
+        let conditions = values
+            .iter()
+            .map(|(column, value)| (*column, value.as_str()))
+            .collect::<Vec<_>>();
+
+        match self.table.table().find(&conditions) {
+            Ok(Some(record)) => {
+                log.insert(&self.target, record);
+            }
+            Ok(None) => emit!(EnrichRecordNotFound {
+                table: self.table.name()
+            }),
+            // The columns may have changed with the file since the transform
+            // was built.
+            Err(error) => emit!(EnrichLookupFailed {
+                table: self.table.name(),
+                error,
+            }),
+        }
+
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        enrichment_tables::Table,
+        event::{LogEvent, Value},
+    };
+
+    #[test]
+    fn generate_config() {
+        crate::test_util::test_generate_config::<EnrichConfig>();
+    }
+
+    fn enrich(keys: &[(&str, &str)]) -> Enrich {
+        let table = Table::from_reader(
+            "hostname,team,owner\nweb-1,web,alice\ndb-1,data,carol\n".as_bytes(),
+            b',',
+        )
+        .unwrap();
+        Enrich::new(
+            Arc::new(EnrichmentTable::new("owners", table)),
+            keys.iter()
+                .map(|(column, field)| (column.to_string(), field.to_string()))
+                .collect(),
+            "owner".into(),
+        )
+    }
+
+    #[test]
+    fn enrich_adds_matching_record() {
+        let mut log = LogEvent::default();
+        log.insert("host", "db-1");
+
+        let event = enrich(&[("hostname", "host")])
+            .transform(log.into())
+            .unwrap();
+
+        let log = event.as_log();
+        assert_eq!(log["owner.owner"], Value::from("carol"));
+        assert_eq!(log["owner.team"], Value::from("data"));
+    }
+
+    #[test]
+    fn enrich_passes_unmatched_events() {
+        let mut log = LogEvent::default();
+        log.insert("host", "web-2");
+        let event = enrich(&[("hostname", "host")])
+            .transform(log.into())
+            .unwrap();
+        assert!(!event.as_log().contains("owner"));
+
+        // Without the key field.
+        let event = enrich(&[("hostname", "host")])
+            .transform(LogEvent::default().into())
+            .unwrap();
+        assert!(!event.as_log().contains("owner"));
+    }
+}
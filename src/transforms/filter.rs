@@ -44,6 +44,10 @@ impl TransformConfig for FilterConfig {
     fn transform_type(&self) -> &'static str {
         "filter"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct Filter {
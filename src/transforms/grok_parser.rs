@@ -75,6 +75,10 @@ impl TransformConfig for GrokParserConfig {
     fn transform_type(&self) -> &'static str {
         "grok_parser"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct GrokParser {
@@ -1,4 +1,4 @@
-use super::Transform;
+use super::{Transform, TransformOutputs};
 use crate::{
     config::{log_schema, DataType, TransformConfig, TransformContext, TransformDescription},
     event::Event,
@@ -17,6 +17,7 @@ pub struct JsonParserConfig {
     pub drop_field: bool,
     pub target_field: Option<String>,
     pub overwrite_target: Option<bool>,
+    pub reroute_errors: bool,
 }
 
 inventory::submit! {
@@ -43,6 +44,25 @@ impl TransformConfig for JsonParserConfig {
     fn transform_type(&self) -> &'static str {
         "json_parser"
     }
+
+    /// Only known when the object is kept under `target_field`.
+    fn fields(&self) -> Option<Vec<String>> {
+        self.target_field
+            .clone()
+            .map(|target_field| vec![target_field])
+    }
+
+    fn stateless(&self) -> bool {
+        true
+    }
+
+    fn named_outputs(&self) -> Vec<String> {
+        if self.reroute_errors {
+            vec!["errors".into()]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -52,6 +72,7 @@ pub struct JsonParser {
     drop_field: bool,
     target_field: Option<String>,
     overwrite_target: bool,
+    reroute_errors: bool,
 }
 
 impl From<JsonParserConfig> for JsonParser {
@@ -66,12 +87,14 @@ impl From<JsonParserConfig> for JsonParser {
             drop_field: config.drop_field,
             target_field: config.target_field,
             overwrite_target: config.overwrite_target.unwrap_or(false),
+            reroute_errors: config.reroute_errors,
         }
     }
 }
 
-impl Transform for JsonParser {
-    fn transform(&mut self, mut event: Event) -> Option<Event> {
+impl JsonParser {
+    /// Parses the field in place, returning whether it held a JSON object.
+    fn parse(&self, event: &mut Event) -> bool {
         let log = event.as_mut_log();
         let value = log.get(&self.field);
 
@@ -123,18 +146,39 @@ impl Transform for JsonParser {
                     }
                 }
             }
-        } else if self.drop_invalid {
-            return None;
+            true
+        } else {
+            false
         }
+    }
+}
+
+impl Transform for JsonParser {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        let mut outputs = TransformOutputs::default();
+        self.transform_into_outputs(&mut outputs, event);
+        outputs.primary.pop()
+    }
 
-        Some(event)
+    fn transform_into_outputs(&mut self, outputs: &mut TransformOutputs, mut event: Event) {
+        if self.parse(&mut event) {
+            outputs.push(event);
+        } else if self.reroute_errors {
+            outputs.push_named("errors", event);
+        } else if !self.drop_invalid {
+            outputs.push(event);
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::{JsonParser, JsonParserConfig};
-    use crate::{config::log_schema, event::Event, transforms::Transform};
+    use crate::{
+        config::log_schema,
+        event::Event,
+        transforms::{Transform, TransformOutputs},
+    };
     use serde_json::json;
 
     #[test]
@@ -369,6 +413,29 @@ mod test {
         assert!(parser.transform(event).is_none());
     }
 
+    #[test]
+    fn json_parser_reroute_errors() {
+        let mut parser = JsonParser::from(JsonParserConfig {
+            drop_invalid: true,
+            reroute_errors: true,
+            ..Default::default()
+        });
+        let mut outputs = TransformOutputs::default();
+
+        parser.transform_into_outputs(&mut outputs, Event::from(r#"{"greeting": "hello"}"#));
+        parser.transform_into_outputs(&mut outputs, Event::from(r#"{"greeting": "hello","#));
+
+        assert_eq!(outputs.primary.len(), 1);
+        assert_eq!(outputs.primary[0].as_log()["greeting"], "hello".into());
+        assert_eq!(outputs.named.len(), 1);
+        let (output, event) = &outputs.named[0];
+        assert_eq!(output, "errors");
+        assert_eq!(
+            event.as_log()[log_schema().message_key()],
+            r#"{"greeting": "hello","#.into()
+        );
+    }
+
     #[test]
     fn json_parser_chained() {
         let mut parser1 = JsonParser::from(JsonParserConfig {
@@ -53,6 +53,10 @@ impl TransformConfig for LogfmtConfig {
     fn transform_type(&self) -> &'static str {
         "logfmt_parser"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct Logfmt {
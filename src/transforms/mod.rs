@@ -1,6 +1,7 @@
 use crate::Event;
 use snafu::Snafu;
 
+pub mod parallel;
 pub mod util;
 
 #[cfg(feature = "transforms-add_fields")]
@@ -19,6 +20,8 @@ pub mod coercer;
 pub mod concat;
 #[cfg(feature = "transforms-dedupe")]
 pub mod dedupe;
+#[cfg(feature = "transforms-enrich")]
+pub mod enrich;
 #[cfg(feature = "transforms-field_filter")]
 pub mod field_filter;
 #[cfg(feature = "transforms-filter")]
@@ -64,6 +67,7 @@ pub mod tokenizer;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+use crate::event::batch::{ready_batches, unbatch};
 use futures01::Stream;
 
 pub trait Transform: Send {
@@ -75,6 +79,12 @@ pub trait Transform: Send {
         }
     }
 
+    /// Like `transform_into`, for transforms whose config declares named
+    /// outputs through `TransformConfig::named_outputs`.
+    fn transform_into_outputs(&mut self, outputs: &mut TransformOutputs, event: Event) {
+        self.transform_into(&mut outputs.primary, event);
+    }
+
     fn transform_stream(
         self: Box<Self>,
         input_rx: Box<dyn Stream<Item = Event, Error = ()> + Send>,
@@ -93,6 +103,78 @@ pub trait Transform: Send {
                 .flatten(),
         )
     }
+
+    /// Runs the transform over batches of events, which is how the topology
+    /// runs it.
+    fn transform_batches(
+        self: Box<Self>,
+        input_rx: Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send>,
+    ) -> Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send>
+    where
+        Self: 'static,
+    {
+        Box::new(ready_batches(
+            self.transform_stream(Box::new(unbatch(input_rx))),
+        ))
+    }
+}
+
+/// The events a transform outputs, split between its default output and its
+/// named outputs.
+#[derive(Debug, Default)]
+pub struct TransformOutputs {
+    pub primary: Vec<Event>,
+    pub named: Vec<(String, Event)>,
+}
+
+impl TransformOutputs {
+    /// Sends `event` to the default output.
+    pub fn push(&mut self, event: Event) {
+        self.primary.push(event);
+    }
+
+    /// Sends `event` to the named output `output`.
+    pub fn push_named(&mut self, output: &str, event: Event) {
+        self.named.push((output.to_string(), event));
+    }
+
+    /// The batches of events for each output, `None` being the default output.
+    fn into_batches(self) -> Vec<(Option<String>, Vec<Event>)> {
+        let mut batches = Vec::new();
+        if !self.primary.is_empty() {
+            batches.push((None, self.primary));
+        }
+        for (output, event) in self.named {
+            match batches
+                .iter_mut()
+                .find(|(name, _)| name.as_ref() == Some(&output))
+            {
+                Some((_, events)) => events.push(event),
+                None => batches.push((Some(output), vec![event])),
+            }
+        }
+        batches
+    }
+}
+
+/// Runs a transform with named outputs over batches of events. Every batch
+/// comes with the name of the output it goes to, `None` being the default
+/// output.
+pub fn transform_stream_with_outputs(
+    mut transform: Box<dyn Transform>,
+    input_rx: Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send>,
+) -> Box<dyn Stream<Item = (Option<String>, Vec<Event>), Error = ()> + Send> {
+    Box::new(
+        input_rx
+            .map(move |events| {
+                let mut outputs = TransformOutputs::default();
+                for event in events {
+                    transform.transform_into_outputs(&mut outputs, event);
+                }
+                futures01::stream::iter_ok(outputs.into_batches())
+            })
+            .flatten(),
+    )
 }
 
 #[derive(Debug, Snafu)]
@@ -0,0 +1,123 @@
+//! Running several instances of a stateless transform in parallel.
+//!
+//! Each batch of events is handed to its own task, which borrows an idle
+//! instance of the transform for as long as it runs. At most as many batches
+//! as there are instances are in flight at once, so an instance is always free
+//! for the next one. When the order is preserved, the output of a batch waits
+//! for the batches before it.
+
+use super::{Transform, TransformOutputs};
+use crate::{event::Event, internal_events::TransformTaskFailed};
+use futures::{FutureExt, TryFutureExt};
+use futures01::{stream, Stream};
+use std::sync::{Arc, Mutex};
+
+/// Runs `instances` of a transform over batches of events, each batch coming
+/// out with the name of the output it goes to, `None` being the default output.
+pub fn transform_stream(
+    instances: Vec<Box<dyn Transform>>,
+    input_rx: Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send>,
+    preserve_order: bool,
+) -> Box<dyn Stream<Item = (Option<String>, Vec<Event>), Error = ()> + Send> {
+    let concurrency = instances.len();
+    let instances = Arc::new(Mutex::new(instances));
+
+    let tasks = input_rx.map(move |events| {
+        let instances = Arc::clone(&instances);
+        tokio::spawn(async move {
+            let mut transform = instances
+                .lock()
+                .unwrap()
+                .pop()
+                .expect("More batches in flight than transform instances.");
+            let mut outputs = TransformOutputs::default();
+            for event in events {
+                transform.transform_into_outputs(&mut outputs, event);
+            }
+            instances.lock().unwrap().push(transform);
+            outputs.into_batches()
+        })
+        .map_err(|error| emit!(TransformTaskFailed { error }))
+        .boxed()
+        .compat()
+    });
+
+    let outputs: Box<dyn Stream<Item = _, Error = ()> + Send> = if preserve_order {
+        Box::new(tasks.buffered(concurrency))
+    } else {
+        Box::new(tasks.buffer_unordered(concurrency))
+    };
+    Box::new(outputs.map(stream::iter_ok).flatten())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::compat::Future01CompatExt;
+    use futures01::Future;
+
+    /// Tags events with the name of the instance, after some busy work for the
+    /// batches to finish out of order.
+    struct Tag(String);
+
+    impl Transform for Tag {
+        fn transform(&mut self, mut event: Event) -> Option<Event> {
+            let log = event.as_mut_log();
+            let message = log["message"].to_string_lossy();
+            let n: u64 = message.parse().unwrap();
+            std::thread::sleep(std::time::Duration::from_micros((n * 7919) % 100));
+            log.insert("instance", self.0.clone());
+            Some(event)
+        }
+    }
+
+    fn instances(n: usize) -> Vec<Box<dyn Transform>> {
+        (0..n)
+            .map(|i| Box::new(Tag(i.to_string())) as Box<dyn Transform>)
+            .collect()
+    }
+
+    fn input(n: usize) -> Box<dyn Stream<Item = Vec<Event>, Error = ()> + Send> {
+        let events = (0..n)
+            .map(|i| Event::from(i.to_string()))
+            .collect::<Vec<_>>();
+        Box::new(stream::iter_ok(
+            events.chunks(10).map(<[Event]>::to_vec).collect::<Vec<_>>(),
+        ))
+    }
+
+    fn messages(batches: Vec<(Option<String>, Vec<Event>)>) -> Vec<usize> {
+        batches
+            .into_iter()
+            .flat_map(|(output, events)| {
+                assert_eq!(output, None);
+                events
+            })
+            .map(|event| event.as_log()["message"].to_string_lossy().parse().unwrap())
+            .collect()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn preserves_order() {
+        let output = transform_stream(instances(4), input(1000), true)
+            .collect()
+            .compat()
+            .await
+            .unwrap();
+
+        assert_eq!(messages(output), (0..1000).collect::<Vec<_>>());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn processes_everything_without_order() {
+        let output = transform_stream(instances(4), input(1000), false)
+            .collect()
+            .compat()
+            .await
+            .unwrap();
+
+        let mut messages = messages(output);
+        messages.sort();
+        assert_eq!(messages, (0..1000).collect::<Vec<_>>());
+    }
+}
@@ -291,6 +291,8 @@ mod test {
         event::Value,
         Event,
     };
+    use futures::compat::Future01CompatExt;
+    use futures01::{stream, Stream};
     use serde_json::json;
 
     #[test]
@@ -364,6 +366,46 @@ group_by = [ "request_id" ]
         assert_eq!(outputs.first().unwrap().as_log()["counter"], Value::from(7));
     }
 
+    #[tokio::test]
+    async fn transform_batches_flushes_at_end_of_input() {
+        let reduce = toml::from_str::<ReduceConfig>(
+            r#"
+group_by = [ "request_id" ]
+
+[ends_when]
+  "test_end.exists" = true
+"#,
+        )
+        .unwrap()
+        .build(TransformContext::new_test())
+        .await
+        .unwrap();
+
+        let batches = (1..=3)
+            .map(|counter| {
+                let mut e = Event::from(format!("test message {}", counter));
+                e.as_mut_log().insert("counter", counter);
+                e.as_mut_log().insert("request_id", "1");
+                vec![e]
+            })
+            .collect::<Vec<_>>();
+
+        // Only the `transform_stream` of reduce flushes what is left when the
+        // input ends.
+        let outputs = reduce
+            .transform_batches(Box::new(stream::iter_ok::<_, ()>(batches)))
+            .collect()
+            .compat()
+            .await
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].as_log()["counter"], Value::from(6));
+    }
+
     #[tokio::test]
     async fn reduce_merge_strategies() {
         let mut reduce = toml::from_str::<ReduceConfig>(
@@ -58,6 +58,22 @@ impl TransformConfig for RegexParserConfig {
     fn transform_type(&self) -> &'static str {
         "regex_parser"
     }
+
+    fn fields(&self) -> Option<Vec<String>> {
+        if let Some(target_field) = &self.target_field {
+            return Some(vec![target_field.clone()]);
+        }
+        let mut fields = Vec::new();
+        for pattern in self.regex.iter().chain(&self.patterns) {
+            let regex = Regex::new(pattern).ok()?;
+            fields.extend(regex.capture_names().flatten().map(String::from));
+        }
+        Some(fields)
+    }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct RegexParser {
@@ -1,4 +1,4 @@
-use super::Transform;
+use super::{Transform, TransformOutputs};
 use crate::{
     config::{DataType, TransformConfig, TransformContext, TransformDescription},
     event::Event,
@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 pub struct RemapConfig {
     pub mapping: String,
     pub drop_on_err: bool,
+    pub reroute_errors: bool,
 }
 
 inventory::submit! {
@@ -39,12 +40,25 @@ impl TransformConfig for RemapConfig {
     fn transform_type(&self) -> &'static str {
         "remap"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
+
+    fn named_outputs(&self) -> Vec<String> {
+        if self.reroute_errors {
+            vec!["errors".into()]
+        } else {
+            Vec::new()
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct Remap {
     mapping: Mapping,
     drop_on_err: bool,
+    reroute_errors: bool,
 }
 
 impl Remap {
@@ -52,26 +66,36 @@ impl Remap {
         Ok(Remap {
             mapping: parse_mapping(&config.mapping)?,
             drop_on_err: config.drop_on_err,
+            reroute_errors: config.reroute_errors,
         })
     }
 }
 
 impl Transform for Remap {
-    fn transform(&mut self, mut event: Event) -> Option<Event> {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        let mut outputs = TransformOutputs::default();
+        self.transform_into_outputs(&mut outputs, event);
+        outputs.primary.pop()
+    }
+
+    fn transform_into_outputs(&mut self, outputs: &mut TransformOutputs, mut event: Event) {
         emit!(RemapEventProcessed);
 
         if let Err(error) = self.mapping.execute(&mut event) {
             emit!(RemapFailedMapping {
-                event_dropped: self.drop_on_err,
+                event_dropped: self.drop_on_err && !self.reroute_errors,
                 error
             });
 
+            if self.reroute_errors {
+                return outputs.push_named("errors", event);
+            }
             if self.drop_on_err {
-                return None;
+                return;
             }
         }
 
-        Some(event)
+        outputs.push(event);
     }
 }
 
@@ -103,6 +127,7 @@ mod tests {
 "#
             .to_string(),
             drop_on_err: true,
+            reroute_errors: false,
         };
         let mut tform = Remap::new(conf).unwrap();
 
@@ -113,4 +138,24 @@ mod tests {
         assert_eq!(get_field_string(&result, "bar"), "baz");
         assert_eq!(get_field_string(&result, "copy"), "buz");
     }
+
+    #[test]
+    fn check_remap_reroute_errors() {
+        let conf = RemapConfig {
+            mapping: ".bar = parse_json(.foo)".to_string(),
+            drop_on_err: true,
+            reroute_errors: true,
+        };
+        let mut tform = Remap::new(conf).unwrap();
+        let mut outputs = TransformOutputs::default();
+
+        let mut event = Event::from("not json");
+        event.as_mut_log().insert("foo", "{");
+        tform.transform_into_outputs(&mut outputs, event);
+
+        assert!(outputs.primary.is_empty());
+        assert_eq!(outputs.named.len(), 1);
+        assert_eq!(outputs.named[0].0, "errors");
+        assert_eq!(get_field_string(&outputs.named[0].1, "message"), "not json");
+    }
 }
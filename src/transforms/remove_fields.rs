@@ -55,6 +55,10 @@ impl TransformConfig for RemoveFieldsConfig {
     fn transform_type(&self) -> &'static str {
         "remove_fields"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 impl RemoveFields {
@@ -44,6 +44,10 @@ impl TransformConfig for RemoveTagsConfig {
     fn transform_type(&self) -> &'static str {
         "remove_tags"
     }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 impl RemoveTags {
@@ -60,6 +60,14 @@ impl TransformConfig for SplitConfig {
     fn transform_type(&self) -> &'static str {
         "split"
     }
+
+    fn fields(&self) -> Option<Vec<String>> {
+        Some(self.field_names.clone())
+    }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct Split {
@@ -58,6 +58,14 @@ impl TransformConfig for TokenizerConfig {
     fn transform_type(&self) -> &'static str {
         "tokenizer"
     }
+
+    fn fields(&self) -> Option<Vec<String>> {
+        Some(self.field_names.clone())
+    }
+
+    fn stateless(&self) -> bool {
+        true
+    }
 }
 
 pub struct Tokenizer {
@@ -1,9 +1,14 @@
 use crate::{
-    config::{self, Config, ConfigDiff},
+    config::{
+        self,
+        lint::{Finding, Severity},
+        Config, ConfigDiff,
+    },
     topology::{self, builder::Pieces},
 };
 use colored::*;
 use exitcode::ExitCode;
+use serde::Serialize;
 use std::{fmt, fs::remove_dir_all, path::PathBuf};
 use structopt::StructOpt;
 
@@ -20,22 +25,67 @@ pub struct Opts {
     #[structopt(short, long)]
     deny_warnings: bool,
 
+    /// Print the results as `text`, or as a `json` report for CI.
+    #[structopt(long, default_value = "text", possible_values = &["text", "json"])]
+    format: Format,
+
+    /// Read the config files as `toml`, `yaml` or `json`, instead of in the
+    /// format given by their extensions.
+    #[structopt(long, possible_values = &["toml", "yaml", "json"])]
+    config_format: Option<config::Format>,
+
+    /// Any number of config directories, each with its components in files of
+    /// its `sources`, `transforms` and `sinks` directories.
+    #[structopt(long = "config-dir")]
+    config_dirs: Vec<PathBuf>,
+
+    /// Fail validation when the config references environment variables that
+    /// aren't set.
+    #[structopt(long)]
+    strict_env_vars: bool,
+
     /// Any number of Vector config files to validate. If none are specified the
     /// default config path `/etc/vector/vector.toml` will be targeted.
     paths: Vec<PathBuf>,
 }
 
-/// Performs topology, component, and health checks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            s => Err(format!(
+                "{} is not a valid option, expected `text` or `json`",
+                s
+            )),
+        }
+    }
+}
+
+/// Performs topology, lint, component, and health checks.
 pub async fn validate(opts: &Opts, color: bool) -> ExitCode {
-    let mut fmt = Formatter::new(color);
+    let mut fmt = Formatter::new(color, opts.format);
 
     let mut validated = true;
 
     let mut config = match validate_config(opts, &mut fmt) {
         Some(config) => config,
-        None => return exitcode::CONFIG,
+        None => {
+            fmt.finish(false);
+            return exitcode::CONFIG;
+        }
     };
 
+    validated &= validate_lints(&config, &mut fmt);
+
     if !opts.no_environment {
         if let Some(tmp_directory) = create_tmp_directory(&mut config, &mut fmt) {
             validated &= validate_environment(&config, &mut fmt).await;
@@ -45,8 +95,13 @@ pub async fn validate(opts: &Opts, color: bool) -> ExitCode {
         }
     }
 
+    if opts.deny_warnings && fmt.warnings > 0 {
+        fmt.error("Warnings were found, and are denied");
+        validated = false;
+    }
+
+    fmt.finish(validated);
     if validated {
-        fmt.validated();
         exitcode::OK
     } else {
         exitcode::CONFIG
@@ -57,26 +112,42 @@ pub async fn validate(opts: &Opts, color: bool) -> ExitCode {
 /// Err Some contains only successfully validated configs.
 fn validate_config(opts: &Opts, fmt: &mut Formatter) -> Option<Config> {
     // Prepare paths
-    let paths = if let Some(paths) = config::process_paths(&opts.paths) {
+    let paths = if let Some(paths) = config::process_paths_with_dirs(&opts.paths, &opts.config_dirs)
+    {
         paths
     } else {
         fmt.error("No config file paths");
         return None;
     };
 
-    match config::load_from_paths(&paths) {
+    if opts.strict_env_vars {
+        config::set_strict_env_vars(true);
+    }
+
+    let loaded = paths.iter().chain(&opts.config_dirs).collect::<Vec<_>>();
+    match config::load_from_paths_and_dirs(&paths, &opts.config_dirs, opts.config_format) {
         Ok(config) => {
-            fmt.success(format!("Loaded {:?}", &paths));
+            fmt.success(format!("Loaded {:?}", loaded));
             Some(config)
         }
         Err(errors) => {
-            fmt.title(format!("Failed to load {:?}", paths));
+            fmt.title(format!("Failed to load {:?}", loaded));
             fmt.sub_error(errors);
             None
         }
     }
 }
 
+/// Ok unless some lint found an error.
+fn validate_lints(config: &Config, fmt: &mut Formatter) -> bool {
+    let findings = config::lint::lint(config);
+    let validated = findings
+        .iter()
+        .all(|finding| finding.severity != Severity::Error);
+    fmt.lints(findings);
+    validated
+}
+
 async fn validate_environment(config: &Config, fmt: &mut Formatter) -> bool {
     let diff = ConfigDiff::initial(config);
 
@@ -187,10 +258,33 @@ struct Formatter {
     error_intro: String,
     warning_intro: String,
     success_intro: String,
+    /// Number of warnings, for `--deny-warnings`
+    warnings: usize,
+    /// Everything that would be printed, kept to be printed as JSON at the end
+    report: Option<Report>,
+}
+
+#[derive(Serialize, Default)]
+struct Report {
+    validated: bool,
+    messages: Vec<Message>,
+    lints: Vec<Finding>,
+    /// Title of the messages being added
+    #[serde(skip)]
+    section: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    level: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    section: Option<String>,
+    message: String,
 }
 
 impl Formatter {
-    fn new(color: bool) -> Self {
+    fn new(color: bool, format: Format) -> Self {
+        let color = color && format == Format::Text;
         Self {
             max_line_width: 0,
             print_space: false,
@@ -210,6 +304,27 @@ impl Formatter {
                 "√".to_owned()
             },
             color,
+            warnings: 0,
+            report: if format == Format::Json {
+                Some(Report::default())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Prints the report, or the final confirmation if validation succeeded.
+    fn finish(&mut self, validated: bool) {
+        match self.report.take() {
+            Some(mut report) => {
+                report.validated = validated;
+                match serde_json::to_string_pretty(&report) {
+                    Ok(report) => println!("{}", report),
+                    Err(error) => eprintln!("Failed to write the report: {}", error),
+                }
+            }
+            None if validated => self.validated(),
+            None => {}
         }
     }
 
@@ -233,21 +348,71 @@ impl Formatter {
 
     /// Standalone line
     fn success(&mut self, msg: impl AsRef<str>) {
-        self.print(format!("{} {}\n", self.success_intro, msg.as_ref()))
+        if !self.report("success", msg.as_ref()) {
+            self.print(format!("{} {}\n", self.success_intro, msg.as_ref()))
+        }
     }
 
     /// Standalone line
     fn warning(&mut self, warning: impl AsRef<str>) {
-        self.print(format!("{} {}\n", self.warning_intro, warning.as_ref()))
+        self.warnings += 1;
+        if !self.report("warning", warning.as_ref()) {
+            self.print(format!("{} {}\n", self.warning_intro, warning.as_ref()))
+        }
     }
 
     /// Standalone line
     fn error(&mut self, error: impl AsRef<str>) {
-        self.print(format!("{} {}\n", self.error_intro, error.as_ref()))
+        if !self.report("error", error.as_ref()) {
+            self.print(format!("{} {}\n", self.error_intro, error.as_ref()))
+        }
+    }
+
+    /// Adds a message to the report, if there's one.
+    fn report(&mut self, level: &'static str, message: &str) -> bool {
+        match self.report.as_mut() {
+            Some(report) => {
+                report.messages.push(Message {
+                    level,
+                    section: report.section.clone(),
+                    message: message.to_owned(),
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The findings of the lints, under their own title.
+    fn lints(&mut self, findings: Vec<Finding>) {
+        self.warnings += findings
+            .iter()
+            .filter(|finding| finding.severity == Severity::Warning)
+            .count();
+        if let Some(report) = self.report.as_mut() {
+            report.lints = findings;
+        } else if findings.is_empty() {
+            self.success("Lints");
+        } else {
+            self.title("Lints");
+            for finding in findings {
+                let intro = match finding.severity {
+                    Severity::Warning => &self.warning_intro,
+                    Severity::Error => &self.error_intro,
+                };
+                let line = format!("{} [{}] {}\n", intro, finding.lint, finding.message);
+                self.print(line);
+            }
+            self.space();
+        }
     }
 
     /// Marks sub
     fn title(&mut self, title: impl AsRef<str>) {
+        if let Some(report) = self.report.as_mut() {
+            report.section = Some(title.as_ref().to_owned());
+            return;
+        }
         self.space();
         self.print(format!(
             "{}\n{:-<width$}\n",
@@ -262,6 +427,17 @@ impl Formatter {
     where
         I::Item: fmt::Display,
     {
+        if let Some(report) = self.report.as_mut() {
+            let section = report.section.take();
+            report
+                .messages
+                .extend(errors.into_iter().map(|error| Message {
+                    level: "error",
+                    section: section.clone(),
+                    message: error.to_string(),
+                }));
+            return;
+        }
         self.sub(self.error_intro.clone(), errors)
     }
 
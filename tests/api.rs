@@ -14,19 +14,26 @@ mod tests {
         sync::Once,
         time::{Duration, Instant},
     };
-    use tokio::{select, sync::oneshot};
+    use tokio::{
+        select,
+        sync::{mpsc, oneshot},
+    };
     use url::Url;
     use vector::{
         self,
         api::{self, Server},
         config::Config,
         internal_events::{emit, GeneratorEventProcessed, Heartbeat},
+        sinks::util::http::Auth,
         test_util::{next_addr, retry_until},
+        tls::{TlsConfig, TlsOptions},
+        topology::Control,
     };
     use vector_api_client::{
-        connect_subscription_client,
+        connect_subscription_client, connect_subscription_client_with_auth,
+        connect_subscription_client_with_tls,
         gql::{HealthQueryExt, HealthSubscriptionExt, MetricsSubscriptionExt},
-        Client, SubscriptionClient,
+        Auth as ClientAuth, Client, SubscriptionClient, Tls,
     };
 
     static METRICS_INIT: Once = Once::new();
@@ -70,7 +77,7 @@ mod tests {
     // Starts and returns the server
     fn start_server() -> Server {
         let config = api_enabled_config();
-        api::Server::start(&config)
+        api::Server::start(&config, mpsc::unbounded_channel().0).unwrap()
     }
 
     fn make_client(addr: SocketAddr) -> Client {
@@ -85,7 +92,7 @@ mod tests {
         let addr = config.api.bind.unwrap();
         let url = format!("http://{}:{}/{}", addr.ip(), addr.port(), url);
 
-        let _server = api::Server::start(&config);
+        let _server = api::Server::start(&config, mpsc::unbounded_channel().0).unwrap();
 
         // Build the request
         let client = reqwest::Client::new();
@@ -312,4 +319,156 @@ mod tests {
             new_heartbeat_subscription(&client, 3, 500),
         };
     }
+
+    #[tokio::test]
+    /// Tests that mutations are passed on to the topology only with the credentials of `auth`
+    async fn api_graphql_mutations_require_auth() {
+        let mut config = api_enabled_config();
+        let addr = config.api.bind.unwrap();
+        config.api.auth = Some(Auth::Bearer {
+            token: "s3cr3t".to_owned(),
+        });
+
+        let (control_tx, mut control_rx) = mpsc::unbounded_channel();
+        let _server = api::Server::start(&config, control_tx).unwrap();
+        tokio::spawn(async move {
+            while let Some((control, reply)) = control_rx.recv().await {
+                let result = match control {
+                    Control::PauseSource(name) if name == "in1" => Ok(()),
+                    _ => Err("There is no source.".to_owned()),
+                };
+                let _ = reply.send(result);
+            }
+        });
+
+        let url = format!("http://{}/graphql", addr);
+        let client = reqwest::Client::new();
+        let pause = |name: &str, token: Option<&str>| {
+            let mut request = client.post(&url).json(&serde_json::json!({
+                "query": format!("mutation {{ pauseSource(name: {:?}) }}", name)
+            }));
+            if let Some(token) = token {
+                request = request.bearer_auth(token);
+            }
+            async move { request.send().await.unwrap() }
+        };
+        retry_until(
+            || client.get(&format!("http://{}/health", addr)).send(),
+            Duration::from_millis(100),
+            Duration::from_secs(10),
+        )
+        .await;
+
+        let res = pause("in1", Some("s3cr3t"))
+            .await
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(res["data"]["pauseSource"], true);
+        assert!(res["errors"].is_null());
+
+        let res = pause("in2", Some("s3cr3t"))
+            .await
+            .json::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert!(res["data"].is_null());
+        assert!(res["errors"].is_array());
+
+        // Requests without the credentials aren't served at all
+        for token in &[None, Some("wrong")] {
+            let res = pause("in1", *token).await;
+            assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+            assert!(res.headers().get("www-authenticate").is_none());
+        }
+    }
+
+    #[tokio::test]
+    /// Tests that queries and subscriptions are served only with the credentials of `auth`
+    async fn api_graphql_auth() {
+        let mut config = api_enabled_config();
+        config.api.auth = Some(Auth::Basic {
+            user: "vector".to_owned(),
+            password: "s3cr3t".to_owned(),
+        });
+        let server = api::Server::start(&config, mpsc::unbounded_channel().0).unwrap();
+
+        let auth = ClientAuth::Basic {
+            user: "vector".to_owned(),
+            password: "s3cr3t".to_owned(),
+        };
+        let res = make_client(server.addr())
+            .with_auth(auth.clone())
+            .health_query()
+            .await
+            .unwrap();
+        assert!(res.data.unwrap().health);
+        assert!(make_client(server.addr()).health_query().await.is_err());
+
+        // Clients are challenged for the basic auth credentials
+        let res = reqwest::Client::new()
+            .post(&format!("http://{}/graphql", server.addr()))
+            .json(&serde_json::json!({ "query": "{ health }" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            res.headers()["www-authenticate"],
+            r#"Basic realm="Vector API""#
+        );
+
+        let url = Url::parse(&*format!("ws://{}/graphql", server.addr())).unwrap();
+        assert!(connect_subscription_client(&url).await.is_err());
+        let client = connect_subscription_client_with_auth(&url, &auth)
+            .await
+            .unwrap();
+        new_heartbeat_subscription(&client, 1, 500).await;
+    }
+
+    #[tokio::test]
+    /// Tests that queries and subscriptions are served over TLS, to clients trusting the CA of
+    /// the server certificate
+    async fn api_graphql_tls() {
+        let mut config = api_enabled_config();
+        config.api.tls = Some(TlsConfig {
+            enabled: Some(true),
+            options: TlsOptions {
+                crt_file: Some("tests/data/localhost.crt".into()),
+                key_file: Some("tests/data/localhost.key".into()),
+                ..Default::default()
+            },
+        });
+        let server = api::Server::start(&config, mpsc::unbounded_channel().0).unwrap();
+        let port = server.addr().port();
+
+        let tls = Tls {
+            ca_file: Some("tests/data/Vector_CA.crt".into()),
+            insecure: false,
+        };
+        let url = Url::parse(&*format!("https://localhost:{}/graphql", port)).unwrap();
+        let res = Client::new(url.clone())
+            .with_tls(tls.clone())
+            .health_query()
+            .await
+            .unwrap();
+        assert!(res.data.unwrap().health);
+        assert!(Client::new(url).health_query().await.is_err());
+
+        let url = Url::parse(&*format!("wss://localhost:{}/graphql", port)).unwrap();
+        assert!(connect_subscription_client(&url).await.is_err());
+        let client = connect_subscription_client_with_tls(&url, None, &tls)
+            .await
+            .unwrap();
+        new_heartbeat_subscription(&client, 1, 500).await;
+
+        // Without verification, the CA isn't needed
+        let insecure = Tls {
+            ca_file: None,
+            insecure: true,
+        };
+        assert!(connect_subscription_client_with_tls(&url, None, &insecure)
+            .await
+            .is_ok());
+    }
 }
@@ -1,5 +1,3 @@
-#![cfg(feature = "leveldb")]
-
 use futures::{
     compat::{Future01CompatExt, Sink01CompatExt},
     SinkExt, StreamExt,
@@ -54,6 +52,7 @@ fn test_buffering() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            max_age: None,
         };
         config.global.data_dir = Some(data_dir.clone());
         config.build().unwrap()
@@ -104,6 +103,7 @@ fn test_buffering() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            max_age: None,
         };
         config.global.data_dir = Some(data_dir);
         config.build().unwrap()
@@ -91,3 +91,35 @@ fn validate_cleanup() {
             .collect::<Vec<_>>()
     );
 }
+
+#[test]
+fn validate_denies_unconsumed_transforms() {
+    let dir = support::create_directory();
+    let config = support::create_file(&format!(
+        r#"{}
+[transforms.unused]
+    inputs = ["in"]
+    type = "add_fields"
+    fields.key = "value"
+"#,
+        source_config(r#"type = "stdin""#)
+    ));
+
+    let validate = |arguments: &[&str]| {
+        Command::cargo_bin("vector")
+            .unwrap()
+            .arg("validate")
+            .arg("--no-environment")
+            .args(arguments)
+            .arg(&config)
+            .env("VECTOR_DATA_DIR", &dir)
+            .output()
+            .expect("Failed to execute process")
+    };
+
+    assert!(validate(&[]).status.success());
+    assert!(!validate(&["--deny-warnings"]).status.success());
+
+    let report = String::from_utf8(validate(&["--format", "json"]).stdout).unwrap();
+    assert!(report.contains("unconsumed"));
+}
@@ -4,7 +4,7 @@ use vector::{
 };
 
 async fn load(config: &str) -> Result<Vec<String>, Vec<String>> {
-    match config::load_from_str(config) {
+    match config::load_from_str(config, config::Format::Toml) {
         Ok(c) => {
             let diff = ConfigDiff::initial(&c);
             match (
@@ -194,6 +194,45 @@ async fn nonexistant_input() {
     );
 }
 
+#[cfg(all(
+    feature = "sources-socket",
+    feature = "transforms-sampler",
+    feature = "sinks-socket"
+))]
+#[tokio::test]
+async fn concurrency_on_stateful_transform() {
+    let err = load(
+        r#"
+        [sources.in]
+        type = "socket"
+        mode = "tcp"
+        address = "127.0.0.1:1235"
+
+        [transforms.sampler]
+        type = "sampler"
+        inputs = ["in"]
+        concurrency = 4
+        rate = 10
+
+        [sinks.out]
+        type = "socket"
+        mode = "tcp"
+        inputs = ["sampler"]
+        encoding = "text"
+        address = "127.0.0.1:9999"
+      "#,
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        vec![
+            "Transform \"sampler\" of type \"sampler\" keeps state between events and can't run with a concurrency above 1."
+        ]
+    );
+}
+
 #[cfg(all(
     feature = "sources-socket",
     feature = "transforms-sampler",
@@ -14,7 +14,12 @@ use std::{
 };
 
 use tokio::time::{delay_for, Duration};
-use vector::{config::Config, event::Event, test_util::start_topology, topology};
+use vector::{
+    config::Config,
+    event::Event,
+    test_util::{collect_ready, start_topology},
+    topology::{self, tap::Tap},
+};
 
 fn basic_config() -> Config {
     let mut config = Config::builder();
@@ -116,6 +121,64 @@ async fn topology_source_and_sink() {
     assert_eq!(vec![event], res);
 }
 
+#[tokio::test]
+async fn topology_pause_and_resume_source() {
+    let (in1, source1) = source();
+    let (mut out1, sink1) = sink(10);
+
+    let mut config = Config::builder();
+    config.add_source("in1", source1);
+    config.add_sink("out1", &["in1"], sink1);
+
+    let (mut topology, _crash) = start_topology(config.build().unwrap(), false).await;
+    assert!(topology.pause_source("out1").is_err());
+    assert!(topology.flush_sink("in1").is_err());
+    topology.pause_source("in1").unwrap();
+
+    let event = Event::from("this");
+    in1.send(event.clone()).compat().await.unwrap();
+    delay_for(Duration::from_millis(50)).await;
+    assert_eq!(collect_ready(out1.by_ref()).await.unwrap(), vec![]);
+
+    topology.resume_source("in1").unwrap();
+    topology.flush_sink("out1").unwrap();
+    topology.stop().compat().await.unwrap();
+
+    let res = out1.collect().compat().await.unwrap();
+
+    assert_eq!(vec![event], res);
+}
+
+#[tokio::test]
+async fn topology_tap() {
+    let (in1, source1) = source();
+    let (out1, sink1) = sink(10);
+
+    let mut config = Config::builder();
+    config.add_source("in1", source1);
+    config.add_transform("t1", &["in1"], transform(" transformed", 0.0));
+    config.add_sink("out1", &["t1"], sink1);
+
+    let (mut topology, _crash) = start_topology(config.build().unwrap(), false).await;
+    let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+    let tap = Tap::new(&["t*".to_owned()], tx).unwrap();
+    let id = tap.id();
+    topology.tap(tap);
+
+    let event = Event::from("this");
+    in1.send(event.clone()).compat().await.unwrap();
+    let tapped = rx.recv().await.unwrap();
+    assert_eq!(tapped.output, "t1");
+    assert_eq!(into_message(tapped.event), "this transformed");
+
+    topology.untap(id);
+    topology.stop().compat().await.unwrap();
+    assert!(rx.recv().await.is_none());
+
+    let res = out1.collect().compat().await.unwrap();
+    assert_eq!(res.len(), 1);
+}
+
 #[tokio::test]
 async fn topology_multiple_sources() {
     let (in1, source1) = source();
@@ -172,6 +235,50 @@ async fn topology_multiple_sinks() {
     assert_eq!(vec![event], res2);
 }
 
+#[cfg(feature = "transforms-json_parser")]
+#[tokio::test]
+async fn topology_named_transform_outputs() {
+    use vector::transforms::json_parser::JsonParserConfig;
+
+    let (in1, source1) = source();
+    let (out1, sink1) = sink(10);
+    let (out2, sink2) = sink(10);
+
+    let mut config = Config::builder();
+    config.add_source("in1", source1);
+    config.add_transform(
+        "parse",
+        &["in1"],
+        JsonParserConfig {
+            reroute_errors: true,
+            ..Default::default()
+        },
+    );
+    config.add_sink("out1", &["parse"], sink1);
+    config.add_sink("errors", &["parse.errors"], sink2);
+
+    let (topology, _crash) = start_topology(config.build().unwrap(), false).await;
+
+    let invalid = Event::from("not json");
+    let in1 = in1
+        .send(Event::from(r#"{"message": "parsed"}"#))
+        .compat()
+        .await
+        .unwrap();
+    in1.send(invalid.clone()).compat().await.unwrap();
+
+    topology.stop().compat().await.unwrap();
+
+    let res1 = out1.collect().compat().await.unwrap();
+    let res2 = out2.collect().compat().await.unwrap();
+
+    assert_eq!(
+        vec!["parsed".to_string()],
+        res1.into_iter().map(into_message).collect::<Vec<_>>()
+    );
+    assert_eq!(vec![invalid], res2);
+}
+
 #[tokio::test]
 async fn topology_transform_chain() {
     let (in1, source1) = source();